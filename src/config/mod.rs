@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Serialize, Deserialize};
 
 pub fn load_config(cfg_path: String) -> Config {
@@ -27,5 +29,101 @@ pub struct CfgNetwork {
 pub struct CfgGameplay {
     pub starting_system: String,
     pub starting_station: String,
-    pub starting_money: i64
-}
\ No newline at end of file
+    pub starting_money: i64,
+    #[serde(default = "default_starting_ship_class")]
+    pub starting_ship_class: String,
+    #[serde(default = "default_broker_fee")]
+    pub broker_fee: f64, // fraction of an order's value charged when it is placed
+    #[serde(default = "default_sales_tax")]
+    pub sales_tax: f64, // fraction of a sale's value taken from the seller
+    #[serde(default = "default_market_sink_account")]
+    pub market_sink_account: String, // bank account that fees and taxes are paid in to
+    #[serde(default = "default_order_modify_cooldown")]
+    pub order_modify_cooldown_s: i64, // time between price changes on the same order, 0 for none
+    #[serde(default = "default_wreck_lifetime")]
+    pub wreck_lifetime_s: f64, // how long a wreck stays in space before it despawns with whatever is left in it
+    #[serde(default = "default_jettison_lifetime")]
+    pub jettison_lifetime_s: f64, // how long a jettisoned container stays in space if nobody empties it
+    #[serde(default = "default_loot_access_range")]
+    pub loot_access_range_m: f64, // how close a ship has to be to look in to or take from a wreck or jettisoned container
+    #[serde(default = "default_insurance_premium_rate")]
+    pub insurance_premium_rate: f64, // fraction of the payout charged up front for a contract
+    #[serde(default = "default_insurance_duration_days")]
+    pub insurance_duration_days: i64,
+    #[serde(default = "default_max_production_runs")]
+    pub max_production_runs: u32, // per job
+    #[serde(default = "default_legacy_ship_classes")]
+    pub legacy_ship_classes: HashMap<String, String>, // class names saved before classes were loaded from assets, mapped to the class that replaces them
+    #[serde(default = "default_max_shared_bookmarks")]
+    pub max_shared_bookmarks: usize, // how many bookmarks shared by other players one player can be holding
+}
+
+fn default_starting_ship_class() -> String { String::from("rookie_frigate") }
+fn default_broker_fee() -> f64 { 0.02 }
+fn default_sales_tax() -> f64 { 0.03 }
+fn default_market_sink_account() -> String { String::from("$market") }
+fn default_order_modify_cooldown() -> i64 { 300 }
+fn default_wreck_lifetime() -> f64 { 1800.0 }
+fn default_jettison_lifetime() -> f64 { 3600.0 }
+fn default_loot_access_range() -> f64 { 2500.0 }
+fn default_insurance_premium_rate() -> f64 { 0.1 }
+fn default_insurance_duration_days() -> i64 { 30 }
+fn default_max_production_runs() -> u32 { 1000 }
+fn default_legacy_ship_classes() -> HashMap<String, String> { HashMap::from([(String::from("Test Ship"), String::from("rookie_frigate"))]) }
+fn default_max_shared_bookmarks() -> usize { 200 }
+
+impl CfgGameplay {
+    pub fn broker_fee_for(&self, value: i64) -> i64 {
+        (value as f64 * self.broker_fee).ceil().max(0.0) as i64
+    }
+
+    pub fn sales_tax_for(&self, value: i64) -> i64 {
+        (value as f64 * self.sales_tax).ceil().max(0.0) as i64
+    }
+
+    pub fn insurance_premium_for(&self, payout: i64) -> i64 {
+        (payout as f64 * self.insurance_premium_rate).ceil().max(0.0) as i64
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gameplay(broker_fee: f64, sales_tax: f64) -> CfgGameplay {
+        let mut cfg: CfgGameplay = serde_json::from_str(r#"{ "starting_system": "sys", "starting_station": "station", "starting_money": 0 }"#).expect("Could not parse gameplay config");
+        cfg.broker_fee = broker_fee;
+        cfg.sales_tax = sales_tax;
+        cfg
+    }
+
+    #[test]
+    fn fees_round_up() {
+        let cfg = gameplay(0.02, 0.03);
+        assert_eq!(cfg.broker_fee_for(1000), 20);
+        assert_eq!(cfg.broker_fee_for(1001), 21);
+        assert_eq!(cfg.broker_fee_for(1), 1);
+        assert_eq!(cfg.sales_tax_for(1000), 30);
+        assert_eq!(cfg.sales_tax_for(1001), 31);
+        assert_eq!(cfg.broker_fee_for(0), 0);
+        assert_eq!(cfg.sales_tax_for(0), 0);
+    }
+
+    #[test]
+    fn negative_values_are_never_charged() {
+        let cfg = gameplay(0.02, 0.03);
+        assert_eq!(cfg.broker_fee_for(-1000), 0);
+        assert_eq!(cfg.sales_tax_for(-1000), 0);
+    }
+
+    #[test]
+    fn configs_without_a_starting_ship_class_still_load() {
+        assert_eq!(gameplay(0.0, 0.0).starting_ship_class, "rookie_frigate");
+    }
+
+    #[test]
+    fn zero_rates_charge_nothing() {
+        let cfg = gameplay(0.0, 0.0);
+        assert_eq!(cfg.broker_fee_for(1_000_000), 0);
+        assert_eq!(cfg.sales_tax_for(1_000_000), 0);
+    }
+}
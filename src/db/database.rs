@@ -1,11 +1,12 @@
 
 use std::{fmt::Debug, collections::HashMap};
 
+use chrono::Utc;
 use serde::{Serialize, Deserialize};
 use sled::{Tree, Db, IVec};
 
-use crate::{shared::ObjPath, galaxy::{components::{Ship, GameObject, Navigation, Transform, HngId}, bundles::ships::BPlayerShip}, inventory::{ItemTable, Inventory, Stack, InvSlot, ItemId, InvId}};
-use super::{db_consts::*, db_structs::{account::*, hanger::PlayerHanger, ship_in_space::ShipInSpace, bank::BankAccount, market::{self, ItemStore}}, HangerSlot, PlayerOutstanding};
+use crate::{shared::ObjPath, galaxy::{components::{Ship, ShipId, UNASSIGNED_SHIP_ID, GameObject, Navigation, Transform, HngId, Extractor, Container, Health}, bundles::{ships::BPlayerShip, structures::BExtractor}}, inventory::{ItemTable, Inventory, Stack, InvSlot, ItemId, InvId, ModuleTable, Mapping, SlotType, ShipClassTable, ShipClassId, BlueprintTable, RefineTable, StatModifier}, skills::{SkillTable, SkillId, skill_stat_modifiers, skill_max_orders_bonus}};
+use super::{db_consts::*, db_structs::{account::*, hanger::PlayerHanger, ship_in_space::ShipInSpace, bank::BankAccount, market::{self, ItemStore}, bookmark::{PlayerBookmarks, Bookmark, BookmarkId}, extractor::ExtractorInSpace, production::{ProductionJob, JobId}, skills::PlayerSkills, statistics::{MarketFill, DailyStats, fill_day}, insurance::InsuranceContract}, HangerSlot, PlayerOutstanding};
 use rmp_serde::{to_vec, from_slice};
 
 pub struct DB {
@@ -20,9 +21,26 @@ pub struct DB {
     ships_in_space: Tree,
     statistics: Tree,
     overlord: Tree,
+    bookmarks: Tree,
+    insurance: Tree,
     db: Db,
 
-    pub item_table: ItemTable
+    pub item_table: ItemTable,
+    pub module_table: ModuleTable,
+    pub ship_class_table: ShipClassTable,
+    pub blueprint_table: BlueprintTable,
+    pub refine_table: RefineTable,
+    pub skill_table: SkillTable
+}
+
+/// the static tables loaded from the assets folder, the DB keeps them to validate against
+pub struct AssetTables {
+    pub items: ItemTable,
+    pub modules: ModuleTable,
+    pub ship_classes: ShipClassTable,
+    pub blueprints: BlueprintTable,
+    pub refines: RefineTable,
+    pub skills: SkillTable
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -40,7 +58,7 @@ pub enum CreateAccountStatus {
 
 
 impl DB {
-    pub fn load(path: &String, sled_cache_size: u64, item_table: ItemTable) -> Self {
+    pub fn load(path: &String, sled_cache_size: u64, tables: AssetTables) -> Self {
         let config = sled::Config::default()
             .path(path)
             .cache_capacity(sled_cache_size)
@@ -60,11 +78,19 @@ impl DB {
             ships_in_space: db.open_tree(IN_SPACE_TREE).expect("Could not open ships in space tree"), 
             statistics: db.open_tree(STATISTICS_TREE).expect("Could not open statistics tree"), 
             overlord: db.open_tree(OVERLORD_TREE).expect("Could not open inventory tree"),
+            bookmarks: db.open_tree(BOOKMARK_TREE).expect("Could not open bookmark tree"),
+            insurance: db.open_tree(INSURANCE_TREE).expect("Could not open insurance tree"),
             db: db,
-            item_table: item_table.clone()
+            item_table: tables.items,
+            module_table: tables.modules,
+            ship_class_table: tables.ship_classes,
+            blueprint_table: tables.blueprints,
+            refine_table: tables.refines,
+            skill_table: tables.skills
         };
 
-        db.market_inject_items(&item_table);
+        db.market_inject_items(&db.item_table);
+        db.ships_assign_missing_ids();
         db
     }
 
@@ -119,6 +145,21 @@ impl DB {
         self.account.get(name.as_bytes()).expect("Could not read account from db").and_then(|x| Some(self.deser::<Account>(&x).home_station_path))
     }
 
+    pub fn account_set_home_station(&self, name: &String, station: ObjPath) {
+        match self.account.get(name.as_bytes()).expect("Could not read account from db") {
+            None => { eprintln!("Account not found for {} while changing home station", name); },
+            Some(a) => {
+                let mut acc: Account = self.deser(&a);
+                acc.home_station_path = station;
+                self.account.insert(name.as_bytes(), self.ser(&acc)).expect("Could not write account to db during home station change");
+            }
+        };
+    }
+
+    pub fn account_exists(&self, name: &String) -> bool {
+        self.account.contains_key(name.as_bytes()).expect("Could not read account db")
+    }
+
     pub fn account_delete(&self, name: &String) {
         eprintln!("TODO: SUPPORT ACCOUNT DELETION, NEED TO CLEAN UP DATA IN ALL TABLES");
     }
@@ -325,6 +366,35 @@ impl DB {
         }
     }
 
+    /// FITS A MODULE ITEM TO A DOCKED SHIP, returns the module item that was in the slot before (if any)
+    pub fn hanger_fit_module(&self, name: &String, hanger_id: HngId, slot: HangerSlot, item: ItemId, index: usize) -> Result<Option<ItemId>, String> {
+        let module = match self.item_table.get(&item).map(|i| &i.mapping) {
+            Some(Mapping::Module(m)) => self.module_table.get(m).ok_or(format!("Module {} not found", m))?,
+            _ => { return Err(format!("{} is not a module", item)); }
+        };
+
+        let key = self.hanger_cook_key(name, hanger_id);
+        let h = self.hanger.get(key.as_bytes()).expect("Could not read hanger tree").ok_or(String::from("Hanger not found"))?;
+        let mut h: PlayerHanger = self.deser(&h);
+        let ship = h.inventory.get_mut(&slot).ok_or(String::from("Ship not found in hanger"))?;
+        let replaced = ship.fitting.fit(module.slot, index, item)?;
+        ship.recompute_stats(&self.ship_class_table, &self.item_table, &self.module_table, &self.skills_stat_modifiers(name))?;
+        self.hanger.insert(key.as_bytes(), self.ser(&h)).expect("Could not push fitting change to hanger tree");
+        Ok(replaced)
+    }
+
+    /// REMOVES A MODULE FROM A DOCKED SHIP, returns the module item
+    pub fn hanger_unfit_module(&self, name: &String, hanger_id: HngId, slot: HangerSlot, slot_type: SlotType, index: usize) -> Result<ItemId, String> {
+        let key = self.hanger_cook_key(name, hanger_id);
+        let h = self.hanger.get(key.as_bytes()).expect("Could not read hanger tree").ok_or(String::from("Hanger not found"))?;
+        let mut h: PlayerHanger = self.deser(&h);
+        let ship = h.inventory.get_mut(&slot).ok_or(String::from("Ship not found in hanger"))?;
+        let item = ship.fitting.unfit(slot_type, index)?;
+        ship.recompute_stats(&self.ship_class_table, &self.item_table, &self.module_table, &self.skills_stat_modifiers(name))?;
+        self.hanger.insert(key.as_bytes(), self.ser(&h)).expect("Could not push fitting change to hanger tree");
+        Ok(item)
+    }
+
     /* INVENTORY */
     fn inventory_cook_key(&self, name: &String, inventory_id: InvId) -> String {
         format!("{}:{}", name, inventory_id)
@@ -384,6 +454,29 @@ impl DB {
         res
     }
 
+    /// removes all of the stacks or none of them
+    pub fn inventory_consume_stacks(&self, name: &String, inventory_id: InvId, stacks: &[Stack]) -> Result<(), String> {
+        if !self.inventory.contains_key(self.inventory_cook_key(name, inventory_id.clone()).as_bytes()).expect("Could not read key from db") { return Err(String::from("Inventory is empty")); }
+        self.inventory_run_fn(name, inventory_id, |inv| {
+            let mut inv = match inv {
+                None => { return (None, Err(String::from("Inventory is empty"))); },
+                Some(i) => i
+            };
+
+            for stack in stacks.iter() {
+                let have = inv.count_item(&stack.id);
+                if have < stack.count {
+                    return (Some(inv), Err(format!("Need {} {}, only have {}", stack.count, stack.id, have)));
+                }
+            }
+
+            for stack in stacks.iter() {
+                inv.remove_items(&stack.id, stack.count);
+            }
+            (Some(inv), Ok(()))
+        })
+    }
+
     /// CLONE, SO YOU CANT MUTATE IT
     pub fn inventory_get_inv(&self, name: &String, inventory_id: InvId) -> Option<Inventory> {
         let key = self.inventory_cook_key(name, inventory_id.clone());
@@ -511,32 +604,209 @@ impl DB {
         }
     }
 
-    pub fn market_can_place_new_order(&self, name: &String) -> bool {
+    pub fn market_can_place_new_order(&self, name: &String) -> Result<(), String> {
         let key = self.market_cook_index_key(name);
         match self.market.get(key.as_bytes()).expect("Could not read player index from market tree").and_then(|idx| Some(self.deser::<PlayerOutstanding>(&idx))) {
             Some(data) => {
-                data.can_place_order()
+                if data.can_place_order() { Ok(()) }
+                else { Err(format!("You already have {} of {} orders open, cancel one or train your trade skills to place more", data.order_count(), data.max_orders)) }
+            },
+            None => {
+                eprintln!("Unable to update player outstanding orders");
+                Err(String::from("No market account found"))
+            }
+        }
+    }
+
+    /// brings the stored order limit in line with the player's skills, orders already open over the limit are left alone
+    pub fn market_refresh_max_orders(&self, name: &String) {
+        let key = self.market_cook_index_key(name);
+        let levels = self.skills.get(name.as_bytes()).expect("Could not read skills tree").map(|s| self.deser::<PlayerSkills>(&s).levels).unwrap_or_default();
+        let max = market::BASE_MAX_ORDERS + skill_max_orders_bonus(&self.skill_table, &levels);
+        match self.market.get(key.as_bytes()).expect("Could not read player index from market tree").map(|idx| self.deser::<PlayerOutstanding>(&idx)) {
+            Some(mut data) => {
+                data.set_max_orders(max);
+                self.market.insert(key.as_bytes(), self.ser(&data)).expect("Could not write to market player index");
             },
             None => {
                 eprintln!("Unable to update player outstanding orders");
-                false
             }
         }
     }
 
+    /// takes every expired order out of its store and the owner's index, the caller hands back the items and escrow
+    pub fn market_take_expired_orders(&self, now: i64) -> (Vec<market::SellOrder>, Vec<market::BuyOrder>) {
+        let mut sells = vec![];
+        let mut buys = vec![];
+        let prefix = format!("{}:", MARKET_ITEM);
+        for (k, v) in self.market.scan_prefix(prefix.as_bytes()).filter_map(|r| r.ok()) {
+            let mut store: ItemStore = self.deser(&v);
+            let (s, b) = store.take_expired(now);
+            if s.is_empty() && b.is_empty() {
+                continue;
+            }
+            self.market.insert(k, self.ser(&store)).expect("Could not write item store to tree");
+            sells.extend(s);
+            buys.extend(b);
+        }
+
+        for o in sells.iter() {
+            self.market_remove_sell_order_from_player(&o.player, o.order_id);
+        }
+        for o in buys.iter() {
+            self.market_remove_buy_order_from_player(&o.player, o.order_id);
+        }
+        (sells, buys)
+    }
+
+    /// makes sure every item has a store, stores that already exist hold live orders and escrow so they are only migrated
     fn market_inject_items(&self, items: &ItemTable) {
         for item in items.keys() {
             let key = self.market_cook_store_key(item.clone());
+            if let Some(s) = self.market.get(key.as_bytes()).expect("Could not read market tree") {
+                let mut store: ItemStore = self.deser(&s);
+                if store.migrate_legacy_orders(Utc::now().timestamp()) {
+                    self.market.insert(key.as_bytes(), self.ser(&store)).expect("Could not write migrated item store during init");
+                }
+                continue;
+            }
             let store = ItemStore::new(item.clone());
             self.market.insert(key.as_bytes(), self.ser(&store)).expect("Could not insert new item store during init");
         }
     }
 
     /* SKILLS */
+    /// the closure returns its result and whether it changed anything, the skills are only written back if it did
+    fn skills_run_fn<F, F1>(&self, name: &String, func: F1) -> F
+    where F1: FnOnce(&mut PlayerSkills) -> (F, bool) {
+        let mut skills: PlayerSkills = match self.skills.get(name.as_bytes()).expect("Could not read skills tree") {
+            Some(s) => self.deser(&s),
+            None => PlayerSkills::new(name.clone())
+        };
+        let before = skills.levels.clone();
+        let (ret, changed) = func(&mut skills);
+        if !changed {
+            return ret;
+        }
+        self.skills.insert(name.as_bytes(), self.ser(&skills)).expect("Could not write skills");
+        if skills.levels != before {
+            // anything derived from skill levels that is stored has to follow them
+            self.market_refresh_max_orders(name);
+        }
+        ret
+    }
+
+    pub fn skills_get(&self, name: &String, now: i64) -> PlayerSkills {
+        self.skills_run_fn(name, |s| {
+            let done = s.advance(now, &self.skill_table);
+            (s.clone(), !done.is_empty())
+        })
+    }
+
+    pub fn skills_enqueue(&self, name: &String, id: &SkillId, now: i64) -> Result<u8, String> {
+        self.skills_run_fn(name, |s| {
+            let done = s.advance(now, &self.skill_table);
+            let res = s.enqueue(id, &self.skill_table, now);
+            let changed = res.is_ok() || !done.is_empty();
+            (res, changed)
+        })
+    }
+
+    pub fn skills_remove_queued(&self, name: &String, index: usize, now: i64) -> Result<(), String> {
+        self.skills_run_fn(name, |s| {
+            let done = s.advance(now, &self.skill_table);
+            let res = s.remove(index, &self.skill_table, now);
+            let changed = res.is_ok() || !done.is_empty();
+            (res, changed)
+        })
+    }
+
+    /// completes finished training, returns what completed and when the next level will finish
+    pub fn skills_advance(&self, name: &String, now: i64) -> (Vec<(SkillId, u8)>, Option<i64>) {
+        self.skills_run_fn(name, |s| {
+            let done = s.advance(now, &self.skill_table);
+            let changed = !done.is_empty();
+            ((done, s.head_finishes_at(&self.skill_table)), changed)
+        })
+    }
+
+    /// the ship stat modifiers a player's skills give
+    pub fn skills_stat_modifiers(&self, name: &String) -> Vec<StatModifier> {
+        let skills = self.skills_get(name, Utc::now().timestamp());
+        skill_stat_modifiers(&self.skill_table, &skills.levels)
+    }
+
+    /// when every training queue's head finishes, loaded in to the skill queue when the server starts
+    pub fn skills_load_queue_heads(&self) -> Vec<(String, i64)> {
+        self.skills.iter().values().filter_map(|s| s.ok()).filter_map(|s| {
+            let skills: PlayerSkills = self.deser(&s);
+            skills.head_finishes_at(&self.skill_table).map(|t| (skills.name, t))
+        }).collect()
+    }
 
     /* RESOURCES (planets/moons/asteroid belts) */
+    fn resources_cook_extractor_key(&self, path: &ObjPath) -> String {
+        format!("{}:{}:{:?}:{}", RESOURCES_EXTRACTOR_PREFIX, path.sys, path.t, path.name)
+    }
+
+    pub fn resources_extractor_exists(&self, path: &ObjPath) -> bool {
+        self.resources.contains_key(self.resources_cook_extractor_key(path).as_bytes()).expect("Could not read resources tree")
+    }
+
+    pub fn resources_save_extractor(&self, game_obj: &GameObject, extractor: &Extractor, container: &Container, transform: &Transform) {
+        let eis = ExtractorInSpace {
+            extractor: extractor.clone(),
+            container: container.clone(),
+            transform: transform.clone(),
+            game_object: game_obj.clone()
+        };
+        self.resources.insert(self.resources_cook_extractor_key(&game_obj.path).as_bytes(), self.ser(&eis)).expect("Could not save extractor");
+    }
+
+    pub fn resources_remove_extractor(&self, path: &ObjPath) {
+        self.resources.remove(self.resources_cook_extractor_key(path).as_bytes()).expect("Could not remove extractor");
+    }
+
+    /// every extractor and planet office that is still deployed, spawned back in when the server starts
+    pub fn resources_load_extractors(&self) -> Vec<BExtractor> {
+        self.resources.scan_prefix(RESOURCES_EXTRACTOR_PREFIX.as_bytes()).filter_map(|r| {
+            match r {
+                Ok((_, v)) => {
+                    let eis: ExtractorInSpace = self.deser(&v);
+                    Some(BExtractor::load_from_db(eis.game_object, eis.extractor, eis.container, eis.transform))
+                },
+                Err(e) => { eprintln!("Could not read extractor from db: {}", e); None }
+            }
+        }).collect()
+    }
 
     /* PRODUCTION */
+    fn production_cook_key(&self, name: &String, job_id: JobId) -> String {
+        format!("{}:{}", name, job_id)
+    }
+
+    pub fn production_add_job(&self, job: &ProductionJob) {
+        self.production.insert(self.production_cook_key(&job.player, job.job_id).as_bytes(), self.ser(job)).expect("Could not save production job");
+    }
+
+    pub fn production_remove_job(&self, name: &String, job_id: JobId) -> Option<ProductionJob> {
+        self.production.remove(self.production_cook_key(name, job_id).as_bytes()).expect("Could not remove production job").map(|j| self.deser(&j))
+    }
+
+    pub fn production_player_jobs(&self, name: &String) -> Vec<ProductionJob> {
+        let prefix = format!("{}:", name);
+        self.production.scan_prefix(prefix.as_bytes()).values().filter_map(|j| j.ok()).map(|j| self.deser(&j)).collect()
+    }
+
+    /// every running job, loaded in to the production queue when the server starts
+    pub fn production_load_jobs(&self) -> Vec<ProductionJob> {
+        self.production.iter().values().filter_map(|j| j.ok()).map(|j| self.deser(&j)).collect()
+    }
+
+    /// unique for the life of the database, used to name objects spawned at runtime (missiles, wrecks, containers)
+    pub fn object_new_id(&self) -> u64 {
+        self.db.generate_id().expect("Could not generate object id")
+    }
 
     /* SHIPS IN SPACE */
 
@@ -544,24 +814,200 @@ impl DB {
         match self.ships_in_space.remove(name.as_bytes()).expect("Could not read ship from db") {
             Some(s) => {
                 let ship: ShipInSpace = self.deser(&s);
-                Some(BPlayerShip::load_from_db(ship.ship, &ship.player_name, ship.navigation, ship.transform, ship.game_object))
+                Some(BPlayerShip::load_from_db(ship.ship, &ship.player_name, ship.navigation, ship.transform, ship.game_object, ship.health, self))
             },
             None => None
         }
     }
 
-    pub fn sis_save_ship(&self, name: &String, ship: &Ship, nav: &Navigation, transform: &Transform, game_obj: &GameObject) {
+    pub fn sis_save_ship(&self, name: &String, ship: &Ship, nav: &Navigation, transform: &Transform, game_obj: &GameObject, health: &Health) {
         let ss = ShipInSpace {
             player_name: name.clone(),
             ship: ship.clone(),
             navigation: nav.clone(),
             transform: transform.clone(),
-            game_object: game_obj.clone()
+            game_object: game_obj.clone(),
+            health: Some(health.clone())
         };
         self.ships_in_space.insert(name.as_bytes(), self.ser(&ss)).expect("Could not save ship");
     }
 
+    /// ids come from sled so they are unique for the life of the database, offset so none collide with UNASSIGNED_SHIP_ID
+    pub fn ship_new_id(&self) -> ShipId {
+        self.object_new_id() + 1
+    }
+
+    /// RUN ONCE AT STARTUP, rederives legacy ships against their new class so they can be fitted and insured
+    pub fn ships_migrate_legacy_classes(&self, legacy: &HashMap<String, ShipClassId>) {
+        for (k, v) in self.hanger.iter().filter_map(|e| e.ok()) {
+            let key = String::from_utf8_lossy(&k).to_string();
+            let name = String::from(key.split(':').next().unwrap_or_default());
+            let mut h: PlayerHanger = self.deser(&v);
+            let mut changed = false;
+            for ship in h.inventory.values_mut() {
+                if !ship.migrate_legacy_class(legacy, &self.ship_class_table) {
+                    continue;
+                }
+                if let Err(e) = ship.rederive(&self.ship_class_table, &self.item_table, &self.module_table, &self.skills_stat_modifiers(&name)) {
+                    eprintln!("Could not rederive migrated ship {}: {}", ship.ship_name, e);
+                }
+                changed = true;
+            }
+            if changed {
+                self.hanger.insert(k, self.ser(&h)).expect("Could not write hanger while migrating ship classes");
+            }
+        }
+
+        for (k, v) in self.ships_in_space.iter().filter_map(|e| e.ok()) {
+            let mut ss: ShipInSpace = self.deser(&v);
+            if ss.ship.migrate_legacy_class(legacy, &self.ship_class_table) {
+                if let Err(e) = ss.ship.rederive(&self.ship_class_table, &self.item_table, &self.module_table, &self.skills_stat_modifiers(&ss.player_name)) {
+                    eprintln!("Could not rederive migrated ship {}: {}", ss.ship.ship_name, e);
+                }
+                self.ships_in_space.insert(k, self.ser(&ss)).expect("Could not write ship in space while migrating ship classes");
+            }
+        }
+    }
+
+    /// gives every stored ship from before ship ids existed an id of its own
+    fn ships_assign_missing_ids(&self) {
+        for (k, v) in self.hanger.iter().filter_map(|e| e.ok()) {
+            let mut h: PlayerHanger = self.deser(&v);
+            let mut changed = false;
+            for ship in h.inventory.values_mut().filter(|s| s.ship_id == UNASSIGNED_SHIP_ID) {
+                ship.ship_id = self.ship_new_id();
+                changed = true;
+            }
+            if changed {
+                self.hanger.insert(k, self.ser(&h)).expect("Could not write hanger while assigning ship ids");
+            }
+        }
+
+        for (k, v) in self.ships_in_space.iter().filter_map(|e| e.ok()) {
+            let mut ss: ShipInSpace = self.deser(&v);
+            if ss.ship.ship_id == UNASSIGNED_SHIP_ID {
+                ss.ship.ship_id = self.ship_new_id();
+                self.ships_in_space.insert(k, self.ser(&ss)).expect("Could not write ship in space while assigning ship ids");
+            }
+        }
+    }
+
+    /* INSURANCE */
+    fn insurance_cook_key(&self, name: &String, ship_id: ShipId) -> String {
+        format!("{}:{}", name, ship_id)
+    }
+
+    pub fn insurance_add(&self, contract: &InsuranceContract) {
+        self.insurance.insert(self.insurance_cook_key(&contract.owner, contract.ship_id).as_bytes(), self.ser(contract)).expect("Could not save insurance contract");
+    }
+
+    /// only active contracts are returned, an expired one is deleted when it is found
+    pub fn insurance_get(&self, name: &String, ship_id: ShipId, now: i64) -> Option<InsuranceContract> {
+        let key = self.insurance_cook_key(name, ship_id);
+        let contract: InsuranceContract = self.deser(&self.insurance.get(key.as_bytes()).expect("Could not read insurance tree")?);
+        if contract.is_active(now) {
+            return Some(contract);
+        }
+        self.insurance.remove(key.as_bytes()).expect("Could not remove expired insurance contract");
+        None
+    }
+
+    /// only active contracts are returned, expired ones are deleted as they are found
+    pub fn insurance_player_contracts(&self, name: &String, now: i64) -> Vec<InsuranceContract> {
+        let prefix = format!("{}:", name);
+        let (active, expired): (Vec<InsuranceContract>, Vec<InsuranceContract>) = self.insurance.scan_prefix(prefix.as_bytes()).values()
+            .filter_map(|c| c.ok())
+            .map(|c| self.deser(&c))
+            .partition(|c: &InsuranceContract| c.is_active(now));
+        for c in expired {
+            self.insurance.remove(self.insurance_cook_key(name, c.ship_id).as_bytes()).expect("Could not remove expired insurance contract");
+        }
+        active
+    }
+
+    /// the ship is gone so the contract is used up either way, returns it only if it was still active
+    pub fn insurance_claim(&self, name: &String, ship_id: ShipId, now: i64) -> Option<InsuranceContract> {
+        self.insurance.remove(self.insurance_cook_key(name, ship_id).as_bytes()).expect("Could not remove insurance contract")
+            .map(|c| self.deser::<InsuranceContract>(&c))
+            .filter(|c| c.is_active(now))
+    }
+
+    /* BOOKMARKS */
+    /// only writes back if func succeeded, a failed edit leaves the stored bookmarks untouched
+    fn bookmark_run_fn<F, F1>(&self, name: &String, func: F1) -> Result<F, String>
+    where F1: FnOnce(&mut PlayerBookmarks) -> Result<F, String> {
+        let mut bms: PlayerBookmarks = match self.bookmarks.get(name.as_bytes()).expect("Could not read bookmark tree") {
+            Some(b) => self.deser(&b),
+            None => PlayerBookmarks::new(name.clone())
+        };
+        let result = func(&mut bms);
+        if result.is_ok() {
+            self.bookmarks.insert(name.as_bytes(), self.ser(&bms)).expect("Could not write bookmarks");
+        }
+        result
+    }
+
+    pub fn bookmark_add(&self, name: &String, bookmark: Bookmark) -> Result<BookmarkId, String> {
+        self.bookmark_run_fn(name, |bms| bms.add(bookmark))
+    }
+
+    pub fn bookmark_add_shared(&self, name: &String, bookmark: Bookmark, max: usize) -> Result<BookmarkId, String> {
+        self.bookmark_run_fn(name, |bms| bms.add_shared(bookmark, max))
+    }
+
+    pub fn bookmark_rename(&self, name: &String, id: BookmarkId, label: String) -> Result<(), String> {
+        self.bookmark_run_fn(name, |bms| bms.rename(id, label))
+    }
+
+    pub fn bookmark_move(&self, name: &String, id: BookmarkId, folder: String) -> Result<(), String> {
+        self.bookmark_run_fn(name, |bms| bms.move_to_folder(id, folder))
+    }
+
+    pub fn bookmark_delete(&self, name: &String, id: BookmarkId) -> Result<Bookmark, String> {
+        self.bookmark_run_fn(name, |bms| bms.remove(id))
+    }
+
+    /// RETURNS A COPY, CAN NOT MUTATE DIRECTLY
+    pub fn bookmark_get(&self, name: &String, id: BookmarkId) -> Option<Bookmark> {
+        self.bookmark_get_all(name).and_then(|bms| bms.get(id).cloned())
+    }
+
+    /// RETURNS A COPY, CAN NOT MUTATE DIRECTLY
+    pub fn bookmark_get_all(&self, name: &String) -> Option<PlayerBookmarks> {
+        self.bookmarks.get(name.as_bytes()).expect("Could not read bookmark tree").map(|b| self.deser(&b))
+    }
+
     /* STATISTICS */
+    // fills sort by time within an item, so a time window is a range scan. nanoseconds keep partial fills of one order apart
+    fn statistics_cook_fill_key(&self, item: &ItemId, time_ns: i64, order_id: u64) -> String {
+        format!("{}:{}:{:020}:{}", STATISTICS_MARKET_FILL_PREFIX, item, time_ns, order_id)
+    }
+
+    fn statistics_cook_day_key(&self, item: &ItemId, day: i64) -> String {
+        format!("{}:{}:{:010}", STATISTICS_MARKET_DAY_PREFIX, item, day)
+    }
+
+    /// keeps the raw fill and rolls it in to its day straight away, so history requests never have to go through raw fills
+    pub fn statistics_record_fill(&self, fill: &MarketFill) {
+        let key = self.statistics_cook_fill_key(&fill.item, Utc::now().timestamp_nanos(), fill.order_id);
+        self.statistics.insert(key.as_bytes(), self.ser(fill)).expect("Could not write market fill to statistics tree");
+
+        let day = fill_day(fill);
+        let day_key = self.statistics_cook_day_key(&fill.item, day);
+        let mut stats: DailyStats = match self.statistics.get(day_key.as_bytes()).expect("Could not read statistics tree") {
+            Some(d) => self.deser(&d),
+            None => DailyStats::new(day)
+        };
+        stats.add_fill(fill);
+        self.statistics.insert(day_key.as_bytes(), self.ser(&stats)).expect("Could not write daily market stats");
+    }
+
+    /// one entry per day with trades from the day since falls in, oldest first
+    pub fn statistics_market_history(&self, item: &ItemId, since: i64) -> Vec<DailyStats> {
+        let start = self.statistics_cook_day_key(item, since.max(0).div_euclid(86_400));
+        let end = format!("{}:{};", STATISTICS_MARKET_DAY_PREFIX, item);
+        self.statistics.range(start.as_bytes()..end.as_bytes()).values().filter_map(|v| v.ok()).map(|v| self.deser(&v)).collect()
+    }
 
     /* OVERLORD */
 
@@ -10,9 +10,16 @@ pub const IN_SPACE_TREE: &'static str = "IN_SPACE";
 pub const STATISTICS_TREE: &'static str = "STATISTICS";
 pub const OVERLORD_TREE: &'static str = "OVERLORD";
 pub const BANK_TREE: &'static str = "BANK";
+pub const BOOKMARK_TREE: &'static str = "BOOKMARK";
+pub const INSURANCE_TREE: &'static str = "INSURANCE";
 
 pub const BANK_ACCOUNT_PREFIX: &'static str = "BANK_ACCOUNT";
 pub const BANK_VALUE_PREFIX: &'static str = "BANK_VALUE";
 
 pub const MARKET_PLAYER_LIST: &'static str = "MARKET_PLAYER_LIST";
-pub const MARKET_ITEM: &'static str = "MARKET_ITEM";
\ No newline at end of file
+pub const MARKET_ITEM: &'static str = "MARKET_ITEM";
+
+pub const STATISTICS_MARKET_FILL_PREFIX: &'static str = "MARKET_FILL";
+pub const STATISTICS_MARKET_DAY_PREFIX: &'static str = "MARKET_DAY";
+
+pub const RESOURCES_EXTRACTOR_PREFIX: &'static str = "EXTRACTOR";
\ No newline at end of file
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+
+use chrono::Utc;
+use nalgebra::Vector3;
+use serde::{Serialize, Deserialize};
+
+pub type BookmarkId = u64;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Bookmark {
+    pub id: BookmarkId,
+    pub label: String,
+    pub folder: String,
+    pub system: String,
+    pub pos: Vector3<f64>,
+    pub shared_by: Option<String>,
+}
+
+impl Bookmark {
+    pub fn new(label: String, folder: String, system: String, pos: Vector3<f64>) -> Self {
+        let time = Utc::now();
+        let val = format!("bm{}-{}-{}-{:?}-{}", label, folder, system, pos, time.to_rfc3339());
+        let mut s = DefaultHasher::new();
+        val.hash(&mut s);
+        let id = s.finish();
+        Bookmark { id, label, folder, system, pos, shared_by: None }
+    }
+
+    /// makes a copy of this bookmark (with a fresh id) to hand to another player
+    pub fn share(&self, from: &str, folder: String) -> Self {
+        let mut bm = Bookmark::new(self.label.clone(), folder, self.system.clone(), self.pos);
+        bm.shared_by = Some(from.to_string());
+        bm
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlayerBookmarks {
+    pub name: String,
+    pub bookmarks: HashMap<BookmarkId, Bookmark>,
+}
+
+impl PlayerBookmarks {
+    pub fn new(name: String) -> Self {
+        PlayerBookmarks { name, bookmarks: HashMap::new() }
+    }
+
+    pub fn add(&mut self, bookmark: Bookmark) -> Result<BookmarkId, String> {
+        if self.bookmarks.contains_key(&bookmark.id) {
+            return Err(String::from("A bookmark with that ID already exists (hash collision)"));
+        }
+        let id = bookmark.id;
+        self.bookmarks.insert(id, bookmark);
+        Ok(id)
+    }
+
+    /// like add, but refuses once the player is holding max bookmarks shared by others
+    pub fn add_shared(&mut self, bookmark: Bookmark, max: usize) -> Result<BookmarkId, String> {
+        if self.shared_count() >= max {
+            return Err(format!("{} can not receive any more shared bookmarks", self.name));
+        }
+        self.add(bookmark)
+    }
+
+    pub fn shared_count(&self) -> usize {
+        self.bookmarks.values().filter(|b| b.shared_by.is_some()).count()
+    }
+
+    pub fn get(&self, id: BookmarkId) -> Option<&Bookmark> {
+        self.bookmarks.get(&id)
+    }
+
+    pub fn rename(&mut self, id: BookmarkId, label: String) -> Result<(), String> {
+        let bm = self.bookmarks.get_mut(&id).ok_or(String::from("Bookmark not found"))?;
+        bm.label = label;
+        Ok(())
+    }
+
+    pub fn move_to_folder(&mut self, id: BookmarkId, folder: String) -> Result<(), String> {
+        let bm = self.bookmarks.get_mut(&id).ok_or(String::from("Bookmark not found"))?;
+        bm.folder = folder;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id: BookmarkId) -> Result<Bookmark, String> {
+        self.bookmarks.remove(&id).ok_or(String::from("Bookmark not found"))
+    }
+
+    pub fn list(&self) -> Vec<Bookmark> {
+        self.bookmarks.values().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(label: &str) -> Bookmark {
+        Bookmark::new(String::from(label), String::from("folder"), String::from("sys"), Vector3::zeros())
+    }
+
+    #[test]
+    fn shared_bookmarks_are_capped() {
+        let mut bms = PlayerBookmarks::new(String::from("them"));
+        let from = String::from("me");
+        bms.add(bookmark("own")).unwrap();
+        bms.add_shared(bookmark("a").share(&from, String::from("Shared")), 2).unwrap();
+        bms.add_shared(bookmark("b").share(&from, String::from("Shared")), 2).unwrap();
+        assert!(bms.add_shared(bookmark("c").share(&from, String::from("Shared")), 2).is_err());
+        assert_eq!(bms.shared_count(), 2);
+        assert_eq!(bms.list().len(), 3);
+
+        // their own bookmarks do not count towards the cap
+        bms.add(bookmark("own2")).unwrap();
+        assert_eq!(bms.shared_count(), 2);
+    }
+}
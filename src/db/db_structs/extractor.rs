@@ -0,0 +1,11 @@
+use serde::{Serialize, Deserialize};
+
+use crate::galaxy::components::{Extractor, Container, Transform, GameObject};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExtractorInSpace {
+    pub extractor: Extractor,
+    pub container: Container,
+    pub transform: Transform,
+    pub game_object: GameObject
+}
@@ -0,0 +1,27 @@
+use chrono::{Utc, Duration};
+use serde::{Serialize, Deserialize};
+
+use crate::galaxy::components::{Ship, ShipId};
+
+/// Pays out the insured value of a ship if it is destroyed before the contract runs out
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InsuranceContract {
+    pub ship_id: ShipId,
+    pub owner: String,
+    pub ship_name: String,
+    pub ship_class: String,
+    pub premium: i64, // what the owner paid for the contract
+    pub payout: i64,
+    pub expires_at: i64, // unix timestamp
+}
+
+impl InsuranceContract {
+    pub fn new(owner: &str, ship: &Ship, premium: i64, payout: i64, duration_days: i64) -> Self {
+        let expires_at = (Utc::now() + Duration::days(duration_days)).timestamp();
+        InsuranceContract { ship_id: ship.ship_id, owner: owner.to_string(), ship_name: ship.ship_name.clone(), ship_class: ship.ship_class.clone(), premium, payout, expires_at }
+    }
+
+    pub fn is_active(&self, now: i64) -> bool {
+        now < self.expires_at
+    }
+}
@@ -6,9 +6,74 @@ use std::collections::hash_map::DefaultHasher;
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 
+use crate::galaxy::pathfinding::RouteGraph;
 use crate::inventory::InvId;
 use crate::{inventory::{Stack, ItemId}};
 
+/// how far from its station a buy order will accept items from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OrderRange {
+    Station,
+    System,
+    Jumps(u32),
+    Region,
+}
+
+impl OrderRange {
+    /// true if an inventory at other (station inventory, system) can trade with an order placed at order
+    pub fn reaches(&self, graph: &RouteGraph, order: (&InvId, &String), other: (&InvId, &String)) -> bool {
+        match self {
+            OrderRange::Station => order.0 == other.0,
+            OrderRange::System => order.1 == other.1,
+            OrderRange::Jumps(n) => graph.jump_count(order.1, other.1).map(|j| j <= *n as usize).unwrap_or(false),
+            OrderRange::Region => match (graph.get_region(order.1), graph.get_region(other.1)) {
+                (Some(a), Some(b)) => a == b,
+                _ => false
+            }
+        }
+    }
+}
+
+// orders placed before ranges existed could only be traded with at their own station
+fn default_order_range() -> OrderRange { OrderRange::Station }
+
+pub const MAX_ORDER_DURATION_DAYS: u32 = 90;
+
+// orders placed before durations existed load with this, ItemStore::migrate_legacy_orders gives them a real expiry once and it is written back
+pub const UNKNOWN_EXPIRY: i64 = 0;
+
+fn expiry_from_days(time: &DateTime<Utc>, duration_days: u32) -> i64 {
+    time.timestamp() + duration_days.clamp(1, MAX_ORDER_DURATION_DAYS) as i64 * 86_400
+}
+
+/// a price of nothing or less would pay the player for placing or changing an order
+pub fn check_price(cost_per_item: i64) -> Result<(), String> {
+    if cost_per_item <= 0 {
+        return Err(String::from("Price must be positive"));
+    }
+    Ok(())
+}
+
+pub fn check_new_order(count: u32, cost_per_item: i64) -> Result<(), String> {
+    if count == 0 {
+        return Err(String::from("Order must be for at least one item"));
+    }
+    check_price(cost_per_item)
+}
+
+/// what count items cost at cost_per_item, prices come from clients so this has to be checked
+pub fn order_value(count: u32, cost_per_item: i64) -> Result<i64, String> {
+    cost_per_item.checked_mul(count as i64).ok_or(String::from("Price too high"))
+}
+
+fn check_cooldown(last_modified: i64, now: i64, cooldown_s: i64) -> Result<(), String> {
+    let wait = last_modified + cooldown_s - now;
+    if wait > 0 {
+        return Err(format!("This order can be modified again in {} seconds", wait));
+    }
+    Ok(())
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BuyOrder {
     pub item_id: ItemId,
@@ -18,16 +83,22 @@ pub struct BuyOrder {
     pub location: InvId,
     pub order_id: u64,
     pub time_placed: String,
+    #[serde(default = "default_order_range")]
+    pub range: OrderRange,
+    #[serde(default)]
+    pub last_modified: i64, // unix timestamp of placement or the last price change, 0 if unknown
+    #[serde(default)]
+    pub expires_at: i64, // unix timestamp, UNKNOWN_EXPIRY for orders placed before durations existed
 }
 
 impl BuyOrder {
-    pub fn new(item_id: ItemId, count: u32, player: String, escrow: i64, location: InvId) -> Self {
+    pub fn new(item_id: ItemId, count: u32, player: String, escrow: i64, location: InvId, range: OrderRange, duration_days: u32) -> Self {
         let time = Utc::now();
         let val = format!("buy{}-{}-{}-{:?}-{}", item_id, count, player, location, time.to_rfc3339());
         let mut s = DefaultHasher::new();
         val.hash(&mut s);
         let id = s.finish();
-        BuyOrder { item_id, count, escrow, player, location, order_id: id, time_placed: time.to_rfc3339()}
+        BuyOrder { item_id, count, escrow, player, location, order_id: id, time_placed: time.to_rfc3339(), range, last_modified: time.timestamp(), expires_at: expiry_from_days(&time, duration_days) }
     }
 
     pub fn satisfy(&mut self, stack: Stack) -> i64 {
@@ -42,7 +113,8 @@ impl BuyOrder {
         }
 
         let cost_per_item = self.escrow / (self.count as i64);
-        let amount_earned = self.count as i64 * cost_per_item;
+        // the last items take whatever is left so rounding never strands escrow
+        let amount_earned = if stack.count == self.count { self.escrow } else { stack.count as i64 * cost_per_item };
         self.count -= stack.count;
         self.escrow -= amount_earned;
         return amount_earned;
@@ -61,17 +133,21 @@ pub struct SellOrder {
     pub cost_per_item: i64,
     pub location: InvId,
     pub order_id: u64,
-    pub time_placed: String
+    pub time_placed: String,
+    #[serde(default)]
+    pub last_modified: i64, // unix timestamp of placement or the last price change, 0 if unknown
+    #[serde(default)]
+    pub expires_at: i64, // unix timestamp, UNKNOWN_EXPIRY for orders placed before durations existed
 }
 
 impl SellOrder {
-    pub fn new(stack: Stack, cost_per_item: i64, player: String, location: InvId) -> Self {
+    pub fn new(stack: Stack, cost_per_item: i64, player: String, location: InvId, duration_days: u32) -> Self {
         let time = Utc::now();
         let val = format!("buy{}-{}-{:?}-{}", stack.id, player, location, time.to_rfc3339());
         let mut s = DefaultHasher::new();
         val.hash(&mut s);
         let id = s.finish();
-        SellOrder { stack, player, cost_per_item, location, order_id: id, time_placed: time.to_rfc3339() }
+        SellOrder { stack, player, cost_per_item, location, order_id: id, time_placed: time.to_rfc3339(), last_modified: time.timestamp(), expires_at: expiry_from_days(&time, duration_days) }
     }
 
     pub fn buy_from(&mut self, count: u32, money: i64) -> Option<Stack> {
@@ -116,8 +192,10 @@ impl ItemStore {
         ItemStore { item: item_id, sell_orders: HashMap::new(), buy_orders: HashMap::new() }
     }
 
-    pub fn add_sell_order(&mut self, player: &String, stack: Stack, cost_per_item: i64, location: InvId) -> Result<u64, String> {
-        let order = SellOrder::new(stack, cost_per_item, player.clone(), location);
+    pub fn add_sell_order(&mut self, player: &String, stack: Stack, cost_per_item: i64, location: InvId, duration_days: u32) -> Result<u64, String> {
+        check_new_order(stack.count, cost_per_item)?;
+        order_value(stack.count, cost_per_item)?;
+        let order = SellOrder::new(stack, cost_per_item, player.clone(), location, duration_days);
         let id = order.order_id;
         if self.sell_orders.contains_key(&order.order_id) {
             return Err(String::from("An order with that ID already exists (hash collision)"));
@@ -126,8 +204,9 @@ impl ItemStore {
         Ok(id)
     }
 
-    pub fn add_buy_order(&mut self, player: &String, item_id: ItemId, count: u32, cost_per_item: i64, location: InvId) -> Result<u64, String> {
-        let order = BuyOrder::new(item_id, count, player.clone(), count as i64 * cost_per_item, location);
+    pub fn add_buy_order(&mut self, player: &str, count: u32, cost_per_item: i64, location: InvId, range: OrderRange, duration_days: u32) -> Result<u64, String> {
+        check_new_order(count, cost_per_item)?;
+        let order = BuyOrder::new(self.item.clone(), count, player.to_string(), order_value(count, cost_per_item)?, location, range, duration_days);
         let id = order.order_id;
         if self.buy_orders.contains_key(&order.order_id) {
             return Err(String::from("An order with that ID already exists (hash collision)"));
@@ -136,11 +215,9 @@ impl ItemStore {
         Ok(id)
     }
 
+    /// the caller is responsible for checking location is within the order's range
     pub fn fulfill_buy_order(&mut self, order_id: u64, stack: Stack, location: InvId, selling_player: String) -> Result<StoreTransaction, String> {
         let mut order = self.buy_orders.get_mut(&order_id).ok_or(String::from("Order no longer exists"))?;
-        if location != order.location {
-            return Err(String::from("Order is not in range"));
-        }
 
         if order.item_id != stack.id {
             return Err(String::from("Buy order not being fulfilled with item of right type"));
@@ -173,7 +250,7 @@ impl ItemStore {
             return Err(String::from("Trying to purchase too many items from sell order"));
         }
 
-        let cost = order.cost_per_item * (count as i64);
+        let cost = order_value(count, order.cost_per_item)?;
         let stack_retreived = order.buy_from(count, cost).ok_or(String::from("Order not fulfilled correctly"))?;
         let transaction = StoreTransaction {
             purchasing_player: buying_player.clone(),
@@ -245,10 +322,71 @@ impl ItemStore {
         self.buy_orders.get(&order_id)
     }
 
+    /// changes the price of a sell order, orders can only be modified once every cooldown_s seconds
+    pub fn modify_sell_order(&mut self, player: &String, order_id: u64, cost_per_item: i64, now: i64, cooldown_s: i64) -> Result<(), String> {
+        let order = self.sell_orders.get_mut(&order_id).ok_or(String::from("Order no longer exists"))?;
+        if order.player != *player {
+            return Err(String::from("Cannot modify other player's sell order"));
+        }
+        check_price(cost_per_item)?;
+        order_value(order.stack.count, cost_per_item)?;
+        check_cooldown(order.last_modified, now, cooldown_s)?;
+        order.cost_per_item = cost_per_item;
+        order.last_modified = now;
+        Ok(())
+    }
+
+    /// changes the price of a buy order, returns how much the escrow went up by (negative if it went down)
+    pub fn modify_buy_order(&mut self, player: &String, order_id: u64, cost_per_item: i64, now: i64, cooldown_s: i64) -> Result<i64, String> {
+        let order = self.buy_orders.get_mut(&order_id).ok_or(String::from("Order no longer exists"))?;
+        if order.player != *player {
+            return Err(String::from("Cannot modify other player's buy order"));
+        }
+        check_price(cost_per_item)?;
+        let escrow = order_value(order.count, cost_per_item)?;
+        let delta = escrow.checked_sub(order.escrow).ok_or(String::from("Price too high"))?;
+        check_cooldown(order.last_modified, now, cooldown_s)?;
+        order.escrow = escrow;
+        order.last_modified = now;
+        Ok(delta)
+    }
+
+    /// gives legacy orders the longest duration counted from now, returns true if any were changed and the store needs saving
+    pub fn migrate_legacy_orders(&mut self, now: i64) -> bool {
+        let expires_at = now + MAX_ORDER_DURATION_DAYS as i64 * 86_400;
+        let mut changed = false;
+        for e in self.sell_orders.values_mut().map(|o| &mut o.expires_at).chain(self.buy_orders.values_mut().map(|o| &mut o.expires_at)) {
+            if *e == UNKNOWN_EXPIRY {
+                *e = expires_at;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// removes and returns every order that expired at or before now
+    pub fn take_expired(&mut self, now: i64) -> (Vec<SellOrder>, Vec<BuyOrder>) {
+        let sells: Vec<u64> = self.sell_orders.values().filter(|o| o.expires_at <= now).map(|o| o.order_id).collect();
+        let buys: Vec<u64> = self.buy_orders.values().filter(|o| o.expires_at <= now).map(|o| o.order_id).collect();
+        (
+            sells.iter().filter_map(|id| self.sell_orders.remove(id)).collect(),
+            buys.iter().filter_map(|id| self.buy_orders.remove(id)).collect()
+        )
+    }
+
+    /// drops every order whose station inventory does not pass the filter, used to scope what a player sees
+    pub fn retain_orders<F: Fn(&InvId) -> bool>(&mut self, f: F) {
+        self.sell_orders.retain(|_, o| f(&o.location));
+        self.buy_orders.retain(|_, o| f(&o.location));
+    }
+
 }
 
 
 /* PLAYER METADATA */
+// orders a player can have open before any skills are trained
+pub const BASE_MAX_ORDERS: u32 = 100; // what every account had before skills, kept so nobody loses order slots
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PlayerOutstanding {
     pub name: String,
@@ -259,7 +397,7 @@ pub struct PlayerOutstanding {
 
 impl PlayerOutstanding {
     pub fn new(name: String) -> Self {
-        PlayerOutstanding { name: name, sell_orders: HashMap::new(), buy_orders: HashMap::new(), max_orders: 100 }
+        PlayerOutstanding { name: name, sell_orders: HashMap::new(), buy_orders: HashMap::new(), max_orders: BASE_MAX_ORDERS }
     }
 
     pub fn set_max_orders(&mut self, orders: u32) {
@@ -282,7 +420,134 @@ impl PlayerOutstanding {
         self.buy_orders.remove(&order_id);
     }
 
+    pub fn order_count(&self) -> usize {
+        self.sell_orders.len() + self.buy_orders.len()
+    }
+
     pub fn can_place_order(&self) -> bool {
-        self.sell_orders.len() + self.buy_orders.len() < self.max_orders as usize
+        self.order_count() < self.max_orders as usize
+    }
+}
+#[cfg(test)]
+mod tests {
+    use rmp_serde::{to_vec, from_slice};
+    use crate::galaxy::pathfinding::tests::graph;
+    use super::*;
+
+    // a buy order and store as the baseline saved them, before ranges, cooldowns and expiry existed
+    #[derive(Serialize)]
+    struct BaselineBuyOrder { item_id: ItemId, count: u32, escrow: i64, player: String, location: InvId, order_id: u64, time_placed: String }
+
+    #[derive(Serialize)]
+    struct BaselineItemStore { item: ItemId, sell_orders: HashMap<u64, BaselineBuyOrder>, buy_orders: HashMap<u64, BaselineBuyOrder> }
+
+    fn round_trip(store: &ItemStore) -> ItemStore {
+        from_slice(&to_vec(store).unwrap()).expect("Item store did not load")
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn legacy_order_expiry_is_fixed_once() {
+        let order = BaselineBuyOrder { item_id: String::from("haxonite"), count: 10, escrow: 1000, player: String::from("p"), location: String::from("station"), order_id: 7, time_placed: String::from("") };
+        let legacy = BaselineItemStore { item: String::from("haxonite"), sell_orders: HashMap::new(), buy_orders: HashMap::from([(7, order)]) };
+        let mut store: ItemStore = from_slice(&to_vec(&legacy).unwrap()).expect("Baseline item store did not load");
+        assert_eq!(store.buy_orders[&7].expires_at, UNKNOWN_EXPIRY);
+        assert_eq!(store.buy_orders[&7].range, OrderRange::Station);
+
+        assert!(store.migrate_legacy_orders(1_000));
+        let expires_at = store.buy_orders[&7].expires_at;
+        assert_eq!(expires_at, 1_000 + MAX_ORDER_DURATION_DAYS as i64 * 86_400);
+
+        let mut store = round_trip(&round_trip(&store));
+        assert_eq!(store.buy_orders[&7].expires_at, expires_at);
+        assert!(!store.migrate_legacy_orders(2_000));
+        assert_eq!(store.buy_orders[&7].expires_at, expires_at);
+    }
+
+    // A - B - C are one region in a line, D is another region linked to C, E has no gates
+    fn range_graph() -> RouteGraph {
+        graph(&[("A", "R1", 5), ("B", "R1", 5), ("C", "R1", 5), ("D", "R2", 5), ("E", "R1", 5)], &[("A", "B"), ("B", "C"), ("C", "D")])
+    }
+
+    fn reaches(range: OrderRange, order: (&str, &str), other: (&str, &str)) -> bool {
+        range.reaches(&range_graph(), (&order.0.to_string(), &order.1.to_string()), (&other.0.to_string(), &other.1.to_string()))
+    }
+
+    #[test]
+    fn station_range_only_reaches_the_same_station() {
+        assert!(reaches(OrderRange::Station, ("s1", "A"), ("s1", "A")));
+        assert!(!reaches(OrderRange::Station, ("s1", "A"), ("s2", "A")));
+    }
+
+    #[test]
+    fn system_range_reaches_every_station_in_the_system() {
+        assert!(reaches(OrderRange::System, ("s1", "A"), ("s2", "A")));
+        assert!(!reaches(OrderRange::System, ("s1", "A"), ("s3", "B")));
+    }
+
+    #[test]
+    fn jump_range_counts_jumps() {
+        assert!(reaches(OrderRange::Jumps(0), ("s1", "A"), ("s2", "A")));
+        assert!(!reaches(OrderRange::Jumps(0), ("s1", "A"), ("s3", "B")));
+        assert!(reaches(OrderRange::Jumps(2), ("s1", "A"), ("s3", "C")));
+        assert!(!reaches(OrderRange::Jumps(2), ("s1", "A"), ("s4", "D")));
+        // no route at all is never in range, however big the range
+        assert!(!reaches(OrderRange::Jumps(u32::MAX), ("s1", "A"), ("s5", "E")));
+        assert!(!reaches(OrderRange::Jumps(u32::MAX), ("s1", "A"), ("s6", "Nowhere")));
+    }
+
+    #[test]
+    fn region_range_reaches_the_whole_region() {
+        assert!(reaches(OrderRange::Region, ("s1", "A"), ("s3", "C")));
+        // same region without a gate route still counts
+        assert!(reaches(OrderRange::Region, ("s1", "A"), ("s5", "E")));
+        assert!(!reaches(OrderRange::Region, ("s1", "A"), ("s4", "D")));
+        assert!(!reaches(OrderRange::Region, ("s1", "A"), ("s6", "Nowhere")));
+        assert!(!reaches(OrderRange::Region, ("s6", "Nowhere"), ("s6", "Nowhere")));
+    }
+
+    #[test]
+    fn buy_order_pays_per_item_and_final_fill_takes_remaining_escrow() {
+        let mut order = BuyOrder::new(String::from("ore"), 3, String::from("p"), 100, String::from("station"), OrderRange::Station, 1);
+        // only the items handed over are paid for
+        assert_eq!(order.satisfy(Stack::new(String::from("ore"), 1)), 33);
+        assert_eq!(order.escrow, 67);
+        assert_eq!(order.satisfy(Stack::new(String::from("ore"), 1)), 33);
+        // 34 left for the last item, rounding would otherwise strand 1
+        assert_eq!(order.satisfy(Stack::new(String::from("ore"), 1)), 34);
+        assert_eq!(order.escrow, 0);
+        assert!(order.is_empty());
+    }
+
+    #[test]
+    fn orders_without_a_positive_price_or_count_are_rejected() {
+        let mut store = ItemStore::new(String::from("ore"));
+        let p = String::from("p");
+        for price in [0, -1, i64::MIN] {
+            assert_eq!(store.add_buy_order(&p, 10, price, String::from("station"), OrderRange::Station, 1).unwrap_err(), "Price must be positive");
+            assert_eq!(store.add_sell_order(&p, Stack::new(String::from("ore"), 10), price, String::from("station"), 1).unwrap_err(), "Price must be positive");
+        }
+        assert!(store.add_buy_order(&p, 0, 10, String::from("station"), OrderRange::Station, 1).is_err());
+        assert!(store.add_sell_order(&p, Stack::new(String::from("ore"), 0), 10, String::from("station"), 1).is_err());
+        assert!(store.buy_orders.is_empty() && store.sell_orders.is_empty());
+        assert!(store.add_buy_order(&p, 10, 1, String::from("station"), OrderRange::Station, 1).is_ok());
+    }
+
+    #[test]
+    fn prices_that_overflow_are_rejected() {
+        let mut store = ItemStore::new(String::from("ore"));
+        let p = String::from("p");
+        assert_eq!(store.add_buy_order(&p, 2, i64::MAX / 2 + 1, String::from("station"), OrderRange::Station, 1).unwrap_err(), "Price too high");
+        assert_eq!(store.add_sell_order(&p, Stack::new(String::from("ore"), 2), i64::MAX, String::from("station"), 1).unwrap_err(), "Price too high");
+
+        let buy = store.add_buy_order(&p, 10, 5, String::from("station"), OrderRange::Station, 1).unwrap();
+        let sell = store.add_sell_order(&p, Stack::new(String::from("ore"), 10), 5, String::from("station"), 1).unwrap();
+        assert_eq!(store.modify_buy_order(&p, buy, i64::MAX, i64::MAX, 0).unwrap_err(), "Price too high");
+        assert_eq!(store.modify_sell_order(&p, sell, i64::MAX, i64::MAX, 0).unwrap_err(), "Price too high");
+        assert_eq!(store.modify_buy_order(&p, buy, -5, i64::MAX, 0).unwrap_err(), "Price must be positive");
+        // nothing was changed by the rejected requests
+        assert_eq!(store.get_buy_order(buy).unwrap().escrow, 50);
+        assert_eq!(store.get_sell_order(sell).unwrap().cost_per_item, 5);
+        assert_eq!(store.modify_buy_order(&p, buy, 7, i64::MAX, 0), Ok(20));
+        assert_eq!(order_value(u32::MAX, i64::MAX).unwrap_err(), "Price too high");
+    }
+}
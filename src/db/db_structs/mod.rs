@@ -2,4 +2,10 @@ pub mod account;
 pub mod hanger;
 pub mod ship_in_space;
 pub mod bank;
-pub mod market;
\ No newline at end of file
+pub mod market;
+pub mod bookmark;
+pub mod extractor;
+pub mod production;
+pub mod skills;
+pub mod statistics;
+pub mod insurance;
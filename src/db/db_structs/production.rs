@@ -0,0 +1,33 @@
+use std::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+
+use chrono::{Utc, Duration};
+use serde::{Serialize, Deserialize};
+
+use crate::inventory::{InvId, Blueprint, BlueprintId};
+
+pub type JobId = u64;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProductionJob {
+    pub job_id: JobId,
+    pub player: String,
+    pub blueprint: BlueprintId,
+    pub runs: u32,
+    pub location: InvId, // station inventory the inputs came from and the outputs go to
+    pub time_started: String,
+    pub finishes_at: i64, // unix timestamp
+}
+
+impl ProductionJob {
+    /// time_mult scales the blueprint's duration, it comes from the player's skills
+    pub fn new(player: &String, blueprint: &Blueprint, runs: u32, location: InvId, time_mult: f64) -> Self {
+        let time = Utc::now();
+        let val = format!("job{}-{}-{}-{}-{}", player, blueprint.id, runs, location, time.to_rfc3339());
+        let mut s = DefaultHasher::new();
+        val.hash(&mut s);
+        let id = s.finish();
+        let finish = time + Duration::milliseconds((blueprint.duration_s * runs as f64 * time_mult * 1000.0) as i64);
+        ProductionJob { job_id: id, player: player.clone(), blueprint: blueprint.id.clone(), runs, location, time_started: time.to_rfc3339(), finishes_at: finish.timestamp() }
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Serialize, Deserialize};
 
-use crate::galaxy::components::{Ship, Navigation, Transform, GameObject};
+use crate::galaxy::components::{Ship, Navigation, Transform, GameObject, Health};
 
 // DON'T SERIALIZE THE SENSOR STATE, WE NEED IT TO BE RESET WHEN THE SHIP LOADS BACK IN
 #[derive(Serialize, Deserialize, Debug)]
@@ -9,5 +9,7 @@ pub struct ShipInSpace {
     pub player_name: String,
     pub navigation: Navigation,
     pub transform: Transform,
-    pub game_object: GameObject
+    pub game_object: GameObject,
+    #[serde(default)]
+    pub health: Option<Health>, // None for ships saved before damage existed
 }
\ No newline at end of file
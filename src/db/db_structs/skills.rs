@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::skills::{SkillTable, SkillId, MAX_SKILL_LEVEL};
+
+// how many levels can be queued up at once
+const MAX_QUEUE_LEN: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerSkills {
+    pub name: String,
+    pub levels: HashMap<SkillId, u8>,
+    pub queue: Vec<(SkillId, u8)>, // skill and the level it trains to, the head is always the one training
+    pub head_started_at: i64, // unix timestamp the head of the queue started training at
+}
+
+impl PlayerSkills {
+    pub fn new(name: String) -> Self {
+        PlayerSkills { name, levels: HashMap::new(), queue: vec![], head_started_at: 0 }
+    }
+
+    pub fn level(&self, id: &SkillId) -> u8 {
+        self.levels.get(id).copied().unwrap_or(0)
+    }
+
+    /// the level a skill will be at once everything queued has trained
+    pub fn queued_level(&self, id: &SkillId) -> u8 {
+        self.queue.iter().filter(|(s, _)| s == id).map(|(_, l)| *l).max().unwrap_or(self.level(id))
+    }
+
+    /// queues the next level of a skill, prerequisites may be met by levels that are queued ahead of it
+    pub fn enqueue(&mut self, id: &SkillId, table: &SkillTable, now: i64) -> Result<u8, String> {
+        let skill = table.get(id).ok_or(format!("Unknown skill {}", id))?;
+        if self.queue.len() >= MAX_QUEUE_LEN {
+            return Err(format!("Can not queue more than {} skills", MAX_QUEUE_LEN));
+        }
+
+        let level = self.queued_level(id) + 1;
+        if level > MAX_SKILL_LEVEL {
+            return Err(format!("{} is already at max level", skill.name));
+        }
+
+        for (pre, pre_level) in skill.prerequisites.iter() {
+            if self.queued_level(pre) < *pre_level {
+                return Err(format!("{} requires {} level {}", skill.name, pre, pre_level));
+            }
+        }
+
+        if self.queue.is_empty() {
+            self.head_started_at = now;
+        }
+        self.queue.push((id.clone(), level));
+        Ok(level)
+    }
+
+    /// takes an entry out of the queue along with every later level of that skill, and anything that depended on them
+    pub fn remove(&mut self, index: usize, table: &SkillTable, now: i64) -> Result<(), String> {
+        let (id, level) = self.queue.get(index).cloned().ok_or(String::from("Nothing queued at that position"))?;
+        if index == 0 {
+            // progress on the head is lost
+            self.head_started_at = now;
+        }
+
+        self.queue.retain(|(s, l)| !(*s == id && *l >= level));
+
+        // drop anything whose prerequisites are no longer met, in queue order
+        let mut kept: Vec<(SkillId, u8)> = vec![];
+        for (s, l) in self.queue.drain(..) {
+            let met = table.get(&s).map(|sk| sk.prerequisites.iter().all(|(pre, pre_level)| {
+                let queued = kept.iter().filter(|(k, _)| k == pre).map(|(_, kl)| *kl).max().unwrap_or(0);
+                self.levels.get(pre).copied().unwrap_or(0).max(queued) >= *pre_level
+            })).unwrap_or(false);
+            if met {
+                kept.push((s, l));
+            }
+        }
+        self.queue = kept;
+        Ok(())
+    }
+
+    /// completes every level that finished training at or before now, this is what lets training continue while offline
+    pub fn advance(&mut self, now: i64, table: &SkillTable) -> Vec<(SkillId, u8)> {
+        let mut done = vec![];
+        while let Some(finish) = self.head_finishes_at(table) {
+            if finish > now {
+                break;
+            }
+            let (id, level) = self.queue.remove(0);
+            self.levels.insert(id.clone(), level);
+            self.head_started_at = finish;
+            done.push((id, level));
+        }
+        done
+    }
+
+    pub fn head_finishes_at(&self, table: &SkillTable) -> Option<i64> {
+        let (id, level) = self.queue.first()?;
+        let skill = table.get(id)?;
+        Some(self.head_started_at + skill.train_time_s(*level))
+    }
+
+    /// when each entry of the queue will finish training, in queue order
+    pub fn queue_times(&self, table: &SkillTable) -> Vec<i64> {
+        let mut t = self.head_started_at;
+        self.queue.iter().map(|(id, level)| {
+            t += table.get(id).map(|s| s.train_time_s(*level)).unwrap_or(0);
+            t
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::skills::Skill;
+    use super::*;
+
+    fn table() -> SkillTable {
+        let skill = |id: &str, prerequisites: Vec<(SkillId, u8)>| (String::from(id), Skill { id: String::from(id), name: String::from(id), rank: 1, prerequisites, effects: vec![] });
+        SkillTable::from([skill("a", vec![]), skill("b", vec![(String::from("a"), 1)])])
+    }
+
+    fn a() -> SkillId { String::from("a") }
+    fn b() -> SkillId { String::from("b") }
+
+    #[test]
+    fn queue_keeps_order_and_prerequisites() {
+        let table = table();
+        let mut s = PlayerSkills::new(String::from("p"));
+        assert!(s.enqueue(&b(), &table, 0).is_err());
+        assert_eq!(s.enqueue(&a(), &table, 0), Ok(1));
+        assert_eq!(s.enqueue(&a(), &table, 0), Ok(2));
+        assert_eq!(s.enqueue(&b(), &table, 0), Ok(1));
+        assert_eq!(s.queue, vec![(a(), 1), (a(), 2), (b(), 1)]);
+        assert_eq!(s.queue_times(&table), vec![600, 2400, 3000]);
+    }
+
+    #[test]
+    fn finished_levels_carry_over_to_the_next() {
+        let table = table();
+        let mut s = PlayerSkills::new(String::from("p"));
+        s.enqueue(&a(), &table, 0).unwrap();
+        s.enqueue(&a(), &table, 0).unwrap();
+
+        assert_eq!(s.advance(1000, &table), vec![(a(), 1)]);
+        assert_eq!(s.level(&a()), 1);
+        // the next level started when the last one finished, not when advance was called
+        assert_eq!(s.head_started_at, 600);
+        assert_eq!(s.head_finishes_at(&table), Some(2400));
+
+        assert!(s.advance(2399, &table).is_empty());
+        assert_eq!(s.advance(10_000, &table), vec![(a(), 2)]);
+        assert!(s.queue.is_empty());
+        assert_eq!(s.head_finishes_at(&table), None);
+    }
+
+    #[test]
+    fn removing_the_training_entry_drops_what_depends_on_it() {
+        let table = table();
+        let mut s = PlayerSkills::new(String::from("p"));
+        s.enqueue(&a(), &table, 0).unwrap();
+        s.enqueue(&a(), &table, 0).unwrap();
+        s.enqueue(&b(), &table, 0).unwrap();
+
+        s.remove(0, &table, 300).unwrap();
+        assert!(s.queue.is_empty());
+        assert_eq!(s.head_started_at, 300);
+        assert!(s.remove(0, &table, 300).is_err());
+    }
+
+    #[test]
+    fn removing_a_later_entry_keeps_training_progress() {
+        let table = table();
+        let mut s = PlayerSkills::new(String::from("p"));
+        s.enqueue(&a(), &table, 0).unwrap();
+        s.enqueue(&a(), &table, 0).unwrap();
+        s.enqueue(&b(), &table, 0).unwrap();
+
+        s.remove(1, &table, 300).unwrap();
+        assert_eq!(s.queue, vec![(a(), 1), (b(), 1)]);
+        assert_eq!(s.head_started_at, 0);
+    }
+}
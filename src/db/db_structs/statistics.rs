@@ -0,0 +1,121 @@
+use serde::{Serialize, Deserialize};
+
+use chrono::Utc;
+
+use crate::inventory::{ItemId, InvId};
+
+use super::market::StoreTransaction;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// one fill of a buy or sell order
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MarketFill {
+    pub item: ItemId,
+    pub order_id: u64,
+    pub count: u32,
+    pub total_cost: i64,
+    pub location: InvId,
+    pub time: i64, // unix timestamp
+}
+
+impl MarketFill {
+    pub fn new(order_id: u64, t: &StoreTransaction) -> Self {
+        MarketFill { item: t.purchased_stack.id.clone(), order_id, count: t.purchased_stack.count, total_cost: t.cost, location: t.location.clone(), time: Utc::now().timestamp() }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DailyStats {
+    pub day: i64, // days since the unix epoch
+    pub volume: u64, // items traded
+    pub high: f64, // highest price per item
+    pub low: f64, // lowest price per item
+    pub average: f64, // average price per item, weighted by volume
+    pub order_count: u32, // number of fills
+    pub value: i64, // total paid across every fill
+}
+
+impl DailyStats {
+    pub fn new(day: i64) -> Self {
+        DailyStats { day, volume: 0, high: 0.0, low: 0.0, average: 0.0, order_count: 0, value: 0 }
+    }
+
+    pub fn add_fill(&mut self, f: &MarketFill) {
+        if f.count == 0 {
+            return;
+        }
+        let price = f.total_cost as f64 / f.count as f64;
+        if self.order_count == 0 {
+            self.high = price;
+            self.low = price;
+        }
+        self.volume += f.count as u64;
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.order_count += 1;
+        self.value += f.total_cost;
+        self.average = self.value as f64 / self.volume as f64;
+    }
+}
+
+/// days since the unix epoch
+pub fn fill_day(f: &MarketFill) -> i64 {
+    f.time.div_euclid(SECONDS_PER_DAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    /// rolls fills up per day the way statistics_record_fill does, oldest first, days without fills are left out
+    fn daily_stats(fills: &[MarketFill]) -> Vec<DailyStats> {
+        let mut days: BTreeMap<i64, DailyStats> = BTreeMap::new();
+        for f in fills.iter().filter(|f| f.count > 0) {
+            days.entry(fill_day(f)).or_insert_with(|| DailyStats::new(fill_day(f))).add_fill(f);
+        }
+        days.into_values().collect()
+    }
+
+    fn fill(time: i64, count: u32, total_cost: i64) -> MarketFill {
+        MarketFill { item: String::from("ore"), order_id: 1, count, total_cost, location: String::from("station"), time }
+    }
+
+    #[test]
+    fn no_fills_no_days() {
+        assert!(daily_stats(&[]).is_empty());
+        assert!(daily_stats(&[fill(100, 0, 0)]).is_empty());
+    }
+
+    #[test]
+    fn one_fill() {
+        let days = daily_stats(&[fill(SECONDS_PER_DAY * 3 + 5, 10, 250)]);
+        assert_eq!(days.len(), 1);
+        let d = &days[0];
+        assert_eq!(d.day, 3);
+        assert_eq!(d.volume, 10);
+        assert_eq!(d.order_count, 1);
+        assert_eq!((d.high, d.low, d.average), (25.0, 25.0, 25.0));
+        assert_eq!(d.value, 250);
+    }
+
+    #[test]
+    fn several_fills_over_two_days() {
+        let days = daily_stats(&[
+            fill(SECONDS_PER_DAY + 1, 10, 100), // 10 each
+            fill(2 * SECONDS_PER_DAY - 1, 30, 600), // 20 each
+            fill(2 * SECONDS_PER_DAY, 5, 25), // next day
+        ]);
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].day, 1);
+        assert_eq!(days[0].volume, 40);
+        assert_eq!(days[0].order_count, 2);
+        assert_eq!((days[0].high, days[0].low), (20.0, 10.0));
+        // weighted by volume, not the mean of the two prices
+        assert_eq!(days[0].average, 17.5);
+        assert_eq!(days[1].day, 2);
+        assert_eq!(days[1].volume, 5);
+    }
+}
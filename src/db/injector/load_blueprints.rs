@@ -0,0 +1,17 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::inventory::{BlueprintTable, BlueprintId, Blueprint, Stack};
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LBlueprint {
+    inputs: Vec<Stack>,
+    outputs: Vec<Stack>,
+    duration_s: f64
+}
+
+pub fn load_blueprint(blueprints: HashMap<BlueprintId, LBlueprint>) -> BlueprintTable {
+    blueprints.into_iter().map(|(k, v)| (k.clone(), Blueprint { id: k, inputs: v.inputs, outputs: v.outputs, duration_s: v.duration_s })).collect()
+}
@@ -2,9 +2,17 @@ use std::{collections::HashMap, f64::consts::PI};
 
 use nalgebra::{Vector3, UnitQuaternion, UnitVector3};
 
-use crate::galaxy::{bundles::celestials::{BSun, BPlanet, BMoon, BAsteroidBelt}, galaxy_map::{GalaxyMap, GMLink, GMSystem}};
+use crate::galaxy::{bundles::celestials::{BSun, BPlanet, BMoon, BAsteroidBelt, BAsteroid}, galaxy_map::{GalaxyMap, GMLink, GMSystem}, components::{AsteroidBelt, Harvestable, Celestial, Transform}};
 
-use super::{galaxy_structs::LGalaxy, orbit::{Orbit, compute_soi}, orbit::orbit_to_csv};
+use super::{galaxy_structs::{LGalaxy, LAsteroidMaterials, LMoonMaterials, LPlanetaryMaterials}, resource_structs::{LAsteroidOres, LMoonOres, LPlanetItems}, orbit::{Orbit, compute_soi}, orbit::orbit_to_csv};
+
+// asteroids in a belt of a system with an asteroid productivity of 1
+const BASE_ASTEROIDS_PER_BELT: f32 = 20.0;
+
+// units per hour for a weight of 1 in a system with a productivity of 1
+const MOON_TIER_YIELD_PER_HOUR: [f32; 4] = [120.0, 60.0, 25.0, 8.0];
+const PLANET_BASE_YIELD_PER_HOUR: f32 = 200.0;
+const PLANET_RARE_YIELD_PER_HOUR: f32 = 20.0;
 
 
 /// PRECONDITION: ALL STARS HAVE UNIQUE NAME
@@ -32,7 +40,7 @@ pub fn load_stars(loaded_gal: &LGalaxy) -> HashMap<String, BSun> {
 }
 
 /// PRECONDITION: ALL PLANETS HAVE UNIQUE NAME
-pub fn load_planets(loaded_gal: &LGalaxy, suns: &HashMap<String, BSun>) -> HashMap<String, BPlanet> {
+pub fn load_planets(loaded_gal: &LGalaxy, suns: &HashMap<String, BSun>, mats: &LPlanetItems) -> HashMap<String, BPlanet> {
     let mut planets = HashMap::new();
 
     for r in loaded_gal.regions.iter() {
@@ -66,7 +74,8 @@ pub fn load_planets(loaded_gal: &LGalaxy, suns: &HashMap<String, BSun>) -> HashM
                 let warp_point = Vector3::from(warp_in_arr) + offset;
                 //println!("warp_point: {:.2?}", warp_point / 1.4959e11);
 
-                let bplanet = BPlanet::new(sys_name, &planet.name, &planet_type, planet.body_info.size_m, planet.body_info.mass_kg, offset, rot, warp_point);
+                let yields = planet_yields(&planet.resources, mats, sys_coord.sys.planet_productivity);
+                let bplanet = BPlanet::new(sys_name, &planet.name, &planet_type, Celestial { radius_m: planet.body_info.size_m, mass_kg: planet.body_info.mass_kg }, Transform { pos: offset, rot, vel: Vector3::zeros() }, warp_point, Harvestable::new(yields));
                 let test = planets.insert(planet.name.clone(), bplanet);
                 if test.is_some() {
                     eprintln!("ERROR: TWO PLANETS WITH DUPLICATE NAMES: {}", test.unwrap().game_object.path.name);
@@ -79,7 +88,7 @@ pub fn load_planets(loaded_gal: &LGalaxy, suns: &HashMap<String, BSun>) -> HashM
 }
 
 /// PRECONDITION: ALL MOONS HAVE UNIQUE NAME
-pub fn load_moons(loaded_gal: &LGalaxy, planets: &HashMap<String, BPlanet>) -> HashMap<String, BMoon> {
+pub fn load_moons(loaded_gal: &LGalaxy, planets: &HashMap<String, BPlanet>, ores: &LMoonOres) -> HashMap<String, BMoon> {
     let mut moons = HashMap::new();
 
     for r in loaded_gal.regions.iter() {
@@ -113,7 +122,8 @@ pub fn load_moons(loaded_gal: &LGalaxy, planets: &HashMap<String, BPlanet>) -> H
                     let warp_point = Vector3::from(warp_in_arr) + offset;
                 
     
-                    let bmoon = BMoon::new(sys_name, &m.name, &moon_type, planet.body_info.size_m, planet.body_info.mass_kg, offset, rot, warp_point);
+                    let yields = moon_yields(&m.resources, ores, sys_coord.sys.moon_productivity);
+                    let bmoon = BMoon::new(sys_name, &m.name, &moon_type, Celestial { radius_m: planet.body_info.size_m, mass_kg: planet.body_info.mass_kg }, Transform { pos: offset, rot, vel: Vector3::zeros() }, warp_point, Harvestable::new(yields));
                     let test = moons.insert(m.name.clone(), bmoon);
                     if test.is_some() {
                         eprintln!("ERROR: TWO MOONS WITH DUPLICATE NAMES: {}", test.unwrap().game_object.path.name);
@@ -125,8 +135,30 @@ pub fn load_moons(loaded_gal: &LGalaxy, planets: &HashMap<String, BPlanet>) -> H
     moons
 }
 
+/// hourly output of a planet office, the rare material only depends on the rare occurence
+fn planet_yields(materials: &LPlanetaryMaterials, mats: &LPlanetItems, productivity: f32) -> Vec<(String, f32)> {
+    let base = PLANET_BASE_YIELD_PER_HOUR * materials.base_occurence * productivity;
+    let rates = [
+        (&mats.a, base * materials.a_weight),
+        (&mats.b, base * materials.b_weight),
+        (&mats.c, base * materials.c_weight),
+        (&mats.rare, PLANET_RARE_YIELD_PER_HOUR * materials.rare_occurence * productivity)
+    ];
+    rates.into_iter().filter(|(_, r)| *r > 0.0).map(|(item, r)| (item.clone(), r)).collect()
+}
+
+/// hourly output of a moon extractor, higher tiers come out slower
+fn moon_yields(materials: &LMoonMaterials, ores: &LMoonOres, productivity: f32) -> Vec<(String, f32)> {
+    let weights = [&materials.t1, &materials.t2, &materials.t3, &materials.t4];
+    weights.iter().zip(ores.tiers().iter()).zip(MOON_TIER_YIELD_PER_HOUR.iter())
+        .flat_map(|((w, items), base)| [w.a, w.b, w.c].into_iter().zip(items.all()).map(move |(w, item)| (item, w * base * productivity)))
+        .filter(|(_, r)| *r > 0.0)
+        .map(|(item, r)| (item.clone(), r))
+        .collect()
+}
+
 /// PRECONDITION: ALL ASTERIOD BELTS HAVE UNIQUE NAME
-pub fn load_belts(loaded_gal: &LGalaxy, suns: &HashMap<String, BSun>) -> HashMap<String, BAsteroidBelt> {
+pub fn load_belts(loaded_gal: &LGalaxy, suns: &HashMap<String, BSun>, ores: &LAsteroidOres) -> HashMap<String, BAsteroidBelt> {
     let mut belts = HashMap::new();
 
     for r in loaded_gal.regions.iter() {
@@ -143,7 +175,9 @@ pub fn load_belts(loaded_gal: &LGalaxy, suns: &HashMap<String, BSun>) -> HashMap
                 let offset = Vector3::new(x, y, z);
             
 
-                let bbelt = BAsteroidBelt::new(sys_name, &belt.name, offset, UnitQuaternion::identity(), offset + Vector3::new(0.0, 0.0, 1000.0));
+                let composition = belt_composition(&belt.resources, ores);
+                let target_count = (BASE_ASTEROIDS_PER_BELT * sys_coord.sys.asteroid_productivity).round().max(1.0) as u32;
+                let bbelt = BAsteroidBelt::new(sys_name, &belt.name, offset, UnitQuaternion::identity(), offset + Vector3::new(0.0, 0.0, 1000.0), AsteroidBelt::new(composition, target_count));
                 let test = belts.insert(belt.name.clone(), bbelt);
                 if test.is_some() {
                     eprintln!("ERROR: TWO ASTEROID BELTS WITH DUPLICATE NAMES: {}", test.unwrap().game_object.path.name);
@@ -155,6 +189,32 @@ pub fn load_belts(loaded_gal: &LGalaxy, suns: &HashMap<String, BSun>) -> HashMap
     belts
 }
 
+/// pairs the tier weights of a belt with the ore they stand for, dropping anything that can never spawn
+fn belt_composition(materials: &LAsteroidMaterials, ores: &LAsteroidOres) -> Vec<(String, f32)> {
+    let weights = [&materials.t1, &materials.t2, &materials.t3, &materials.t4, &materials.t5];
+    weights.iter().zip(ores.tiers().iter())
+        .flat_map(|(w, items)| [w.a, w.b, w.c].into_iter().zip(items.all()))
+        .filter(|(w, _)| *w > 0.0)
+        .map(|(w, item)| (item.clone(), w))
+        .collect()
+}
+
+/// fills every belt up to its target count
+pub fn load_asteroids(belts: &mut HashMap<String, BAsteroidBelt>) -> Vec<BAsteroid> {
+    let mut rng = rand::thread_rng();
+    let mut asteroids = vec![];
+    for b in belts.values_mut() {
+        let center = b.warp_target.warp_point;
+        for _ in 0..b.belt.target_count {
+            match BAsteroid::new(&mut b.belt, &b.game_object.path, center, &mut rng) {
+                Some(a) => asteroids.push(a),
+                None => { eprintln!("Belt {:?} has no ore to spawn", b.game_object.path); break; }
+            }
+        }
+    }
+    asteroids
+}
+
 pub fn load_system_positions(gal: &LGalaxy) -> HashMap<String, Vector3<f64>> {
     let mut spm = HashMap::new();
     for r in gal.regions.iter() {
@@ -175,7 +235,8 @@ pub fn load_galaxy_map(gal: &LGalaxy) -> GalaxyMap {
             let region = r.name.clone();
             let name = name.clone();
             let sun_temp = sys.sys.star.temp;
-            systems.push(GMSystem { name, region, sun_temp, pos: pos })
+            let security_level = sys.sys.security_level;
+            systems.push(GMSystem { name, region, sun_temp, security_level, pos: pos })
         }
 
         for con in r.connections.iter() {
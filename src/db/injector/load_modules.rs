@@ -0,0 +1,18 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::inventory::{ModuleTable, ModuleId, Module, SlotType, StatModifier, WeaponStats};
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LModule {
+    slot: SlotType,
+    modifiers: Vec<StatModifier>,
+    #[serde(default)]
+    weapon: Option<WeaponStats>
+}
+
+pub fn load_module(modules: HashMap<ModuleId, LModule>) -> ModuleTable {
+    modules.into_iter().map(|(k, v)| (k.clone(), Module { id: k, slot: v.slot, modifiers: v.modifiers, weapon: v.weapon })).collect()
+}
@@ -0,0 +1,16 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::inventory::{RefineTable, ItemId, Refine, Stack};
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LRefine {
+    batch: u32,
+    outputs: Vec<Stack>
+}
+
+pub fn load_refine(refines: HashMap<ItemId, LRefine>) -> RefineTable {
+    refines.into_iter().map(|(k, v)| (k.clone(), Refine { ore: k, batch: v.batch, outputs: v.outputs })).collect()
+}
@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{inventory::{ShipClassTable, ShipClassId, ShipClass}, galaxy::components::Stats};
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LSlots {
+    high: usize,
+    mid: usize,
+    low: usize
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LShipClass {
+    name: String,
+    stats: Stats,
+    cargo: u32,
+    signature_m: f64,
+    slots: LSlots,
+    #[serde(default)]
+    insured_value: i64
+}
+
+pub fn load_ship_class(classes: HashMap<ShipClassId, LShipClass>) -> ShipClassTable {
+    classes.into_iter().map(|(k, v)| (k.clone(), ShipClass {
+        id: k,
+        name: v.name,
+        stats: v.stats,
+        cargo_vunits: v.cargo,
+        signature_m: v.signature_m,
+        high_slots: v.slots.high,
+        mid_slots: v.slots.mid,
+        low_slots: v.slots.low,
+        insured_value: v.insured_value
+    })).collect()
+}
@@ -0,0 +1,18 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::skills::{SkillTable, SkillId, Skill, SkillEffect};
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LSkill {
+    name: String,
+    rank: u32,
+    prerequisites: Vec<(SkillId, u8)>,
+    effects: Vec<SkillEffect>
+}
+
+pub fn load_skill(skills: HashMap<SkillId, LSkill>) -> SkillTable {
+    skills.into_iter().map(|(k, v)| (k.clone(), Skill { id: k, name: v.name, rank: v.rank, prerequisites: v.prerequisites, effects: v.effects })).collect()
+}
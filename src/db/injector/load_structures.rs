@@ -23,7 +23,7 @@ pub fn load_stations(input_stations: LStationList, planets: &HashMap<String, BPl
 
         let transform = Transform { pos: abs, rot: face_away_from_planet, vel: Vector3::zeros() };
 
-        let station = BStation::new(&s.system, &s.name, transform, warp_point, undock_offset, 2500.0);
+        let station = BStation::new(&s.system, &s.name, transform, warp_point, undock_offset, 2500.0, s.refining_tax);
         stations.push(station);
     }
 
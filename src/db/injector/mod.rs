@@ -2,9 +2,9 @@ use std::collections::HashMap;
 
 use bevy_ecs::world::World;
 
-use crate::{inventory::{ItemTable, ItemId}, galaxy::resources::galaxy_map::GalaxyMapRes};
+use crate::{inventory::{ItemTable, ItemId, ModuleTable, ModuleId, ShipClassTable, ShipClassId, BlueprintTable, BlueprintId, RefineTable, ItemTag}, skills::{SkillTable, SkillId, MAX_SKILL_LEVEL}, galaxy::resources::galaxy_map::GalaxyMapRes, shared::ObjectType};
 
-use self::{galaxy_structs::LGalaxy, structure_structs::LStationList, load_items::LItem};
+use self::{galaxy_structs::LGalaxy, structure_structs::LStationList, load_items::LItem, load_modules::LModule, load_ship_classes::LShipClass, load_blueprints::LBlueprint, load_refining::LRefine, load_skills::LSkill, resource_structs::LResourceTable};
 
 mod orbit;
 
@@ -12,11 +12,17 @@ mod galaxy_structs;
 mod load_galaxy;
 
 mod structure_structs;
+mod resource_structs;
 mod load_structures;
 
 mod load_items;
+mod load_modules;
+mod load_ship_classes;
+mod load_blueprints;
+mod load_refining;
+mod load_skills;
 
-pub fn inject_statics(path_to_assets: String) -> World {
+pub fn inject_statics(path_to_assets: String, items: &ItemTable) -> World {
     let mut world = World::default();
 
     let gal_file = std::fs::read_to_string(format!("{}/galaxy.json", path_to_assets)).expect("Could not read galaxy file");
@@ -25,12 +31,20 @@ pub fn inject_statics(path_to_assets: String) -> World {
     let system_positions = load_galaxy::load_system_positions(&gal);
 
     let gmap = load_galaxy::load_galaxy_map(&gal);
-    world.insert_resource(GalaxyMapRes { gmap }); //TODO: This is not with the rest of the resources, but since this is not modified I am ok with it
 
     let suns = load_galaxy::load_stars(&gal);
-    let planets = load_galaxy::load_planets(&gal, &suns);
-    let moons = load_galaxy::load_moons(&gal, &planets);
-    let belts = load_galaxy::load_belts(&gal, &suns);
+
+    let resource_file = std::fs::read_to_string(format!("{}/resources.json", path_to_assets)).expect("Could not read resources file");
+    let resources: LResourceTable = serde_json::from_str(resource_file.as_str()).expect("Could not deserialize resources file");
+    for item in resources.all_items() {
+        if !items.contains_key(item) { panic!("Resource {} is not an item", item); }
+    }
+
+    let planets = load_galaxy::load_planets(&gal, &suns, &resources.planet);
+    let moons = load_galaxy::load_moons(&gal, &planets, &resources.moon);
+
+    let mut belts = load_galaxy::load_belts(&gal, &suns, &resources.asteroid);
+    let asteroids = load_galaxy::load_asteroids(&mut belts);
 
     let station_file = std::fs::read_to_string(format!("{}/stations.json", path_to_assets)).expect("Could not read stations file");
     let stations: LStationList = serde_json::from_str(station_file.as_str()).expect("Could not deserialize stations file");
@@ -38,10 +52,13 @@ pub fn inject_statics(path_to_assets: String) -> World {
     let stations = load_structures::load_stations(stations, &planets);
     let gates = load_structures::compute_gates(&gal, &planets, &system_positions);
 
+    world.insert_resource(GalaxyMapRes::new(gmap, &gates)); //TODO: This is not with the rest of the resources, but since this is not modified I am ok with it
+
     world.spawn_batch(suns.into_values());
     world.spawn_batch(planets.into_values());
     world.spawn_batch(moons.into_values());
     world.spawn_batch(belts.into_values());
+    world.spawn_batch(asteroids);
 
     world.spawn_batch(stations.into_iter());
     world.spawn_batch(gates.into_iter());
@@ -53,5 +70,89 @@ pub fn inject_statics(path_to_assets: String) -> World {
 pub fn load_items(path_to_assets: String) -> ItemTable {
     let items_file = std::fs::read_to_string(format!("{}/items.json", path_to_assets)).expect("Could not read item file");
     let items: HashMap<ItemId, LItem> = serde_json::from_str(items_file.as_str()).expect("Could not parse items file");
-    load_items::load_item(items)
-}
\ No newline at end of file
+    let items = load_items::load_item(items);
+    for item in items.values() {
+        if let crate::inventory::Mapping::Structure(t) = &item.mapping {
+            if !matches!(t, ObjectType::MoonExtractor | ObjectType::PlanetOffice) { panic!("Item {} maps to structure {:?} which can not be deployed", item.id, t); }
+        }
+    }
+    items
+}
+
+pub fn load_modules(path_to_assets: String, items: &ItemTable) -> ModuleTable {
+    let modules_file = std::fs::read_to_string(format!("{}/modules.json", path_to_assets)).expect("Could not read module file");
+    let modules: HashMap<ModuleId, LModule> = serde_json::from_str(modules_file.as_str()).expect("Could not parse modules file");
+    let modules = load_modules::load_module(modules);
+    for m in modules.values() {
+        if let Some(w) = &m.weapon {
+            if m.slot != crate::inventory::SlotType::High { panic!("Weapon module {} must be fitted in a high slot", m.id); }
+            if w.cycle_s <= 0.0 { panic!("Weapon module {} has a cycle time of 0", m.id); }
+            if w.kind == crate::inventory::WeaponKind::Missile && w.missile_speed_ms <= 0.0 { panic!("Missile launcher {} has no missile speed", m.id); }
+        }
+    }
+    for item in items.values() {
+        if let crate::inventory::Mapping::Module(m) = &item.mapping {
+            if !modules.contains_key(m) { panic!("Item {} maps to module {} which does not exist", item.id, m); }
+        }
+    }
+    modules
+}
+
+pub fn load_ship_classes(path_to_assets: String, items: &ItemTable) -> ShipClassTable {
+    let ships_file = std::fs::read_to_string(format!("{}/ships.json", path_to_assets)).expect("Could not read ship class file");
+    let classes: HashMap<ShipClassId, LShipClass> = serde_json::from_str(ships_file.as_str()).expect("Could not parse ship class file");
+    let classes = load_ship_classes::load_ship_class(classes);
+    for item in items.values() {
+        if let crate::inventory::Mapping::Ship(c) = &item.mapping {
+            if !classes.contains_key(c) { panic!("Item {} maps to ship class {} which does not exist", item.id, c); }
+        }
+    }
+    classes
+}
+pub fn load_blueprints(path_to_assets: String, items: &ItemTable) -> BlueprintTable {
+    let blueprints_file = std::fs::read_to_string(format!("{}/blueprints.json", path_to_assets)).expect("Could not read blueprint file");
+    let blueprints: HashMap<BlueprintId, LBlueprint> = serde_json::from_str(blueprints_file.as_str()).expect("Could not parse blueprint file");
+    let blueprints = load_blueprints::load_blueprint(blueprints);
+    for bp in blueprints.values() {
+        for stack in bp.inputs.iter().chain(bp.outputs.iter()) {
+            if !items.contains_key(&stack.id) { panic!("Blueprint {} uses {} which is not an item", bp.id, stack.id); }
+        }
+    }
+    for item in items.values() {
+        if let crate::inventory::Mapping::Blueprint(b) = &item.mapping {
+            if !blueprints.contains_key(b) { panic!("Item {} maps to blueprint {} which does not exist", item.id, b); }
+        }
+    }
+    blueprints
+}
+
+pub fn load_refining(path_to_assets: String, items: &ItemTable) -> RefineTable {
+    let refining_file = std::fs::read_to_string(format!("{}/refining.json", path_to_assets)).expect("Could not read refining file");
+    let refines: HashMap<ItemId, LRefine> = serde_json::from_str(refining_file.as_str()).expect("Could not parse refining file");
+    let refines = load_refining::load_refine(refines);
+    for r in refines.values() {
+        match items.get(&r.ore) {
+            Some(i) if i.tags.contains(&ItemTag::Ore) => (),
+            _ => { panic!("Refining entry {} is not an ore", r.ore); }
+        }
+        if r.batch == 0 { panic!("Refining entry {} has a batch size of 0", r.ore); }
+        for stack in r.outputs.iter() {
+            if !items.contains_key(&stack.id) { panic!("Refining {} produces {} which is not an item", r.ore, stack.id); }
+        }
+    }
+    refines
+}
+
+pub fn load_skills(path_to_assets: String) -> SkillTable {
+    let skills_file = std::fs::read_to_string(format!("{}/skills.json", path_to_assets)).expect("Could not read skills file");
+    let skills: HashMap<SkillId, LSkill> = serde_json::from_str(skills_file.as_str()).expect("Could not parse skills file");
+    let skills = load_skills::load_skill(skills);
+    for s in skills.values() {
+        if s.rank == 0 { panic!("Skill {} has a rank of 0", s.id); }
+        for (pre, level) in s.prerequisites.iter() {
+            if !skills.contains_key(pre) { panic!("Skill {} requires {} which is not a skill", s.id, pre); }
+            if *level == 0 || *level > MAX_SKILL_LEVEL { panic!("Skill {} requires {} at level {} which does not exist", s.id, pre, level); }
+        }
+    }
+    skills
+}
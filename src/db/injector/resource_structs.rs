@@ -0,0 +1,76 @@
+use serde::{Serialize, Deserialize};
+
+use crate::inventory::ItemId;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LTierItems {
+    pub a: ItemId,
+    pub b: ItemId,
+    pub c: ItemId
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LAsteroidOres {
+    pub t1: LTierItems,
+    pub t2: LTierItems,
+    pub t3: LTierItems,
+    pub t4: LTierItems,
+    pub t5: LTierItems
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LMoonOres {
+    pub t1: LTierItems,
+    pub t2: LTierItems,
+    pub t3: LTierItems,
+    pub t4: LTierItems
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LPlanetItems {
+    pub a: ItemId,
+    pub b: ItemId,
+    pub c: ItemId,
+    pub rare: ItemId
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LResourceTable {
+    pub asteroid: LAsteroidOres,
+    pub moon: LMoonOres,
+    pub planet: LPlanetItems
+}
+
+impl LTierItems {
+    pub fn all(&self) -> [&ItemId; 3] {
+        [&self.a, &self.b, &self.c]
+    }
+}
+
+impl LAsteroidOres {
+    pub fn tiers(&self) -> [&LTierItems; 5] {
+        [&self.t1, &self.t2, &self.t3, &self.t4, &self.t5]
+    }
+}
+
+impl LMoonOres {
+    pub fn tiers(&self) -> [&LTierItems; 4] {
+        [&self.t1, &self.t2, &self.t3, &self.t4]
+    }
+}
+
+impl LPlanetItems {
+    pub fn all(&self) -> [&ItemId; 4] {
+        [&self.a, &self.b, &self.c, &self.rare]
+    }
+}
+
+impl LResourceTable {
+    /// every item that can come out of the ground
+    pub fn all_items(&self) -> Vec<&ItemId> {
+        let mut items: Vec<&ItemId> = self.asteroid.tiers().iter().flat_map(|t| t.all()).collect();
+        items.extend(self.moon.tiers().iter().flat_map(|t| t.all()));
+        items.extend(self.planet.all());
+        items
+    }
+}
@@ -7,9 +7,13 @@ pub struct LStation {
     pub name: String,
     pub system: String,
     pub planet_name: String,
-    pub orbit: [f64; 6]
+    pub orbit: [f64; 6],
+    #[serde(default = "default_refining_tax")]
+    pub refining_tax: f64 // fraction of refined materials the station keeps
 }
 
+fn default_refining_tax() -> f64 { 0.05 }
+
 #[derive(Deserialize, Debug)]
 pub struct LStationList {
     pub stations: Vec<LStation>
@@ -4,4 +4,9 @@ mod db_consts;
 mod db_structs;
 
 pub use db_structs::hanger::*;
-pub use db_structs::market::*;
\ No newline at end of file
+pub use db_structs::market::*;
+pub use db_structs::bookmark::*;
+pub use db_structs::production::*;
+pub use db_structs::skills::*;
+pub use db_structs::statistics::*;
+pub use db_structs::insurance::*;
\ No newline at end of file
@@ -1,5 +1,6 @@
 use bevy_ecs::prelude::*;
 use nalgebra::{UnitQuaternion, Vector3};
+use rand::Rng;
 
 use crate::{galaxy::components::*, shared::{ObjPath, ObjectType}};
 
@@ -32,16 +33,18 @@ pub struct BPlanet {
     pub celestial: Celestial,
     pub transform: Transform,
     pub warp_target: WarpTarget,
+    pub harvestable: Harvestable,
 }
 
 impl BPlanet {
-    pub fn new(system: &String, name: &String, planet_type: &String, radius_m: f64, mass_kg: f64, abs_pos: Vector3<f64>, abs_rot: UnitQuaternion<f64>, warp_point: Vector3<f64>) -> Self {
+    pub fn new(system: &String, name: &String, planet_type: &String, celestial: Celestial, transform: Transform, warp_point: Vector3<f64>, harvestable: Harvestable) -> Self {
         BPlanet { 
             game_object: GameObject { path: ObjPath::new(system, ObjectType::Planet, name)}, 
             planet: Planet { planet_type: planet_type.clone() }, 
-            celestial: Celestial { radius_m, mass_kg }, 
-            transform: Transform { pos: abs_pos, rot: abs_rot, vel: Vector3::zeros() },
+            celestial, 
+            transform,
             warp_target: WarpTarget::new(warp_point),
+            harvestable,
         }
     }
 }
@@ -53,16 +56,18 @@ pub struct BMoon {
     pub celestial: Celestial,
     pub transform: Transform,
     pub warp_target: WarpTarget,
+    pub harvestable: Harvestable,
 }
 
 impl BMoon {
-    pub fn new(system: &String, name: &String, moon_type: &String, radius_m: f64, mass_kg: f64, abs_pos: Vector3<f64>, abs_rot: UnitQuaternion<f64>, warp_point: Vector3<f64>) -> Self {
+    pub fn new(system: &String, name: &String, moon_type: &String, celestial: Celestial, transform: Transform, warp_point: Vector3<f64>, harvestable: Harvestable) -> Self {
         BMoon { 
             game_object: GameObject { path: ObjPath::new(system, ObjectType::Moon, name)}, 
             moon: Moon { moon_type: moon_type.clone() }, 
-            celestial: Celestial { radius_m, mass_kg }, 
-            transform: Transform { pos: abs_pos, rot: abs_rot, vel: Vector3::zeros() },
-            warp_target: WarpTarget::new(warp_point)
+            celestial, 
+            transform,
+            warp_target: WarpTarget::new(warp_point),
+            harvestable,
         }
     }
 }
@@ -76,12 +81,55 @@ pub struct BAsteroidBelt {
 }
 
 impl BAsteroidBelt {
-    pub fn new(system: &String, name: &String, abs_pos: Vector3<f64>, abs_rot: UnitQuaternion<f64>, warp_point: Vector3<f64>) -> Self {
+    pub fn new(system: &String, name: &String, abs_pos: Vector3<f64>, abs_rot: UnitQuaternion<f64>, warp_point: Vector3<f64>, belt: AsteroidBelt) -> Self {
         BAsteroidBelt { 
             game_object: GameObject { path: ObjPath::new(system, ObjectType::AsteroidBelt, name)}, 
-            belt: AsteroidBelt {},
+            belt,
             transform: Transform { pos: abs_pos, rot: abs_rot, vel: Vector3::zeros() },
             warp_target: WarpTarget::new(warp_point)
         }
     }
+}
+
+// asteroids are scattered this far around the belt's warp in point
+const ASTEROID_FIELD_RADIUS_M: f64 = 20_000.0;
+const ASTEROID_SIG_M: f64 = 40.0;
+const ASTEROID_BASE_ORE: f64 = 5000.0;
+
+#[derive(Bundle)]
+pub struct BAsteroid {
+    pub game_object: GameObject,
+    pub asteroid: Asteroid,
+    pub transform: Transform,
+    pub sig: Signature
+}
+
+impl BAsteroid {
+    /// makes a new asteroid for a belt, ore is picked using the belt's composition weights
+    pub fn new<R: Rng>(belt: &mut AsteroidBelt, belt_path: &ObjPath, center: Vector3<f64>, rng: &mut R) -> Option<Self> {
+        let total: f32 = belt.composition.iter().map(|(_, w)| *w).sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut pick = rng.gen::<f32>() * total;
+        let mut ore = &belt.composition[belt.composition.len() - 1].0;
+        for (item, w) in belt.composition.iter() {
+            if pick < *w { ore = item; break; }
+            pick -= w;
+        }
+
+        let offset = Vector3::new(rng.gen::<f64>() - 0.5, rng.gen::<f64>() - 0.5, rng.gen::<f64>() - 0.5) * 2.0 * ASTEROID_FIELD_RADIUS_M;
+        let name = format!("{}:{}", belt_path.name, belt.spawned);
+        let remaining = (ASTEROID_BASE_ORE * (0.5 + rng.gen::<f64>())) as u32;
+        let ast = BAsteroid {
+            game_object: GameObject { path: ObjPath::new(&belt_path.sys, ObjectType::Asteroid, &name) },
+            asteroid: Asteroid { belt: belt_path.clone(), ore: ore.clone(), remaining },
+            transform: Transform { pos: center + offset, rot: UnitQuaternion::identity(), vel: Vector3::zeros() },
+            sig: Signature::new(ASTEROID_SIG_M)
+        };
+        belt.spawned += 1;
+        belt.live += 1;
+        Some(ast)
+    }
 }
\ No newline at end of file
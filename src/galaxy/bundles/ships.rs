@@ -1,6 +1,18 @@
 use bevy_ecs::prelude::*;
+use nalgebra::Vector3;
+use rand::Rng;
 
-use crate::galaxy::components::*;
+use crate::{galaxy::components::*, db::database::DB, shared::{ObjPath, ObjectType}, config::CfgGameplay, inventory::WeaponStats};
+
+// used for ships whose class can not be found
+const FALLBACK_SIGNATURE_M: f64 = 10.0;
+
+fn class_signature(ship: &Ship, db: &DB) -> Signature {
+    match db.ship_class_table.get(&ship.ship_class) {
+        Some(c) => Signature::new(c.signature_m),
+        None => { eprintln!("Ship class {} not found, using fallback signature", ship.ship_class); Signature::new(FALLBACK_SIGNATURE_M) }
+    }
+}
 
 #[derive(Bundle)]
 pub struct BPlayerShip {
@@ -11,11 +23,20 @@ pub struct BPlayerShip {
     pub nav: Navigation,
     pub sig: Signature,
     pub sensor: Sensor,
-    // health
+    pub autopilot: Autopilot,
+    pub targeting: Targeting,
+    pub miner: Miner,
+    pub health: Health,
+    pub weapons: Weapons,
 }
 
 impl BPlayerShip {
-    pub fn new(player_name: &String, transform: Transform, ship: Ship, system: &String, ship_name: &String) -> BPlayerShip {
+    pub fn new(player_name: &String, transform: Transform, mut ship: Ship, system: &String, ship_name: &String, db: &DB) -> BPlayerShip {
+        // skills may have trained since the ship was last fitted
+        if let Err(e) = ship.recompute_stats(&db.ship_class_table, &db.item_table, &db.module_table, &db.skills_stat_modifiers(player_name)) {
+            eprintln!("Could not recompute stats for {}'s ship, keeping hanger stats: {}", player_name, e);
+        }
+
         let pc = PlayerController {
             player_name: player_name.clone(),
             login_state: LoginState::LoggedIn
@@ -24,11 +45,80 @@ impl BPlayerShip {
         let nav = Navigation::new();
         let go = GameObject::new(system, crate::shared::ObjectType::PlayerShip, ship_name);
 
-        BPlayerShip { ship: ship, transform: transform, pc: pc, nav: nav, game_obj: go, sig: Signature::new(10.0), sensor: Sensor::new() }
+        let sig = class_signature(&ship, db);
+        let health = Health::full(&ship.stats);
+        let weapons = Weapons::from_fitting(&ship.fitting, &db.item_table, &db.module_table);
+        BPlayerShip { ship, transform, pc, nav, game_obj: go, sig, sensor: Sensor::new(), autopilot: Autopilot::new(), targeting: Targeting::new(), miner: Miner::new(), health, weapons }
+    }
+
+    /// ships saved before health was tracked come back undamaged
+    pub fn load_from_db(mut ship: Ship, player: &String, nav: Navigation, transform: Transform, game_obj: GameObject, health: Option<Health>, db: &DB) -> Self {
+        if let Err(e) = ship.rederive(&db.ship_class_table, &db.item_table, &db.module_table, &db.skills_stat_modifiers(player)) {
+            eprintln!("Could not rederive stats for {}'s ship, keeping saved stats: {}", player, e);
+        }
+        let sig = class_signature(&ship, db);
+        let health = match health {
+            Some(mut h) => { h.clamp(&ship.stats); h },
+            None => Health::full(&ship.stats)
+        };
+        let weapons = Weapons::from_fitting(&ship.fitting, &db.item_table, &db.module_table);
+        BPlayerShip { game_obj, ship, transform, pc: PlayerController { player_name: player.clone(), login_state: LoginState::LoggedIn }, nav, sig, sensor: Sensor::new(), autopilot: Autopilot::new(), targeting: Targeting::new(), miner: Miner::new(), health, weapons }
+    }
+}
+
+const MISSILE_SIG_M: f64 = 1.0;
+
+#[derive(Bundle)]
+pub struct BMissile {
+    pub game_object: GameObject,
+    pub transform: Transform,
+    pub missile: Missile,
+    pub sig: Signature,
+}
+
+impl BMissile {
+    /// id has to be unique, a ship can launch from several slots in the same tick
+    /// damage is passed separately as the launcher's stats don't include the ship's damage multiplier
+    pub fn new(id: u64, owner: &str, shooter: &ObjPath, target: &ObjPath, shooter_transform: &Transform, damage: f64, launcher: &WeaponStats) -> Self {
+        let name = format!("{}:{}", shooter.name, id);
+        let speed_ms = launcher.missile_speed_ms;
+        let flight_left_s = if speed_ms > 0.0 { launcher.range_m / speed_ms } else { 0.0 };
+        BMissile {
+            game_object: GameObject::new(&shooter.sys, ObjectType::Missile, &name),
+            transform: shooter_transform.clone(),
+            missile: Missile { owner: owner.to_string(), shooter: shooter.clone(), target: target.clone(), damage, speed_ms, flight_left_s },
+            sig: Signature::new(MISSILE_SIG_M)
+        }
     }
+}
+
+#[derive(Bundle)]
+pub struct BWreck {
+    pub game_object: GameObject,
+    pub wreck: Wreck,
+    pub container: Container,
+    pub transform: Transform,
+    pub sig: Signature,
+}
+
+impl BWreck {
+    /// each stack in the ship's cargo survives with a random fraction of its items, the rest is destroyed
+    pub fn new<R: Rng>(owner: &str, ship: &Ship, ship_path: &ObjPath, transform: &Transform, cfg: &CfgGameplay, db: &DB, rng: &mut R) -> Self {
+        let dropped = ship.inventory.clone().take_all().into_iter()
+            .filter_map(|mut s| {
+                s.count = (s.count as f64 * rng.gen::<f64>()).round() as u32;
+                if s.is_empty() { None } else { Some(s) }
+            })
+            .collect();
 
-    pub fn load_from_db(ship: Ship, player: &String, nav: Navigation, transform: Transform, game_obj: GameObject) -> Self {
-        eprintln!("TODO: SET SIGNATURE AND STATS IN LOAD");
-        BPlayerShip { game_obj, ship, transform, pc: PlayerController { player_name: player.clone(), login_state: LoginState::LoggedIn }, nav, sig: Signature::new(10.0), sensor: Sensor::new() }
+        // the ship's own name is not enough, a pilot can lose several ships in the same place
+        let name = format!("{}:{}", ship_path.name, db.object_new_id());
+        BWreck {
+            game_object: GameObject::new(&ship_path.sys, ObjectType::Wreck, &name),
+            wreck: Wreck { owner: owner.to_string(), ship_class: ship.ship_class.clone(), despawn_in_s: cfg.wreck_lifetime_s },
+            container: Container::new_with_stacks(ship.inventory.get_cap_used(&db.item_table), dropped, &db.item_table, cfg.loot_access_range_m),
+            transform: Transform { pos: transform.pos, rot: transform.rot, vel: Vector3::zeros() },
+            sig: class_signature(ship, db)
+        }
     }
 }
\ No newline at end of file
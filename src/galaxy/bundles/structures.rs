@@ -1,9 +1,9 @@
 use std::{collections::{HashSet, hash_map::DefaultHasher}, hash::Hash, hash::Hasher};
 
 use bevy_ecs::prelude::*;
-use nalgebra::Vector3;
+use nalgebra::{Vector3, UnitQuaternion};
 
-use crate::{galaxy::components::*, shared::{ObjPath, ObjectType}};
+use crate::{galaxy::components::*, shared::{ObjPath, ObjectType}, inventory::ItemId, config::CfgGameplay};
 
 #[derive(Bundle)]
 pub struct BStation {
@@ -15,14 +15,14 @@ pub struct BStation {
 }
 
 impl BStation {
-    pub fn new(system: &String, name: &String, transform: Transform, warp_point: Vector3<f64>, undock_offset: Vector3<f64>, docking_range: f64) -> Self {
+    pub fn new(system: &String, name: &String, transform: Transform, warp_point: Vector3<f64>, undock_offset: Vector3<f64>, docking_range: f64, refining_tax: f64) -> Self {
         let mut hasher = DefaultHasher::new();
         name.hash(&mut hasher);
         let hash = format!("{:x}", hasher.finish());
         BStation { 
             game_object: GameObject { path: ObjPath::new(system, ObjectType::Station, name) }, 
             hanger: Hanger { undock_offset: undock_offset, hanger_uid: hash, docking_range_m: docking_range }, 
-            station: Station { current_players: HashSet::new() }, 
+            station: Station { current_players: HashSet::new(), refining_tax }, 
             transform: transform, 
             warp_target: WarpTarget::new(warp_point) 
         }
@@ -50,4 +50,65 @@ impl BGate {
             warp_target: WarpTarget::new(warp_point) 
         }
     }
+}
+// 5000m3 of storage, the owner has to come and empty it before production stalls
+const EXTRACTOR_CAPACITY_VUNITS: u32 = 500_000;
+const EXTRACTOR_ACCESS_DIST_M: f64 = 2500.0;
+const EXTRACTOR_SIG_M: f64 = 200.0;
+
+#[derive(Bundle)]
+pub struct BExtractor {
+    pub game_object: GameObject,
+    pub extractor: Extractor,
+    pub container: Container,
+    pub transform: Transform,
+    pub sig: Signature
+}
+
+impl BExtractor {
+    /// t is either a MoonExtractor or a PlanetOffice, there is only ever one of each per body so it takes the body's name
+    pub fn new(owner: &str, body: &ObjPath, t: ObjectType, pos: Vector3<f64>, yields: Vec<(ItemId, f32)>, cycle_s: f64) -> Self {
+        BExtractor {
+            game_object: GameObject::new(&body.sys, t, &body.name),
+            extractor: Extractor::new(owner, body, yields, cycle_s),
+            container: Container::new(EXTRACTOR_CAPACITY_VUNITS, EXTRACTOR_ACCESS_DIST_M),
+            transform: Transform { pos, rot: UnitQuaternion::identity(), vel: Vector3::zeros() },
+            sig: Signature::new(EXTRACTOR_SIG_M)
+        }
+    }
+
+    pub fn load_from_db(game_object: GameObject, extractor: Extractor, container: Container, transform: Transform) -> Self {
+        BExtractor { game_object, extractor, container, transform, sig: Signature::new(EXTRACTOR_SIG_M) }
+    }
+}
+
+
+// 2700m3, big enough for anything a normal cargo hold can carry in one stack
+const JET_CAN_CAPACITY_VUNITS: u32 = 270_000;
+const JET_CAN_SIG_M: f64 = 5.0;
+// cans are dropped behind the ship so it does not fly straight back in to them
+const JET_CAN_OFFSET_M: f64 = 200.0;
+
+#[derive(Bundle)]
+pub struct BJetCan {
+    pub game_object: GameObject,
+    pub jet_can: JetCan,
+    pub container: Container,
+    pub transform: Transform,
+    pub sig: Signature
+}
+
+impl BJetCan {
+    /// id has to be unique, a ship can jettison several times in the same tick
+    pub fn new(id: u64, owner: &str, ship: &ObjPath, ship_transform: &Transform, cfg: &CfgGameplay) -> Self {
+        let name = format!("{}:{}", ship.name, id);
+        let pos = ship_transform.pos - ship_transform.rot * Vector3::z() * JET_CAN_OFFSET_M;
+        BJetCan {
+            game_object: GameObject::new(&ship.sys, ObjectType::Container, &name),
+            jet_can: JetCan { owner: owner.to_string(), despawn_in_s: cfg.jettison_lifetime_s },
+            container: Container::new(JET_CAN_CAPACITY_VUNITS, cfg.loot_access_range_m),
+            transform: Transform { pos, rot: UnitQuaternion::identity(), vel: Vector3::zeros() },
+            sig: Signature::new(JET_CAN_SIG_M)
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,32 @@
+use bevy_ecs::prelude::*;
+
+use crate::{shared::ObjPath, inventory::ItemId};
+
+#[derive(Component, Debug)]
+pub struct Asteroid {
+    pub belt: ObjPath,
+    pub ore: ItemId,
+    pub remaining: u32, // units of ore left
+}
+
+#[derive(Component, Debug)]
+pub struct Miner {
+    pub target: Option<ObjPath>,
+    pub cycle_left_s: f64, // time until the current cycle finishes
+}
+
+impl Miner {
+    pub fn new() -> Self {
+        Miner { target: None, cycle_left_s: 0.0 }
+    }
+
+    pub fn start(&mut self, target: ObjPath, cycle_s: f64) {
+        self.target = Some(target);
+        self.cycle_left_s = cycle_s;
+    }
+
+    pub fn stop(&mut self) {
+        self.target = None;
+        self.cycle_left_s = 0.0;
+    }
+}
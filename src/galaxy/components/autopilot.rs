@@ -0,0 +1,29 @@
+use std::collections::VecDeque;
+
+use bevy_ecs::prelude::*;
+
+#[derive(Component, Debug)]
+pub struct Autopilot {
+    pub destination: Option<String>,
+    pub route: VecDeque<String>, // systems left to jump through, not including the current one
+}
+
+impl Autopilot {
+    pub fn new() -> Self {
+        Autopilot { destination: None, route: VecDeque::new() }
+    }
+
+    pub fn engage(&mut self, destination: String, route: VecDeque<String>) {
+        self.destination = Some(destination);
+        self.route = route;
+    }
+
+    pub fn disengage(&mut self) {
+        self.destination = None;
+        self.route.clear();
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.destination.is_some()
+    }
+}
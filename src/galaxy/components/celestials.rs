@@ -1,5 +1,7 @@
 use bevy_ecs::prelude::Component;
 
+use crate::inventory::ItemId;
+
 #[derive(Component)]
 pub struct Planet {
     pub planet_type: String
@@ -18,7 +20,29 @@ pub struct Sun {
 
 #[derive(Component)]
 pub struct AsteroidBelt {
+    pub composition: Vec<(ItemId, f32)>, // ore, weight
+    pub target_count: u32, // how many asteroids the belt refills to
+    pub live: u32, // asteroids currently in the belt
+    pub spawned: u64, // total ever spawned, used to name new asteroids
+    pub respawn_timer_s: f64,
+}
+
+impl AsteroidBelt {
+    pub fn new(composition: Vec<(ItemId, f32)>, target_count: u32) -> Self {
+        AsteroidBelt { composition, target_count, live: 0, spawned: 0, respawn_timer_s: 0.0 }
+    }
+}
+
+/// what an extractor or office built on this body produces
+#[derive(Component)]
+pub struct Harvestable {
+    pub yields: Vec<(ItemId, f32)>, // item, units per hour
+}
 
+impl Harvestable {
+    pub fn new(yields: Vec<(ItemId, f32)>) -> Self {
+        Harvestable { yields }
+    }
 }
 
 #[derive(Component)]
@@ -2,7 +2,7 @@ use bevy_ecs::prelude::*;
 use serde::{Serialize, Deserialize};
 use crate::inventory::{Inventory, Stack, ItemTable};
 
-#[derive(Component, Debug, Serialize, Deserialize)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Container {
     pub inv: Inventory,
     pub access_dist: f64
@@ -21,4 +21,11 @@ impl Container {
 
         Container { inv, access_dist }
     }
+}
+
+/// Container a player dumped out of their cargo hold, it goes away once it is emptied or left alone too long
+#[derive(Component, Debug)]
+pub struct JetCan {
+    pub owner: String, // player that jettisoned it
+    pub despawn_in_s: f64,
 }
\ No newline at end of file
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::{shared::ObjPath, inventory::{ItemId, Stack}};
+
+/// Moon extractor or planet office, output goes in to the Container on the same entity
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Extractor {
+    pub owner: String,
+    pub body: ObjPath, // moon or planet this was built on
+    pub yields: Vec<(ItemId, f32)>, // item, units per hour
+    pub banked: HashMap<ItemId, f64>, // partial units carried over between cycles
+    pub cycle_left_s: f64,
+}
+
+impl Extractor {
+    pub fn new(owner: &str, body: &ObjPath, yields: Vec<(ItemId, f32)>, cycle_s: f64) -> Self {
+        Extractor { owner: owner.to_string(), body: body.clone(), yields, banked: HashMap::new(), cycle_left_s: cycle_s }
+    }
+
+    /// banks a cycle's worth of output and hands back the whole units
+    pub fn produce(&mut self, cycle_s: f64) -> Vec<Stack> {
+        let mut out = vec![];
+        for (item, per_hour) in self.yields.iter() {
+            let banked = self.banked.entry(item.clone()).or_insert(0.0);
+            *banked += *per_hour as f64 * cycle_s / 3600.0;
+            let whole = banked.floor();
+            if whole >= 1.0 {
+                *banked -= whole;
+                out.push(Stack::new(item.clone(), whole as u32));
+            }
+        }
+        out
+    }
+
+    /// puts back anything that did not fit so it comes out next cycle, at most one cycle's worth (or one unit) is held, the rest is lost
+    pub fn bank(&mut self, stack: Stack, cycle_s: f64) {
+        let per_hour = self.yields.iter().find(|(item, _)| *item == stack.id).map(|(_, r)| *r as f64).unwrap_or(0.0);
+        let cap = (per_hour * cycle_s / 3600.0).max(1.0);
+        let banked = self.banked.entry(stack.id).or_insert(0.0);
+        *banked = (*banked + stack.count as f64).min(cap);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shared::ObjectType;
+
+    use super::*;
+
+    fn extractor(yields: &[(&str, f32)]) -> Extractor {
+        let body = ObjPath::new(&String::from("sys"), ObjectType::Moon, &String::from("moon"));
+        Extractor::new(&String::from("owner"), &body, yields.iter().map(|(i, r)| (String::from(*i), *r)).collect(), 600.0)
+    }
+
+    fn count(stacks: &[Stack], item: &str) -> u32 {
+        stacks.iter().filter(|s| s.id == item).map(|s| s.count).sum()
+    }
+
+    #[test]
+    fn partial_units_carry_over() {
+        // 3 per hour is half a unit every 10 minutes
+        let mut ext = extractor(&[("ice", 3.0)]);
+        assert!(ext.produce(600.0).is_empty());
+        assert_eq!(count(&ext.produce(600.0), "ice"), 1);
+        assert!(ext.produce(600.0).is_empty());
+        assert!((ext.banked["ice"] - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn whole_units_come_out_each_cycle() {
+        let mut ext = extractor(&[("ice", 60.0), ("gas", 9.0)]);
+        let out = ext.produce(600.0);
+        assert_eq!(count(&out, "ice"), 10);
+        assert_eq!(count(&out, "gas"), 1);
+        assert!((ext.banked["gas"] - 0.5).abs() < 1e-9);
+        assert_eq!(ext.banked["ice"], 0.0);
+    }
+
+    #[test]
+    fn banked_stack_comes_out_next_cycle() {
+        let mut ext = extractor(&[("ice", 60.0)]);
+        let out = ext.produce(600.0);
+        for stack in out {
+            ext.bank(stack, 600.0);
+        }
+        assert_eq!(count(&ext.produce(600.0), "ice"), 20);
+    }
+
+    #[test]
+    fn full_container_banks_at_most_one_cycle() {
+        let mut ext = extractor(&[("ice", 60.0), ("gas", 3.0)]);
+        // a day of cycles with nowhere to put the output
+        for _ in 0..144 {
+            for stack in ext.produce(600.0) {
+                ext.bank(stack, 600.0);
+            }
+        }
+        assert!(ext.banked["ice"] <= 10.0);
+        assert!(ext.banked["gas"] <= 1.0);
+        assert_eq!(count(&ext.produce(0.0), "ice"), 10);
+    }
+
+    #[test]
+    fn zero_length_cycle_only_releases_whole_banked_units() {
+        let mut ext = extractor(&[("ice", 33.0)]);
+        ext.produce(600.0);
+        ext.bank(Stack::new(String::from("ice"), 4), 600.0);
+        assert_eq!(count(&ext.produce(0.0), "ice"), 4);
+        assert!(ext.produce(0.0).is_empty());
+    }
+}
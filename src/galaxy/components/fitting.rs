@@ -0,0 +1,62 @@
+use serde::{Serialize, Deserialize};
+
+use crate::inventory::{ItemId, SlotType};
+
+/// Modules fitted to a ship, slots hold the item id of the module item so it can be put back in to an inventory
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Fitting {
+    pub high: Vec<Option<ItemId>>,
+    pub mid: Vec<Option<ItemId>>,
+    pub low: Vec<Option<ItemId>>,
+}
+
+impl Fitting {
+    pub fn new(high: usize, mid: usize, low: usize) -> Self {
+        Fitting { high: vec![None; high], mid: vec![None; mid], low: vec![None; low] }
+    }
+
+    /// resizes the slots to match a hull, slots that are removed must be empty, fitted modules are never dropped
+    pub fn ensure_layout(&mut self, high: usize, mid: usize, low: usize) {
+        for (slots, n) in [(&mut self.high, high), (&mut self.mid, mid), (&mut self.low, low)] {
+            while slots.len() > n && slots.last() == Some(&None) {
+                slots.pop();
+            }
+            if slots.len() < n {
+                slots.resize(n, None);
+            }
+        }
+    }
+
+    fn slots_mut(&mut self, slot_type: SlotType) -> &mut Vec<Option<ItemId>> {
+        match slot_type {
+            SlotType::High => &mut self.high,
+            SlotType::Mid => &mut self.mid,
+            SlotType::Low => &mut self.low,
+        }
+    }
+
+    /// puts the module in the slot, returning whatever was fitted there before
+    pub fn fit(&mut self, slot_type: SlotType, index: usize, item: ItemId) -> Result<Option<ItemId>, String> {
+        match self.slots_mut(slot_type).get_mut(index) {
+            Some(slot) => Ok(slot.replace(item)),
+            None => Err(format!("Ship has no {:?} slot {}", slot_type, index))
+        }
+    }
+
+    pub fn unfit(&mut self, slot_type: SlotType, index: usize) -> Result<ItemId, String> {
+        match self.slots_mut(slot_type).get_mut(index) {
+            Some(slot) => slot.take().ok_or(format!("Nothing fitted in {:?} slot {}", slot_type, index)),
+            None => Err(format!("Ship has no {:?} slot {}", slot_type, index))
+        }
+    }
+
+    pub fn fitted(&self) -> impl Iterator<Item = &ItemId> {
+        self.high.iter().chain(self.mid.iter()).chain(self.low.iter()).filter_map(|s| s.as_ref())
+    }
+}
+
+impl Default for Fitting {
+    fn default() -> Self {
+        Fitting::new(3, 3, 3)
+    }
+}
@@ -0,0 +1,93 @@
+use bevy_ecs::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use super::Stats;
+
+/// Damage is taken by the shield first, then armor, then hull. The ship is destroyed when the hull runs out
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Health {
+    pub shield: f64,
+    pub armor: f64,
+    pub hull: f64,
+}
+
+impl Health {
+    pub fn full(stats: &Stats) -> Self {
+        Health { shield: stats.shield_hp, armor: stats.armor_hp, hull: stats.hull_hp }
+    }
+
+    /// keeps the current damage but makes sure no layer is above what the ship can hold
+    pub fn clamp(&mut self, stats: &Stats) {
+        self.shield = self.shield.min(stats.shield_hp);
+        self.armor = self.armor.min(stats.armor_hp);
+        self.hull = self.hull.min(stats.hull_hp);
+    }
+
+    pub fn apply_damage(&mut self, mut damage: f64) {
+        for layer in [&mut self.shield, &mut self.armor, &mut self.hull] {
+            let taken = damage.min(*layer);
+            *layer -= taken;
+            damage -= taken;
+            if damage <= 0.0 {
+                return;
+            }
+        }
+    }
+
+    /// shields recharge linearly, armor and hull have to be repaired at a station
+    pub fn regen(&mut self, stats: &Stats, dt: f64) {
+        if stats.shield_recharge_s <= 0.0 {
+            return;
+        }
+        self.shield = (self.shield + stats.shield_hp * dt / stats.shield_recharge_s).min(stats.shield_hp);
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.hull <= 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats() -> Stats {
+        Stats { warp_speed_ms: 1.0, thrust_n: 1.0, ang_vel_rads: 1.0, mass_kg: 1.0, warp_spool_s: 1.0, sensor_range_m: 1.0, lock_range_m: 1.0, scan_resolution_mm: 300.0, max_locks: 3, mining_yield: 0.0, mining_cycle_s: 10.0, mining_range_m: 10_000.0, shield_hp: 100.0, armor_hp: 200.0, hull_hp: 300.0, shield_recharge_s: 100.0, damage_mult: 1.0 }
+    }
+
+    #[test]
+    fn damage_carries_from_shield_to_armor_to_hull() {
+        let mut h = Health::full(&stats());
+        h.apply_damage(50.0);
+        assert_eq!((h.shield, h.armor, h.hull), (50.0, 200.0, 300.0));
+        h.apply_damage(100.0);
+        assert_eq!((h.shield, h.armor, h.hull), (0.0, 150.0, 300.0));
+        h.apply_damage(250.0);
+        assert_eq!((h.shield, h.armor, h.hull), (0.0, 0.0, 200.0));
+        assert!(!h.is_destroyed());
+    }
+
+    #[test]
+    fn hull_reaching_exactly_zero_is_destroyed() {
+        let mut h = Health::full(&stats());
+        h.apply_damage(599.0);
+        assert!(!h.is_destroyed());
+        h.apply_damage(1.0);
+        assert_eq!(h.hull, 0.0);
+        assert!(h.is_destroyed());
+        // extra damage goes nowhere, no layer goes negative
+        h.apply_damage(1000.0);
+        assert_eq!((h.shield, h.armor, h.hull), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn regen_only_recharges_shields_up_to_full() {
+        let mut h = Health::full(&stats());
+        h.apply_damage(150.0);
+        h.regen(&stats(), 10.0);
+        assert_eq!((h.shield, h.armor), (10.0, 150.0));
+        h.regen(&stats(), 1000.0);
+        assert_eq!(h.shield, 100.0);
+        assert_eq!(h.armor, 150.0);
+    }
+}
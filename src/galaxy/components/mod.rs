@@ -1,5 +1,7 @@
 mod celestials;
 pub use celestials::*;
+mod asteroid;
+pub use asteroid::*;
 mod game_object;
 pub use game_object::*;
 mod gate;
@@ -12,6 +14,8 @@ mod player_controller;
 pub use player_controller::*;
 mod ship;
 pub use ship::*;
+mod fitting;
+pub use fitting::*;
 mod station;
 pub use station::*;
 mod transform;
@@ -19,10 +23,23 @@ pub use transform::*;
 mod warp_target;
 pub use warp_target::*;
 
+mod autopilot;
+pub use autopilot::*;
+
 mod signature;
 pub use signature::*;
 mod sensor;
 pub use sensor::*;
+mod targeting;
+pub use targeting::*;
 
 pub mod container;
-pub use container::*;
\ No newline at end of file
+pub use container::*;
+mod extractor;
+pub use extractor::*;
+mod health;
+pub use health::*;
+mod weapons;
+pub use weapons::*;
+mod wreck;
+pub use wreck::*;
@@ -1,16 +1,83 @@
 
 
+use std::collections::HashMap;
+
 use bevy_ecs::prelude::*;
 use serde::{Serialize, Deserialize};
 
-use crate::inventory::Inventory;
+use crate::inventory::{Inventory, ItemTable, ModuleTable, Mapping, ShipStat, Modifier, StatModifier, ShipClassTable, ShipClass, ShipClassId};
+
+use super::Fitting;
+
+pub type ShipId = u64;
+
+/// ships saved before ids existed load with 0 and are given a real id when the database opens
+pub const UNASSIGNED_SHIP_ID: ShipId = 0;
 
 #[derive(Component, Serialize, Deserialize, Debug, Clone)]
 pub struct Ship {
     pub ship_name: String,
-    pub ship_class: String, // TODO: MAKE SHIP CLASS ITS OWN TYPE
+    pub ship_class: ShipClassId,
     pub stats: Stats,
     pub inventory: Inventory,
+    #[serde(default)]
+    pub fitting: Fitting,
+    #[serde(default)]
+    pub ship_id: ShipId, // stays the same through docking, undocking and jumps, hanger slots do not
+}
+
+impl Ship {
+    pub fn new(ship_id: ShipId, ship_name: String, class: &ShipClass) -> Self {
+        Ship {
+            ship_name,
+            ship_class: class.id.clone(),
+            stats: class.stats.clone(),
+            inventory: Inventory::new(None, Some(class.cargo_vunits)),
+            fitting: Fitting::new(class.high_slots, class.mid_slots, class.low_slots),
+            ship_id
+        }
+    }
+
+    /// ships saved before classes were loaded from assets carry a class that is not in the table, moves them on to the class that replaced it
+    pub fn migrate_legacy_class(&mut self, legacy: &HashMap<String, ShipClassId>, classes: &ShipClassTable) -> bool {
+        if classes.contains_key(&self.ship_class) {
+            return false;
+        }
+        match legacy.get(&self.ship_class).filter(|c| classes.contains_key(*c)) {
+            Some(c) => { self.ship_class = c.clone(); true },
+            None => false
+        }
+    }
+
+    /// brings a stored ship in line with its class (stats, cargo and slot layout), the class data may have changed since it was saved
+    pub fn rederive(&mut self, classes: &ShipClassTable, items: &ItemTable, modules: &ModuleTable, skill_mods: &[StatModifier]) -> Result<(), String> {
+        let class = classes.get(&self.ship_class).ok_or(format!("Unknown ship class {}", self.ship_class))?;
+        self.inventory.set_capacity(Some(class.cargo_vunits));
+        self.fitting.ensure_layout(class.high_slots, class.mid_slots, class.low_slots);
+        self.recompute_stats(classes, items, modules, skill_mods)
+    }
+
+    /// rebuilds the effective stats from the hull, whatever is fitted and the pilot's skills, additive modifiers apply before multiplicative ones
+    pub fn recompute_stats(&mut self, classes: &ShipClassTable, items: &ItemTable, modules: &ModuleTable, skill_mods: &[StatModifier]) -> Result<(), String> {
+        let mut stats = classes.get(&self.ship_class).ok_or(format!("Unknown ship class {}", self.ship_class))?.stats.clone();
+        let mods: Vec<_> = self.fitting.fitted()
+            .filter_map(|item| match items.get(item).map(|i| &i.mapping) {
+                Some(Mapping::Module(m)) => modules.get(m),
+                _ => { eprintln!("Fitted item {} is not a module", item); None }
+            })
+            .flat_map(|m| m.modifiers.iter())
+            .chain(skill_mods.iter())
+            .collect();
+
+        for m in mods.iter().filter(|m| matches!(m.modifier, Modifier::Add(_))) {
+            stats.apply(m.stat, m.modifier);
+        }
+        for m in mods.iter().filter(|m| matches!(m.modifier, Modifier::Mul(_))) {
+            stats.apply(m.stat, m.modifier);
+        }
+        self.stats = stats;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -19,5 +86,118 @@ pub struct Stats {
     pub thrust_n: f64,
     pub ang_vel_rads: f64,
     pub mass_kg: f64,
-    pub warp_spool_s: f32
-}
\ No newline at end of file
+    pub warp_spool_s: f32,
+    #[serde(default = "default_sensor_range")]
+    pub sensor_range_m: f64, // range at which a reference sized signature can be seen
+    #[serde(default = "default_lock_range")]
+    pub lock_range_m: f64, // max range at which a visible object can be locked
+    #[serde(default = "default_scan_resolution")]
+    pub scan_resolution_mm: f64, // higher is faster locking
+    #[serde(default = "default_max_locks")]
+    pub max_locks: u32,
+    #[serde(default)]
+    pub mining_yield: f64, // ore units per mining cycle
+    #[serde(default = "default_mining_cycle")]
+    pub mining_cycle_s: f64,
+    #[serde(default = "default_mining_range")]
+    pub mining_range_m: f64,
+    #[serde(default = "default_shield_hp")]
+    pub shield_hp: f64,
+    #[serde(default = "default_armor_hp")]
+    pub armor_hp: f64,
+    #[serde(default = "default_hull_hp")]
+    pub hull_hp: f64,
+    #[serde(default = "default_shield_recharge")]
+    pub shield_recharge_s: f64, // time for the shield to go from empty to full
+    #[serde(default = "default_damage_mult")]
+    pub damage_mult: f64, // applied to every weapon fitted
+}
+
+// ships saved before sensor stats existed get these
+fn default_sensor_range() -> f64 { 200_000.0 }
+fn default_lock_range() -> f64 { 100_000.0 }
+fn default_scan_resolution() -> f64 { 300.0 }
+fn default_max_locks() -> u32 { 3 }
+fn default_mining_cycle() -> f64 { 10.0 }
+fn default_mining_range() -> f64 { 10_000.0 }
+fn default_shield_hp() -> f64 { 300.0 }
+fn default_armor_hp() -> f64 { 300.0 }
+fn default_hull_hp() -> f64 { 300.0 }
+fn default_shield_recharge() -> f64 { 120.0 }
+fn default_damage_mult() -> f64 { 1.0 }
+
+impl Stats {
+    pub fn apply(&mut self, stat: ShipStat, modifier: Modifier) {
+        let apply = |v: f64| match modifier {
+            Modifier::Add(a) => v + a,
+            Modifier::Mul(m) => v * m,
+        };
+        match stat {
+            ShipStat::WarpSpeed => self.warp_speed_ms = apply(self.warp_speed_ms),
+            ShipStat::Thrust => self.thrust_n = apply(self.thrust_n),
+            ShipStat::AngularVelocity => self.ang_vel_rads = apply(self.ang_vel_rads),
+            ShipStat::Mass => self.mass_kg = apply(self.mass_kg).max(0.0),
+            ShipStat::WarpSpool => self.warp_spool_s = apply(self.warp_spool_s as f64).max(0.0) as f32,
+            ShipStat::SensorRange => self.sensor_range_m = apply(self.sensor_range_m).max(0.0),
+            ShipStat::LockRange => self.lock_range_m = apply(self.lock_range_m).max(0.0),
+            ShipStat::ScanResolution => self.scan_resolution_mm = apply(self.scan_resolution_mm).max(0.0),
+            ShipStat::MaxLocks => self.max_locks = apply(self.max_locks as f64).max(0.0).round() as u32,
+            ShipStat::MiningYield => self.mining_yield = apply(self.mining_yield).max(0.0),
+            ShipStat::MiningCycle => self.mining_cycle_s = apply(self.mining_cycle_s).max(1.0),
+            ShipStat::MiningRange => self.mining_range_m = apply(self.mining_range_m).max(0.0),
+            ShipStat::ShieldHp => self.shield_hp = apply(self.shield_hp).max(0.0),
+            ShipStat::ArmorHp => self.armor_hp = apply(self.armor_hp).max(0.0),
+            ShipStat::HullHp => self.hull_hp = apply(self.hull_hp).max(1.0),
+            ShipStat::ShieldRecharge => self.shield_recharge_s = apply(self.shield_recharge_s).max(1.0),
+            ShipStat::Damage => self.damage_mult = apply(self.damage_mult).max(0.0),
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rmp_serde::{to_vec, from_slice};
+    use crate::inventory::{Item, ItemTag, Module, SlotType};
+    use super::*;
+
+    // the ship and stats as the baseline saved them, before fitting, classes and ids existed
+    #[derive(Serialize)]
+    struct BaselineStats { warp_speed_ms: f64, thrust_n: f64, ang_vel_rads: f64, mass_kg: f64, warp_spool_s: f32 }
+
+    #[derive(Serialize)]
+    struct BaselineShip { ship_name: String, ship_class: String, stats: BaselineStats, inventory: Inventory }
+
+    fn frigate() -> ShipClass {
+        let stats = Stats { warp_speed_ms: 1.496e11, thrust_n: 100.0, ang_vel_rads: 1.0, mass_kg: 10.0, warp_spool_s: 5.0, sensor_range_m: 200_000.0, lock_range_m: 100_000.0, scan_resolution_mm: 300.0, max_locks: 3, mining_yield: 10.0, mining_cycle_s: 10.0, mining_range_m: 10_000.0, shield_hp: 400.0, armor_hp: 350.0, hull_hp: 300.0, shield_recharge_s: 120.0, damage_mult: 1.0 };
+        ShipClass { id: String::from("rookie_frigate"), name: String::from("Rookie Frigate"), stats, cargo_vunits: 10000, signature_m: 10.0, high_slots: 2, mid_slots: 2, low_slots: 2, insured_value: 0 }
+    }
+
+    #[test]
+    fn baseline_ship_migrates_and_can_be_fitted() {
+        let baseline = BaselineShip {
+            ship_name: String::from("New ship"),
+            ship_class: String::from("Test Ship"),
+            stats: BaselineStats { warp_speed_ms: 1.496e11, thrust_n: 100.0, ang_vel_rads: 1.0, mass_kg: 10.0, warp_spool_s: 5.0 },
+            inventory: Inventory::new(None, Some(10000))
+        };
+        let mut ship: Ship = from_slice(&to_vec(&baseline).unwrap()).expect("Baseline ship did not load");
+        assert_eq!(ship.ship_id, UNASSIGNED_SHIP_ID);
+
+        let classes = ShipClassTable::from([(String::from("rookie_frigate"), frigate())]);
+        let items = ItemTable::from([(String::from("thruster_item"), Item { id: String::from("thruster_item"), tags: HashSet::from([ItemTag::Module]), mapping: Mapping::Module(String::from("thruster")), size_vunits: 10, tech_level: 1 })]);
+        let modules = ModuleTable::from([(String::from("thruster"), Module { id: String::from("thruster"), slot: SlotType::Low, modifiers: vec![StatModifier { stat: ShipStat::Thrust, modifier: Modifier::Add(50.0) }], weapon: None })]);
+
+        assert!(ship.recompute_stats(&classes, &items, &modules, &[]).is_err());
+        let legacy = HashMap::from([(String::from("Test Ship"), String::from("rookie_frigate"))]);
+        assert!(ship.migrate_legacy_class(&legacy, &classes));
+        assert!(!ship.migrate_legacy_class(&legacy, &classes));
+        ship.rederive(&classes, &items, &modules, &[]).expect("Migrated ship did not rederive");
+        assert_eq!(ship.fitting.low.len(), 2);
+
+        ship.fitting.fit(SlotType::Low, 0, String::from("thruster_item")).expect("Could not fit module");
+        ship.recompute_stats(&classes, &items, &modules, &[]).expect("Could not recompute stats");
+        assert_eq!(ship.stats.thrust_n, 150.0);
+        assert_eq!(ship.stats.shield_hp, 400.0);
+    }
+}
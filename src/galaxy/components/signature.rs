@@ -2,7 +2,7 @@ use bevy_ecs::prelude::*;
 
 #[derive(Component, Debug)]
 pub struct Signature {
-    size_m: f64
+    pub size_m: f64
 }
 
 impl Signature {
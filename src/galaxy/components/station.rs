@@ -4,5 +4,6 @@ use bevy_ecs::prelude::*;
 
 #[derive(Component)]
 pub struct Station {
-    pub current_players: HashSet<String>
+    pub current_players: HashSet<String>,
+    pub refining_tax: f64, // fraction of refined materials the station keeps
 }
\ No newline at end of file
@@ -0,0 +1,31 @@
+use std::collections::{HashSet, HashMap};
+
+use bevy_ecs::prelude::*;
+
+use crate::shared::ObjPath;
+
+#[derive(Component, Debug)]
+pub struct Targeting {
+    pub locked: HashSet<ObjPath>,
+    pub pending: HashMap<ObjPath, f64>, // target, seconds left until the lock completes
+}
+
+impl Targeting {
+    pub fn new() -> Self {
+        Targeting { locked: HashSet::new(), pending: HashMap::new() }
+    }
+
+    /// locked and pending targets both take up a lock slot
+    pub fn lock_count(&self) -> usize {
+        self.locked.len() + self.pending.len()
+    }
+
+    pub fn is_targeting(&self, target: &ObjPath) -> bool {
+        self.locked.contains(target) || self.pending.contains_key(target)
+    }
+
+    /// returns true if the target was locked or being locked
+    pub fn unlock(&mut self, target: &ObjPath) -> bool {
+        self.locked.remove(target) | self.pending.remove(target).is_some()
+    }
+}
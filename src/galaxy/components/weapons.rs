@@ -0,0 +1,48 @@
+use bevy_ecs::prelude::*;
+
+use crate::{shared::ObjPath, inventory::{ItemTable, ModuleTable, Mapping, WeaponStats}};
+
+use super::Fitting;
+
+#[derive(Debug)]
+pub struct WeaponState {
+    pub slot: usize, // high slot the weapon is fitted in
+    pub stats: WeaponStats,
+    pub target: Option<ObjPath>,
+    pub cycle_left_s: f64, // time until the next shot
+}
+
+/// Weapons fitted to a ship in space, fittings can not change outside of a hanger so this is built once on spawn
+#[derive(Component, Debug)]
+pub struct Weapons {
+    pub slots: Vec<WeaponState>,
+}
+
+impl Weapons {
+    pub fn from_fitting(fitting: &Fitting, items: &ItemTable, modules: &ModuleTable) -> Self {
+        let slots = fitting.high.iter().enumerate()
+            .filter_map(|(i, s)| s.as_ref().map(|item| (i, item)))
+            .filter_map(|(i, item)| match items.get(item).map(|it| &it.mapping) {
+                Some(Mapping::Module(m)) => modules.get(m).and_then(|m| m.weapon.clone()).map(|w| (i, w)),
+                _ => None
+            })
+            .map(|(slot, stats)| WeaponState { slot, stats, target: None, cycle_left_s: 0.0 })
+            .collect();
+        Weapons { slots }
+    }
+
+    pub fn get_mut(&mut self, slot: usize) -> Option<&mut WeaponState> {
+        self.slots.iter_mut().find(|w| w.slot == slot)
+    }
+}
+
+/// In flight missile, it homes in on the target and explodes when it gets close enough
+#[derive(Component, Debug)]
+pub struct Missile {
+    pub owner: String, // player that launched it
+    pub shooter: ObjPath,
+    pub target: ObjPath,
+    pub damage: f64,
+    pub speed_ms: f64,
+    pub flight_left_s: f64, // missile burns out when this runs out
+}
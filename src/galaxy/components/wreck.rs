@@ -0,0 +1,9 @@
+use bevy_ecs::prelude::*;
+
+/// What is left of a destroyed ship, the loot is held in a Container on the same entity
+#[derive(Component, Debug)]
+pub struct Wreck {
+    pub owner: String, // player whose ship this was
+    pub ship_class: String,
+    pub despawn_in_s: f64,
+}
@@ -8,4 +8,10 @@ pub enum EEvent {
     Undock(String, ObjPath), //player, ship
     Dock(String, ObjPath), //player, station
     Jump(String, ObjPath), //player, new_ship_path
+    AutopilotRoute(String, Vec<String>), //player, systems left to jump through
+    AutopilotArrived(String, String), //player, destination system
+    AutopilotDisengaged(String), //player
+    WeaponFired(String, ObjPath, ObjPath, f64), //player, shooter, target, damage (0 for a missile launch)
+    Destroyed(String, ObjPath), //player, destroyed object
+    Death(String, ObjPath), //player, home station they woke up in
 }
\ No newline at end of file
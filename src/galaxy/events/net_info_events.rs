@@ -10,5 +10,13 @@ pub enum EInfo {
     UpdateInventoryShip(String, ObjPath), //player, ship path
     UpdateBankAccount(String), //player
     ItemStore(String, ItemId), //player, item id
+    MarketHistory(String, ItemId), //player, item id
     UpdateInventoryList(String, Vec<(ObjPath, InvId)>), //player, Vec<(station path, station inventory)>
+    UpdateBookmarks(String), //player
+    Route(String, String, String, Option<Vec<String>>), //player, start system, end system, systems to jump through (None if unreachable)
+    ProductionJobs(String), //player
+    Skills(String), //player
+    HomeStation(String), //player
+    Insurance(String), //player
+    OrderExpired(String, ItemId, u64), //player, item, order id
 }
\ No newline at end of file
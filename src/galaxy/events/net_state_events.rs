@@ -1,12 +1,19 @@
 use bevy_ecs::prelude::*;
 
-use crate::{galaxy::components::ObjectVisibility, shared::ObjPath};
+use crate::{galaxy::components::{ObjectVisibility, Health}, shared::ObjPath};
 
 /// Client state event (about things in space)
 pub enum EState {
     // Statics(String, String), // player, system
     OtherShip(String, ObjPath, ObjectVisibility), //player, ship, visibility
     LostSight(String, ObjPath), //player, object path
+    TargetLocking(String, ObjPath, f64), //player, target, seconds until locked
+    TargetLocked(String, ObjPath), //player, target
+    TargetUnlocked(String, ObjPath), //player, target (unlocked by the player or the lock broke)
+    Mining(String, Option<ObjPath>), //player, asteroid being mined (None when mining stops)
+    AsteroidRemaining(String, ObjPath, u32), //player, asteroid, ore left
+    Weapon(String, u32, Option<ObjPath>), //player, high slot index, target (None when the weapon stops)
+    Health(String, ObjPath, Health), //player, damaged or repairing object, its current health
     // OwnShip(String, ObjPath), //player, own ship path
 }
 
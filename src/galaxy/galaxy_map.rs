@@ -13,6 +13,7 @@ pub struct GMSystem {
     pub name: String,
     pub region: String,
     pub sun_temp: u32,
+    pub security_level: i32,
     pub pos: Vector3<f64>
 }
 
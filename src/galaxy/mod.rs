@@ -1,7 +1,7 @@
 use bevy_ecs::{world::World, schedule::{Schedule, Stage}};
 use dashmap::DashMap;
 
-use crate::{db::database::DB, network::messages::{incoming::NetIncomingMessage, outgoing::NetOutgoingMessage}, inventory::ItemTable};
+use crate::{db::database::DB, network::messages::{incoming::NetIncomingMessage, outgoing::NetOutgoingMessage}, inventory::ItemTable, config::CfgGameplay};
 
 use self::{runner::{schedule::generate_schedule, init_resources::init_resources}, resources::{network_handler::NetworkHandler, delta_time::DeltaTime}};
 
@@ -13,6 +13,7 @@ mod runner;
 mod systems;
 
 pub mod galaxy_map;
+pub mod pathfinding;
 
 pub struct Galaxy {
     pub world: World,
@@ -20,8 +21,12 @@ pub struct Galaxy {
 }
 
 impl Galaxy {
-    pub fn new(mut world: World, db: DB, _item_table: ItemTable) -> Self {
-        init_resources(&mut world, db);
+    pub fn new(mut world: World, db: DB, _item_table: ItemTable, gameplay: CfgGameplay) -> Self {
+        world.spawn_batch(db.resources_load_extractors());
+        if db.bank_get_value(&gameplay.market_sink_account).is_none() {
+            db.bank_new_account(&gameplay.market_sink_account);
+        }
+        init_resources(&mut world, db, gameplay);
 
         Galaxy {
             world,
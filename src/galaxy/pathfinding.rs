@@ -0,0 +1,234 @@
+use std::collections::{HashMap, HashSet, BinaryHeap, VecDeque};
+use std::cmp::Reverse;
+
+use serde::{Serialize, Deserialize};
+
+use super::{galaxy_map::GalaxyMap, bundles::structures::BGate};
+
+// cost of a single jump, security penalties are added on top of this
+const JUMP_COST: u64 = 1000;
+// how many extra jumps we are willing to take to avoid the least (or most) secure system in the galaxy
+const SECURITY_WEIGHT: u64 = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RoutePreference {
+    Shortest,
+    PreferHighSec,
+    PreferLowSec,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RouteOptions {
+    pub preference: RoutePreference,
+    pub avoid_systems: HashSet<String>,
+    pub avoid_regions: HashSet<String>,
+}
+
+impl RouteOptions {
+    pub fn new(preference: RoutePreference) -> Self {
+        RouteOptions { preference, avoid_systems: HashSet::new(), avoid_regions: HashSet::new() }
+    }
+}
+
+/// Adjacency list of the gate network, built once from the gates the injector spawns
+#[derive(Debug)]
+pub struct RouteGraph {
+    neighbours: HashMap<String, Vec<String>>,
+    security: HashMap<String, i32>,
+    region: HashMap<String, String>,
+    min_security: i32,
+    max_security: i32,
+}
+
+impl RouteGraph {
+    pub fn new(gmap: &GalaxyMap, gates: &[BGate]) -> Self {
+        let mut neighbours: HashMap<String, Vec<String>> = HashMap::new();
+        let mut security = HashMap::new();
+        let mut region = HashMap::new();
+        for s in gmap.systems.iter() {
+            neighbours.entry(s.name.clone()).or_insert(vec![]);
+            security.insert(s.name.clone(), s.security_level);
+            region.insert(s.name.clone(), s.region.clone());
+        }
+
+        // use the gates themselves rather than the map links, so routes always match what can actually be jumped
+        for g in gates.iter() {
+            let n = neighbours.entry(g.game_object.path.sys.clone()).or_insert(vec![]);
+            if !n.contains(&g.gate.dst_system) { n.push(g.gate.dst_system.clone()); }
+        }
+
+        let min_security = security.values().copied().min().unwrap_or(0);
+        let max_security = security.values().copied().max().unwrap_or(0);
+
+        RouteGraph { neighbours, security, region, min_security, max_security }
+    }
+
+    pub fn contains(&self, sys: &String) -> bool {
+        self.neighbours.contains_key(sys)
+    }
+
+    pub fn get_region(&self, sys: &String) -> Option<&String> {
+        self.region.get(sys)
+    }
+
+    fn is_avoided(&self, sys: &String, opts: &RouteOptions) -> bool {
+        opts.avoid_systems.contains(sys) || self.region.get(sys).map(|r| opts.avoid_regions.contains(r)).unwrap_or(false)
+    }
+
+    /// cost of jumping in to a system
+    fn jump_cost(&self, sys: &String, pref: RoutePreference) -> u64 {
+        let range = (self.max_security - self.min_security) as u64;
+        if range == 0 { return JUMP_COST; }
+        let sec = *self.security.get(sys).unwrap_or(&self.min_security);
+        let penalty = match pref {
+            RoutePreference::Shortest => 0,
+            RoutePreference::PreferHighSec => (self.max_security - sec) as u64,
+            RoutePreference::PreferLowSec => (sec - self.min_security) as u64,
+        };
+        JUMP_COST + (JUMP_COST * SECURITY_WEIGHT * penalty) / range
+    }
+
+    /// returns the list of systems to jump through to get from start to end, not including start
+    /// the start and end systems are never avoided, even if they match the avoid lists
+    pub fn find_route(&self, start: &String, end: &String, opts: &RouteOptions) -> Option<VecDeque<String>> {
+        if !self.contains(start) || !self.contains(end) {
+            return None;
+        }
+
+        let mut dist: HashMap<&String, u64> = HashMap::new();
+        let mut prev: HashMap<&String, &String> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        dist.insert(start, 0);
+        heap.push(Reverse((0, start)));
+
+        while let Some(Reverse((cost, sys))) = heap.pop() {
+            if sys == end {
+                break;
+            }
+            if cost > *dist.get(sys).unwrap_or(&u64::MAX) {
+                continue;
+            }
+
+            for n in self.neighbours.get(sys).into_iter().flatten() {
+                if n != end && self.is_avoided(n, opts) {
+                    continue;
+                }
+
+                let next_cost = cost + self.jump_cost(n, opts.preference);
+                if next_cost < *dist.get(n).unwrap_or(&u64::MAX) {
+                    dist.insert(n, next_cost);
+                    prev.insert(n, sys);
+                    heap.push(Reverse((next_cost, n)));
+                }
+            }
+        }
+
+        if !dist.contains_key(end) {
+            return None;
+        }
+
+        let mut route = VecDeque::new();
+        let mut cur = end;
+        while cur != start {
+            route.push_front(cur.clone());
+            cur = *prev.get(cur).expect("Route broken while walking back from destination");
+        }
+        Some(route)
+    }
+
+    /// number of jumps between two systems along the shortest route
+    pub fn jump_count(&self, start: &String, end: &String) -> Option<usize> {
+        self.find_route(start, end, &RouteOptions::new(RoutePreference::Shortest)).map(|r| r.len())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use nalgebra::{Vector3, UnitQuaternion};
+
+    use crate::galaxy::{galaxy_map::{GMSystem, GMLink}, components::Transform};
+    use super::*;
+
+    /// systems are (name, region, security), every link gets a gate in both directions
+    pub fn graph(systems: &[(&str, &str, i32)], links: &[(&str, &str)]) -> RouteGraph {
+        let gmap = GalaxyMap {
+            systems: systems.iter().map(|(name, region, security_level)| GMSystem { name: name.to_string(), region: region.to_string(), sun_temp: 5000, security_level: *security_level, pos: Vector3::zeros() }).collect(),
+            links: links.iter().map(|(a, b)| GMLink { start: a.to_string(), end: b.to_string() }).collect(),
+        };
+        let t = || Transform { pos: Vector3::zeros(), rot: UnitQuaternion::identity(), vel: Vector3::zeros() };
+        let gates: Vec<BGate> = links.iter()
+            .flat_map(|(a, b)| [(a.to_string(), b.to_string()), (b.to_string(), a.to_string())])
+            .map(|(from, to)| BGate::new(&from, &to, &to, &from, 1000.0, t(), Vector3::zeros()))
+            .collect();
+        RouteGraph::new(&gmap, &gates)
+    }
+
+    // A to D is two jumps through low sec B, or three through high sec C and E. X has no gates
+    fn test_graph() -> RouteGraph {
+        graph(
+            &[("A", "R1", 5), ("B", "R1", -5), ("C", "R1", 5), ("D", "R1", 5), ("E", "R2", 5), ("X", "R3", 5)],
+            &[("A", "B"), ("B", "D"), ("A", "C"), ("C", "E"), ("E", "D")]
+        )
+    }
+
+    fn s(name: &str) -> String { name.to_string() }
+
+    fn route(g: &RouteGraph, start: &str, end: &str, opts: &RouteOptions) -> Option<Vec<String>> {
+        g.find_route(&s(start), &s(end), opts).map(|r| r.into_iter().collect())
+    }
+
+    #[test]
+    fn shortest_route_takes_fewest_jumps() {
+        let g = test_graph();
+        assert_eq!(route(&g, "A", "D", &RouteOptions::new(RoutePreference::Shortest)), Some(vec![s("B"), s("D")]));
+        assert_eq!(route(&g, "D", "A", &RouteOptions::new(RoutePreference::Shortest)), Some(vec![s("B"), s("A")]));
+    }
+
+    #[test]
+    fn security_preference_changes_route() {
+        let g = test_graph();
+        assert_eq!(route(&g, "A", "D", &RouteOptions::new(RoutePreference::PreferHighSec)), Some(vec![s("C"), s("E"), s("D")]));
+        assert_eq!(route(&g, "A", "D", &RouteOptions::new(RoutePreference::PreferLowSec)), Some(vec![s("B"), s("D")]));
+    }
+
+    #[test]
+    fn unreachable_destination_has_no_route() {
+        let g = test_graph();
+        assert_eq!(route(&g, "A", "X", &RouteOptions::new(RoutePreference::Shortest)), None);
+        assert_eq!(route(&g, "A", "Nowhere", &RouteOptions::new(RoutePreference::Shortest)), None);
+        assert_eq!(route(&g, "Nowhere", "A", &RouteOptions::new(RoutePreference::Shortest)), None);
+    }
+
+    #[test]
+    fn same_system_route_is_empty() {
+        let g = test_graph();
+        assert_eq!(route(&g, "A", "A", &RouteOptions::new(RoutePreference::Shortest)), Some(vec![]));
+    }
+
+    #[test]
+    fn avoided_systems_and_regions_are_routed_around() {
+        let g = test_graph();
+        let mut opts = RouteOptions::new(RoutePreference::Shortest);
+        opts.avoid_systems.insert(s("B"));
+        assert_eq!(route(&g, "A", "D", &opts), Some(vec![s("C"), s("E"), s("D")]));
+
+        // avoiding both ways round leaves nothing
+        opts.avoid_regions.insert(s("R2"));
+        assert_eq!(route(&g, "A", "D", &opts), None);
+
+        // the destination itself is never avoided
+        let mut opts = RouteOptions::new(RoutePreference::Shortest);
+        opts.avoid_systems.insert(s("D"));
+        assert_eq!(route(&g, "A", "D", &opts), Some(vec![s("B"), s("D")]));
+    }
+
+    #[test]
+    fn jump_count_and_region() {
+        let g = test_graph();
+        assert_eq!(g.jump_count(&s("A"), &s("D")), Some(2));
+        assert_eq!(g.jump_count(&s("A"), &s("A")), Some(0));
+        assert_eq!(g.jump_count(&s("A"), &s("X")), None);
+        assert_eq!(g.get_region(&s("E")), Some(&s("R2")));
+        assert_eq!(g.get_region(&s("Nowhere")), None);
+    }
+}
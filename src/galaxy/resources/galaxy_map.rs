@@ -1,8 +1,16 @@
 use bevy_ecs::prelude::*;
 
-use crate::galaxy::galaxy_map::GalaxyMap;
+use crate::galaxy::{galaxy_map::GalaxyMap, pathfinding::RouteGraph, bundles::structures::BGate};
 
 #[derive(Resource)]
 pub struct GalaxyMapRes {
-    pub gmap: GalaxyMap
+    pub gmap: GalaxyMap,
+    pub graph: RouteGraph
+}
+
+impl GalaxyMapRes {
+    pub fn new(gmap: GalaxyMap, gates: &[BGate]) -> Self {
+        let graph = RouteGraph::new(&gmap, gates);
+        GalaxyMapRes { gmap, graph }
+    }
 }
\ No newline at end of file
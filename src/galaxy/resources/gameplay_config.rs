@@ -0,0 +1,14 @@
+use bevy_ecs::system::Resource;
+
+use crate::config::CfgGameplay;
+
+#[derive(Resource)]
+pub struct GameplayConfig {
+    pub cfg: CfgGameplay
+}
+
+impl GameplayConfig {
+    pub fn new(cfg: CfgGameplay) -> Self {
+        GameplayConfig { cfg }
+    }
+}
@@ -1,6 +1,10 @@
 pub mod star_system_table;
+pub mod spatial_index;
 pub mod galaxy_map;
 pub mod path_to_entity;
 pub mod network_handler;
 pub mod database_resource;
-pub mod delta_time;
\ No newline at end of file
+pub mod delta_time;
+pub mod production_queue;
+pub mod skill_queue;
+pub mod gameplay_config;
\ No newline at end of file
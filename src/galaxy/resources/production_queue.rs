@@ -0,0 +1,26 @@
+use bevy_ecs::prelude::*;
+
+use crate::db::ProductionJob;
+
+/// Every running production job, the database copy is what survives restarts
+#[derive(Resource, Debug)]
+pub struct ProductionQueue {
+    jobs: Vec<ProductionJob>,
+}
+
+impl ProductionQueue {
+    pub fn new(jobs: Vec<ProductionJob>) -> Self {
+        ProductionQueue { jobs }
+    }
+
+    pub fn add(&mut self, job: ProductionJob) {
+        self.jobs.push(job);
+    }
+
+    /// removes and returns every job that finished at or before now
+    pub fn take_finished(&mut self, now: i64) -> Vec<ProductionJob> {
+        let (done, running) = self.jobs.drain(..).partition(|j| j.finishes_at <= now);
+        self.jobs = running;
+        done
+    }
+}
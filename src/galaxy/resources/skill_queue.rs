@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+
+/// When each player's training queue next completes a level, the queue itself lives in the database
+#[derive(Resource, Debug)]
+pub struct SkillQueue {
+    next: HashMap<String, i64>, // player, unix timestamp
+}
+
+impl SkillQueue {
+    pub fn new(heads: Vec<(String, i64)>) -> Self {
+        SkillQueue { next: heads.into_iter().collect() }
+    }
+
+    pub fn set(&mut self, player: &String, next: Option<i64>) {
+        match next {
+            Some(t) => { self.next.insert(player.clone(), t); },
+            None => { self.next.remove(player); }
+        }
+    }
+
+    /// every player with a level that finished at or before now
+    pub fn due(&self, now: i64) -> Vec<String> {
+        self.next.iter().filter(|(_, t)| **t <= now).map(|(p, _)| p.clone()).collect()
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use nalgebra::Vector3;
+
+// edge length of a grid cell, roughly the sensor range of a ship so a sweep only touches the neighbouring cells
+pub const CELL_SIZE_M: f64 = 200_000.0;
+
+type Cell = (i64, i64, i64);
+
+#[derive(Debug, Clone)]
+pub struct IndexEntry {
+    pub sys: String,
+    pub pos: Vector3<f64>,
+    pub sig_m: Option<f64>, // None for static objects
+    cell: Cell,
+}
+
+/// Uniform grid of every game object, one grid per star system. Kept up to date in bookkeeping alongside the SystemMapTable
+#[derive(Resource, Debug)]
+pub struct SpatialIndex {
+    // map from systems to the entities in each occupied cell
+    grids: HashMap<String, HashMap<Cell, Vec<Entity>>>,
+
+    // map from entities to where they were last indexed
+    entity_table: HashMap<Entity, IndexEntry>,
+
+    // biggest signature currently indexed, used to bound sensor sweeps
+    max_sig_m: f64,
+}
+
+fn cell_of(pos: &Vector3<f64>) -> Cell {
+    ((pos.x / CELL_SIZE_M).floor() as i64, (pos.y / CELL_SIZE_M).floor() as i64, (pos.z / CELL_SIZE_M).floor() as i64)
+}
+
+impl SpatialIndex {
+    pub fn new() -> Self {
+        SpatialIndex { grids: HashMap::new(), entity_table: HashMap::new(), max_sig_m: 0.0 }
+    }
+
+    pub fn max_sig_m(&self) -> f64 {
+        self.max_sig_m
+    }
+
+    pub fn update_entity(&mut self, system: &String, entity: Entity, pos: Vector3<f64>, sig_m: Option<f64>) {
+        let cell = cell_of(&pos);
+
+        if let Some(entry) = self.entity_table.get_mut(&entity) {
+            if entry.sys == *system && entry.cell == cell {
+                // still in the same cell, only the position needs updating
+                entry.pos = pos;
+                let old_sig_m = std::mem::replace(&mut entry.sig_m, sig_m);
+                if old_sig_m != sig_m {
+                    self.forget_sig(old_sig_m);
+                    self.max_sig_m = self.max_sig_m.max(sig_m.unwrap_or(0.0));
+                }
+                return;
+            }
+        }
+
+        self.remove_entity(entity);
+        self.grids.entry(system.clone()).or_default().entry(cell).or_default().push(entity);
+        self.entity_table.insert(entity, IndexEntry { sys: system.clone(), pos, sig_m, cell });
+        self.max_sig_m = self.max_sig_m.max(sig_m.unwrap_or(0.0));
+    }
+
+    /// a signature has left the index (or shrunk), if it was the biggest one the bound has to be worked out again
+    fn forget_sig(&mut self, old_sig_m: Option<f64>) {
+        if matches!(old_sig_m, Some(s) if s >= self.max_sig_m) {
+            self.max_sig_m = self.entity_table.values().filter_map(|e| e.sig_m).fold(0.0, f64::max);
+        }
+    }
+
+    pub fn remove_entity(&mut self, entity: Entity) {
+        let entry = match self.entity_table.remove(&entity) {
+            Some(e) => e,
+            None => return
+        };
+
+        if let Some(grid) = self.grids.get_mut(&entry.sys) {
+            if let Some(ents) = grid.get_mut(&entry.cell) {
+                ents.retain(|e| *e != entity);
+                if ents.is_empty() {
+                    grid.remove(&entry.cell);
+                }
+            }
+        }
+        self.forget_sig(entry.sig_m);
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&IndexEntry> {
+        self.entity_table.get(&entity)
+    }
+
+    /// calls f with every entity (and its entry) within radius of pos in the given system
+    pub fn for_each_in_radius<F: FnMut(Entity, &IndexEntry)>(&self, sys: &String, pos: &Vector3<f64>, radius: f64, mut f: F) {
+        let grid = match self.grids.get(sys) {
+            Some(g) => g,
+            None => return
+        };
+
+        let lo = cell_of(&(pos - Vector3::repeat(radius)));
+        let hi = cell_of(&(pos + Vector3::repeat(radius)));
+
+        // if the search box covers more cells than are occupied, it is cheaper to walk the occupied ones
+        let span = ((hi.0 - lo.0 + 1) as u128) * ((hi.1 - lo.1 + 1) as u128) * ((hi.2 - lo.2 + 1) as u128);
+        if span > grid.len() as u128 {
+            for (cell, ents) in grid.iter() {
+                if cell.0 < lo.0 || cell.0 > hi.0 || cell.1 < lo.1 || cell.1 > hi.1 || cell.2 < lo.2 || cell.2 > hi.2 { continue; }
+                self.visit_cell(ents, pos, radius, &mut f);
+            }
+            return;
+        }
+
+        for x in lo.0..=hi.0 {
+            for y in lo.1..=hi.1 {
+                for z in lo.2..=hi.2 {
+                    if let Some(ents) = grid.get(&(x, y, z)) {
+                        self.visit_cell(ents, pos, radius, &mut f);
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit_cell<F: FnMut(Entity, &IndexEntry)>(&self, ents: &[Entity], pos: &Vector3<f64>, radius: f64, f: &mut F) {
+        for e in ents.iter() {
+            let entry = self.entity_table.get(e).expect("Entity in grid is missing from the entity table");
+            if entry.pos.metric_distance(pos) <= radius {
+                f(*e, entry);
+            }
+        }
+    }
+
+    /// true if both entities are in the same system and strictly closer than range
+    pub fn in_range(&self, a: Entity, b: Entity, range: f64) -> bool {
+        match (self.entity_table.get(&a), self.entity_table.get(&b)) {
+            (Some(ea), Some(eb)) => ea.sys == eb.sys && ea.pos.metric_distance(&eb.pos) < range,
+            _ => false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    fn spawn_random(world: &mut World, idx: &mut SpatialIndex, sys: &String, n: usize, spread: f64, rng: &mut StdRng) -> Vec<(Entity, Vector3<f64>)> {
+        (0..n).map(|_| {
+            let e = world.spawn_empty().id();
+            let pos = Vector3::new(rng.gen_range(-spread..spread), rng.gen_range(-spread..spread), rng.gen_range(-spread..spread));
+            idx.update_entity(sys, e, pos, Some(10.0));
+            (e, pos)
+        }).collect()
+    }
+
+    fn query_radius(idx: &SpatialIndex, sys: &String, pos: &Vector3<f64>, radius: f64) -> Vec<Entity> {
+        let mut out = vec![];
+        idx.for_each_in_radius(sys, pos, radius, |e, _| out.push(e));
+        out
+    }
+
+    fn brute_force(ents: &[(Entity, Vector3<f64>)], pos: &Vector3<f64>, radius: f64) -> Vec<Entity> {
+        ents.iter().filter(|(_, p)| p.metric_distance(pos) <= radius).map(|(e, _)| *e).collect()
+    }
+
+    #[test]
+    fn query_matches_brute_force() {
+        let mut world = World::new();
+        let mut idx = SpatialIndex::new();
+        let mut rng = StdRng::seed_from_u64(7);
+        let sys = String::from("Test");
+        let ents = spawn_random(&mut world, &mut idx, &sys, 500, 1_000_000.0, &mut rng);
+
+        for (_, pos) in ents.iter().take(50) {
+            let mut got = query_radius(&idx, &sys, pos, 250_000.0);
+            let mut expected = brute_force(&ents, pos, 250_000.0);
+            got.sort();
+            expected.sort();
+            assert_eq!(got, expected);
+        }
+    }
+
+    #[test]
+    fn moves_and_removals() {
+        let mut world = World::new();
+        let mut idx = SpatialIndex::new();
+        let a = String::from("A");
+        let b = String::from("B");
+        let e1 = world.spawn_empty().id();
+        let e2 = world.spawn_empty().id();
+
+        idx.update_entity(&a, e1, Vector3::zeros(), Some(10.0));
+        idx.update_entity(&a, e2, Vector3::new(1000.0, 0.0, 0.0), None);
+        assert!(idx.in_range(e1, e2, 2500.0));
+        assert!(!idx.in_range(e1, e2, 1000.0));
+
+        // moving far away changes cell
+        idx.update_entity(&a, e2, Vector3::new(10.0 * CELL_SIZE_M, 0.0, 0.0), None);
+        assert_eq!(query_radius(&idx, &a, &Vector3::zeros(), 5000.0), vec![e1]);
+
+        // same coordinates in a different system are not in range
+        idx.update_entity(&b, e2, Vector3::zeros(), None);
+        assert!(!idx.in_range(e1, e2, 2500.0));
+        assert_eq!(query_radius(&idx, &b, &Vector3::zeros(), 1.0), vec![e2]);
+
+        idx.remove_entity(e2);
+        assert!(idx.get(e2).is_none());
+        assert!(query_radius(&idx, &b, &Vector3::zeros(), 1.0).is_empty());
+    }
+
+    #[test]
+    fn max_sig_follows_removals() {
+        let mut world = World::new();
+        let mut idx = SpatialIndex::new();
+        let sys = String::from("A");
+        let small = world.spawn_empty().id();
+        let big = world.spawn_empty().id();
+        let bigger = world.spawn_empty().id();
+
+        idx.update_entity(&sys, small, Vector3::zeros(), Some(10.0));
+        idx.update_entity(&sys, big, Vector3::zeros(), Some(400.0));
+        idx.update_entity(&sys, bigger, Vector3::new(10.0 * CELL_SIZE_M, 0.0, 0.0), Some(5000.0));
+        assert_eq!(idx.max_sig_m(), 5000.0);
+
+        // shrinking in place (same cell)
+        idx.update_entity(&sys, bigger, Vector3::new(10.0 * CELL_SIZE_M, 0.0, 0.0), Some(50.0));
+        assert_eq!(idx.max_sig_m(), 400.0);
+
+        // moving cell with a smaller signature
+        idx.update_entity(&sys, big, Vector3::new(-10.0 * CELL_SIZE_M, 0.0, 0.0), Some(20.0));
+        assert_eq!(idx.max_sig_m(), 50.0);
+
+        idx.remove_entity(bigger);
+        assert_eq!(idx.max_sig_m(), 20.0);
+
+        // removing something smaller leaves the bound alone
+        idx.remove_entity(small);
+        assert_eq!(idx.max_sig_m(), 20.0);
+
+        // the only signature left shrinking while it changes cell
+        idx.update_entity(&sys, big, Vector3::zeros(), Some(15.0));
+        assert_eq!(idx.max_sig_m(), 15.0);
+
+        idx.remove_entity(big);
+        assert_eq!(idx.max_sig_m(), 0.0);
+    }
+
+    // cargo test --release -- --ignored --nocapture bench_sensor_sweep
+    #[test]
+    #[ignore]
+    fn bench_sensor_sweep() {
+        let sys = String::from("Bench");
+        let radius = 200_000.0;
+        for n in [500, 1000, 2000, 4000, 8000] {
+            let mut world = World::new();
+            let mut idx = SpatialIndex::new();
+            let mut rng = StdRng::seed_from_u64(n as u64);
+            // keep density constant so the number of objects each sensor can actually see stays the same
+            let spread = 2_000_000.0 * (n as f64 / 1000.0).cbrt();
+            let ents = spawn_random(&mut world, &mut idx, &sys, n, spread, &mut rng);
+
+            let start = Instant::now();
+            let mut naive_hits = 0;
+            for (_, pos) in ents.iter() {
+                naive_hits += brute_force(&ents, pos, radius).len();
+            }
+            let naive = start.elapsed();
+
+            let start = Instant::now();
+            let mut grid_hits = 0;
+            for (_, pos) in ents.iter() {
+                idx.for_each_in_radius(&sys, pos, radius, |_, _| grid_hits += 1);
+            }
+            let grid = start.elapsed();
+
+            assert_eq!(naive_hits, grid_hits);
+            println!("n = {:>5}: all pairs {:>10.2?}, grid {:>10.2?} ({:.1}x)", n, naive, grid, naive.as_secs_f64() / grid.as_secs_f64());
+        }
+    }
+}
@@ -1,21 +1,29 @@
 use bevy_ecs::{prelude::*, event::Event};
 
-use crate::{db::database::DB, galaxy::events::{EEvent, EInfo, EState}};
+use crate::{db::database::DB, galaxy::events::{EEvent, EInfo, EState}, config::CfgGameplay};
 
 use super::super::resources::*;
 
-pub fn init_resources<'a>(world: &mut World, db: DB) {
+pub fn init_resources<'a>(world: &mut World, db: DB, gameplay: CfgGameplay) {
     let path_table = path_to_entity::PathToEntityMap::new();
     let entity_table = star_system_table::SystemMapTable::new();
+    let spatial_index = spatial_index::SpatialIndex::new();
     let network_table = network_handler::NetworkHandler::new();
+    let production_queue = production_queue::ProductionQueue::new(db.production_load_jobs());
+    let skill_queue = skill_queue::SkillQueue::new(db.skills_load_queue_heads());
     let db_res = database_resource::DatabaseResource::new(db);
     let dt_res = delta_time::DeltaTime::new();
+    let gameplay_res = gameplay_config::GameplayConfig::new(gameplay);
 
     world.insert_resource(path_table);
     world.insert_resource(entity_table);
+    world.insert_resource(spatial_index);
     world.insert_resource(network_table);
     world.insert_resource(db_res);
     world.insert_resource(dt_res);
+    world.insert_resource(gameplay_res);
+    world.insert_resource(production_queue);
+    world.insert_resource(skill_queue);
     world.init_resource::<Events<EEvent>>();
     world.init_resource::<Events<EInfo>>();
     world.init_resource::<Events<EState>>();
@@ -24,6 +24,19 @@ pub fn generate_schedule() -> Schedule {
     network_stage.add_system(market::sys_process_market); // want this to process before inventory motion later
     network_stage.add_system(hanger_mgmt::hanger_mgmt); // this can process at the same time as the market, but not at the same time as inventory management
     network_stage.add_system(inventory_mgmt::sys_inventory_service_inventory_requests); // this actually does a bit of heavy lifting to grab stations from inventory IDs
+    network_stage.add_system(bookmarks::sys_process_bookmarks);
+    network_stage.add_system(autopilot::sys_process_autopilot_inputs);
+    network_stage.add_system(routing::sys_process_route_requests);
+    network_stage.add_system(targeting::sys_process_lock_inputs);
+    network_stage.add_system(mining::sys_process_mining_inputs);
+    network_stage.add_system(combat::sys_process_weapon_inputs);
+    network_stage.add_system(inventory_mgmt::sys_process_loot_requests);
+    // these read, change and write back the same inventories and cargo as the market, running them side by side loses writes
+    network_stage.add_system(production::sys_process_production_inputs.after(market::sys_process_market));
+    network_stage.add_system(refining::sys_process_refining.after(production::sys_process_production_inputs));
+    network_stage.add_system(inventory_mgmt::sys_process_jettison.after(refining::sys_process_refining));
+    network_stage.add_system(extraction::sys_process_extractor_inputs.after(inventory_mgmt::sys_process_jettison));
+    network_stage.add_system(skills::sys_process_skill_inputs);
 
     // entities examining other entities find them and collect the info they want (before it gets mutated)
     let mut find_stage = SystemStage::parallel();
@@ -33,6 +46,17 @@ pub fn generate_schedule() -> Schedule {
     let mut action_stage = SystemStage::parallel();
     action_stage.add_system(navigation::sys_tick_navigation);
     action_stage.add_system(jump::sys_process_jump_inputs);
+    action_stage.add_system(autopilot::sys_tick_autopilot);
+    action_stage.add_system(targeting::sys_tick_targeting);
+    action_stage.add_system(mining::sys_tick_mining);
+    action_stage.add_system(mining::sys_respawn_asteroids);
+    action_stage.add_system(combat::sys_tick_weapons);
+    action_stage.add_system(combat::sys_tick_missiles);
+    action_stage.add_system(combat::sys_regen_shields);
+    action_stage.add_system(extraction::sys_tick_extractors);
+    action_stage.add_system(production::sys_tick_production);
+    action_stage.add_system(skills::sys_tick_skills);
+    action_stage.add_system(market::sys_expire_market_orders);
     action_stage.add_system(docking_undocking::sys_process_dock);
     action_stage.add_system(inventory_mgmt::sys_manage_inventory_transfers);
 
@@ -42,6 +66,11 @@ pub fn generate_schedule() -> Schedule {
 
     // things that might die get checked for death here, and scheduled for kill if needed
     let mut death_stage = SystemStage::parallel();
+    death_stage.add_system(mining::sys_despawn_depleted_asteroids);
+    death_stage.add_system(combat::sys_process_deaths);
+    death_stage.add_system(combat::sys_dispatch_health);
+    death_stage.add_system(combat::sys_despawn_wrecks);
+    death_stage.add_system(inventory_mgmt::sys_despawn_jet_cans);
 
     death_stage.add_system(logon_mgmt::sys_dispatch_login_info);
 
@@ -61,6 +90,7 @@ pub fn generate_schedule() -> Schedule {
     update_stage
         .add_system(path_table_bookeeping::update_path_table)
         .add_system(star_system_table_bookeeping::update_star_system_table)
+        .add_system(spatial_index_bookeeping::update_spatial_index)
         .add_system(logon_mgmt::logon_bookeeping_handle_send_initial_info);
 
     // all the bookkeeping for things that died is handled here
@@ -0,0 +1,152 @@
+use bevy_ecs::prelude::*;
+
+use crate::galaxy::components::*;
+use crate::galaxy::events::{EEvent, EInfo};
+use crate::galaxy::resources::{galaxy_map::GalaxyMapRes, network_handler::NetworkHandler, path_to_entity::PathToEntityMap};
+use crate::network::messages::incoming::NetIncomingMessage;
+use crate::shared::{ObjPath, ObjectType};
+
+use super::jump::{jump_through_gate, GateParams};
+
+// once we are this close to the gate we approach it instead of warping to it
+const AUTOPILOT_APPROACH_DIST_M: f64 = 150_000.0;
+
+/// PROCESS AUTOPILOT MESSAGES, any other navigation command for the ship takes control back from the autopilot
+/// Stage: COMMAND
+pub fn sys_process_autopilot_inputs(mut ships: Query<(&PlayerController, &mut Autopilot)>, ptm: Res<PathToEntityMap>, gmap: Res<GalaxyMapRes>, net: Res<NetworkHandler>, mut eev: EventWriter<EEvent>, mut ein: EventWriter<EInfo>) {
+    for slot in net.view_incoming() {
+        let player = slot.key();
+        for msg in slot.value().iter() {
+            let ship_path = match msg {
+                NetIncomingMessage::SetAutopilot(ship_path, _, _) => ship_path,
+                NetIncomingMessage::CancelAutopilot(ship_path) => ship_path,
+                NetIncomingMessage::WarpTo(ship_path, _, _) => ship_path,
+                NetIncomingMessage::WarpToBookmark(ship_path, _, _) => ship_path,
+                NetIncomingMessage::Approach(ship_path, _) => ship_path,
+                NetIncomingMessage::Orbit(ship_path, _, _) => ship_path,
+                NetIncomingMessage::KeepAtRange(ship_path, _, _) => ship_path,
+                NetIncomingMessage::AlignTo(ship_path, _) => ship_path,
+                NetIncomingMessage::MNav(ship_path, _, _, _, _) => ship_path,
+                NetIncomingMessage::Jump(ship_path, _) => ship_path,
+                NetIncomingMessage::Dock(ship_path, _) => ship_path,
+                _ => { continue; }
+            };
+
+            let ship_ent = match ptm.get(ship_path) {
+                Some(s) => s,
+                None => { eprintln!("Player requesting autopilot for nonexistent ship"); continue; }
+            };
+
+            let (pc, mut ap) = match ships.get_mut(ship_ent) {
+                Ok(s) => s,
+                Err(_) => { eprintln!("Autopilot ship not found in space: {}", player); continue; }
+            };
+
+            if pc.player_name != *player {
+                eprintln!("Player sending autopilot command for ship they don't own");
+                continue;
+            }
+
+            match msg {
+                NetIncomingMessage::SetAutopilot(_, dst, opts) => {
+                    if *dst == ship_path.sys {
+                        ein.send(EInfo::Error(player.clone(), String::from("You are already in that system")));
+                        continue;
+                    }
+
+                    match gmap.graph.find_route(&ship_path.sys, dst, opts) {
+                        Some(route) => {
+                            eev.send(EEvent::AutopilotRoute(player.clone(), route.iter().cloned().collect()));
+                            ap.engage(dst.clone(), route);
+                        },
+                        None => {
+                            ein.send(EInfo::Error(player.clone(), format!("No route found to {}", dst)));
+                        }
+                    }
+                },
+                _ => {
+                    if ap.is_engaged() {
+                        ap.disengage();
+                        eev.send(EEvent::AutopilotDisengaged(player.clone()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// FLIES ENGAGED AUTOPILOTS TO THEIR NEXT GATE AND JUMPS THROUGH IT
+/// Stage: ACTION
+pub fn sys_tick_autopilot(
+    mut ships: Query<(Entity, &PlayerController, &mut Autopilot, &mut Transform, &mut GameObject, &mut Navigation)>,
+    warp_targets: Query<&WarpTarget>,
+    mut gp: GateParams,
+){
+    for (ent, pc, mut ap, mut transform, mut go, mut nav) in ships.iter_mut() {
+        if !ap.is_engaged() {
+            continue;
+        }
+
+        if !matches!(pc.login_state, LoginState::LoggedIn) {
+            ap.disengage();
+            continue;
+        }
+
+        // we just came through a gate
+        if ap.route.front() == Some(&go.path.sys) {
+            ap.route.pop_front();
+            gp.eev.send(EEvent::AutopilotRoute(pc.player_name.clone(), ap.route.iter().cloned().collect()));
+        }
+
+        let next_sys = match ap.route.front() {
+            Some(n) => n.clone(),
+            None => {
+                gp.eev.send(EEvent::AutopilotArrived(pc.player_name.clone(), go.path.sys.clone()));
+                ap.disengage();
+                continue;
+            }
+        };
+
+        if !matches!(nav.warp_state, WarpState::NotWarping) {
+            continue; // let the warp finish
+        }
+
+        // gates are always named after the systems they link
+        let gate_path = ObjPath::new(&go.path.sys, ObjectType::Gate, &format!("{}->{}", go.path.sys, next_sys));
+        let (gate_ent, jump_range, gate_pos) = match gp.ptm.get(&gate_path).and_then(|e| gp.gates.get(e).ok().map(|(g, t, _)| (e, g.jump_range, t.pos))) {
+            Some(g) => g,
+            None => {
+                eprintln!("Autopilot could not find gate {:?}", gate_path);
+                ap.disengage();
+                gp.eev.send(EEvent::AutopilotDisengaged(pc.player_name.clone()));
+                continue;
+            }
+        };
+
+        let dist = transform.pos.metric_distance(&gate_pos);
+        if dist < jump_range {
+            if let Err(e) = jump_through_gate(&pc.player_name, ent, &mut transform, &mut go, &mut nav, gate_ent, &mut gp) {
+                eprintln!("Autopilot failed to jump: {}", e);
+                ap.disengage();
+                gp.eev.send(EEvent::AutopilotDisengaged(pc.player_name.clone()));
+            }
+        }
+        else if dist < AUTOPILOT_APPROACH_DIST_M {
+            if nav.cur_action != Action::Approach {
+                nav.reset_banked();
+                nav.cur_action = Action::Approach;
+                nav.target = NavTarget::Obj(gate_path);
+            }
+        }
+        else {
+            let warp_point = match warp_targets.get(gate_ent) {
+                Ok(wt) => wt.warp_point,
+                Err(_) => gate_pos
+            };
+            nav.reset_banked();
+            nav.cur_action = Action::Warp(0.0);
+            nav.warp_state = WarpState::Aligning;
+            nav.target = NavTarget::Point(warp_point);
+        }
+    }
+}
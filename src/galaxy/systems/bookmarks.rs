@@ -0,0 +1,70 @@
+use bevy_ecs::prelude::*;
+
+use crate::db::Bookmark;
+use crate::galaxy::components::*;
+use crate::galaxy::events::EInfo;
+use crate::galaxy::resources::{database_resource::DatabaseResource, gameplay_config::GameplayConfig, network_handler::NetworkHandler, path_to_entity::PathToEntityMap};
+use crate::network::messages::incoming::NetIncomingMessage;
+
+const SHARED_FOLDER: &'static str = "Shared";
+
+pub fn sys_process_bookmarks(ships: Query<(&PlayerController, &Transform)>, ptm: Res<PathToEntityMap>, net: Res<NetworkHandler>, db: Res<DatabaseResource>, gameplay: Res<GameplayConfig>, mut ein: EventWriter<EInfo>) {
+    for slot in net.view_incoming() {
+        let player = slot.key();
+        let msgs = slot.value();
+        for msg in msgs.iter() {
+            let result = match msg {
+                NetIncomingMessage::CreateBookmark(ship_path, label, folder) => {
+                    let ship_ent = match ptm.get(ship_path) {
+                        Some(s) => s,
+                        None => { eprintln!("Player bookmarking from nonexistent ship"); continue; }
+                    };
+
+                    let (pc, t) = match ships.get(ship_ent) {
+                        Ok(s) => s,
+                        Err(_) => { eprintln!("Bookmark ship not found in space: {}", player); continue; }
+                    };
+
+                    if pc.player_name != *player {
+                        eprintln!("Player bookmarking with ship they don't own");
+                        continue;
+                    }
+
+                    let bookmark = Bookmark::new(label.clone(), folder.clone(), ship_path.sys.clone(), t.pos);
+                    db.db.bookmark_add(player, bookmark).map(|_| ())
+                },
+                NetIncomingMessage::RenameBookmark(id, label) => db.db.bookmark_rename(player, *id, label.clone()),
+                NetIncomingMessage::MoveBookmark(id, folder) => db.db.bookmark_move(player, *id, folder.clone()),
+                NetIncomingMessage::DeleteBookmark(id) => db.db.bookmark_delete(player, *id).map(|_| ()),
+                NetIncomingMessage::ShareBookmark(id, other) => {
+                    if other == player {
+                        Err(String::from("You cannot share a bookmark with yourself"))
+                    }
+                    else if !db.db.account_exists(other) {
+                        Err(format!("Player {} does not exist", other))
+                    }
+                    else {
+                        match db.db.bookmark_get(player, *id) {
+                            Some(bm) => {
+                                let shared = bm.share(player, String::from(SHARED_FOLDER));
+                                let res = db.db.bookmark_add_shared(other, shared, gameplay.cfg.max_shared_bookmarks).map(|_| ());
+                                if res.is_ok() {
+                                    ein.send(EInfo::UpdateBookmarks(other.clone()));
+                                }
+                                res
+                            },
+                            None => Err(String::from("Bookmark not found"))
+                        }
+                    }
+                },
+                NetIncomingMessage::RequestBookmarks => Ok(()),
+                _ => { continue; }
+            };
+
+            match result {
+                Ok(()) => ein.send(EInfo::UpdateBookmarks(player.clone())),
+                Err(e) => ein.send(EInfo::Error(player.clone(), e))
+            };
+        }
+    }
+}
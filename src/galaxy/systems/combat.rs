@@ -0,0 +1,305 @@
+use std::collections::HashSet;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+use chrono::Utc;
+
+use crate::galaxy::bundles::ships::{BMissile, BWreck};
+use crate::galaxy::components::*;
+use crate::galaxy::events::{EEvent, EInfo, EState};
+use crate::galaxy::resources::{database_resource::DatabaseResource, gameplay_config::GameplayConfig, delta_time::DeltaTime, network_handler::NetworkHandler, path_to_entity::PathToEntityMap, spatial_index::SpatialIndex};
+use crate::inventory::WeaponKind;
+use crate::network::messages::incoming::NetIncomingMessage;
+use crate::shared::{ObjPath, ObjectType};
+
+/// PROCESS WEAPON ACTIVATION COMMANDS
+/// Stage: COMMAND
+pub fn sys_process_weapon_inputs(mut ships: Query<(&PlayerController, &Targeting, &mut Weapons)>, damageable: Query<&Health>, ptm: Res<PathToEntityMap>, net: Res<NetworkHandler>, mut est: EventWriter<EState>, mut ein: EventWriter<EInfo>) {
+    for slot in net.view_incoming() {
+        let player = slot.key();
+        for msg in slot.value().iter() {
+            let res = match msg {
+                NetIncomingMessage::ActivateWeapon(ship, high_slot, target) => activate_weapon(&mut ships, &damageable, &ptm, player, ship, *high_slot, target)
+                    .map(|_| est.send(EState::Weapon(player.clone(), *high_slot, Some(target.clone())))),
+                NetIncomingMessage::DeactivateWeapon(ship, high_slot) => {
+                    match ptm.get(ship).and_then(|e| ships.get_mut(e).ok()) {
+                        Some((pc, _, mut weapons)) if pc.player_name == *player => {
+                            match weapons.get_mut(*high_slot as usize) {
+                                Some(w) => {
+                                    w.target = None;
+                                    est.send(EState::Weapon(player.clone(), *high_slot, None));
+                                    Ok(())
+                                },
+                                None => Err(format!("No weapon fitted in high slot {}", high_slot))
+                            }
+                        },
+                        _ => Err(String::from("Ship not found"))
+                    }
+                },
+                _ => Ok(())
+            };
+
+            if let Err(e) = res {
+                ein.send(EInfo::Error(player.clone(), e));
+            }
+        }
+    }
+}
+
+fn activate_weapon(ships: &mut Query<(&PlayerController, &Targeting, &mut Weapons)>, damageable: &Query<&Health>, ptm: &PathToEntityMap, player: &String, ship: &ObjPath, high_slot: u32, target: &ObjPath) -> Result<(), String> {
+    let (pc, targeting, mut weapons) = ptm.get(ship).and_then(|e| ships.get_mut(e).ok()).ok_or(String::from("Ship not found"))?;
+    if pc.player_name != *player {
+        eprintln!("{} trying to shoot with other player's ship", player);
+        return Err(String::from("Not your ship"));
+    }
+
+    if !targeting.locked.contains(target) {
+        return Err(String::from("Target must be locked before it can be shot"));
+    }
+
+    if ptm.get(target).and_then(|e| damageable.get(e).ok()).is_none() {
+        return Err(String::from("Target can not be damaged"));
+    }
+
+    let weapon = weapons.get_mut(high_slot as usize).ok_or(format!("No weapon fitted in high slot {}", high_slot))?;
+    // an idle weapon fires straight away, switching targets keeps the current cycle
+    weapon.target = Some(target.clone());
+    Ok(())
+}
+
+/// What a weapon needs to find, reach, and hit its target
+#[derive(SystemParam)]
+pub struct FiringParams<'w, 's> {
+    targets: Query<'w, 's, &'static mut Health>,
+    ptm: Res<'w, PathToEntityMap>,
+    index: Res<'w, SpatialIndex>,
+    db: Res<'w, DatabaseResource>,
+    commands: Commands<'w, 's>,
+}
+
+/// CYCLES WEAPONS, turrets hit at the end of the cycle and launchers put a missile in space
+/// Stage: ACTION
+pub fn sys_tick_weapons(
+    mut ships: Query<(Entity, &PlayerController, &Ship, &Targeting, &mut Weapons, &GameObject, &Transform)>,
+    mut fp: FiringParams,
+    dt: Res<DeltaTime>,
+    mut est: EventWriter<EState>,
+    mut eev: EventWriter<EEvent>,
+    mut ein: EventWriter<EInfo>,
+){
+    let FiringParams { targets, ptm, index, db, commands } = &mut fp;
+    for (ent, pc, ship, targeting, mut weapons, go, t) in ships.iter_mut() {
+        for w in weapons.slots.iter_mut() {
+            if w.cycle_left_s > 0.0 {
+                w.cycle_left_s -= dt.dt;
+            }
+
+            let target = match &w.target {
+                Some(t) => t.clone(),
+                None => continue
+            };
+
+            // the lock broke or the target is gone
+            let target_ent = match ptm.get(&target) {
+                Some(e) if targeting.locked.contains(&target) => e,
+                _ => {
+                    w.target = None;
+                    est.send(EState::Weapon(pc.player_name.clone(), w.slot as u32, None));
+                    continue;
+                }
+            };
+
+            if !index.in_range(ent, target_ent, w.stats.range_m) {
+                w.target = None;
+                est.send(EState::Weapon(pc.player_name.clone(), w.slot as u32, None));
+                ein.send(EInfo::Error(pc.player_name.clone(), String::from("Target is out of weapon range")));
+                continue;
+            }
+
+            if w.cycle_left_s > 0.0 {
+                continue;
+            }
+            w.cycle_left_s += w.stats.cycle_s;
+
+            let damage = w.stats.damage * ship.stats.damage_mult;
+            match w.stats.kind {
+                WeaponKind::Turret => {
+                    match targets.get_mut(target_ent) {
+                        Ok(mut h) => h.apply_damage(damage),
+                        Err(_) => { eprintln!("Weapon target {:?} has no health", target); w.target = None; continue; }
+                    };
+                    eev.send(EEvent::WeaponFired(pc.player_name.clone(), go.path.clone(), target.clone(), damage));
+                },
+                WeaponKind::Missile => {
+                    commands.spawn(BMissile::new(db.db.object_new_id(), &pc.player_name, &go.path, &target, t, damage, &w.stats));
+                    eev.send(EEvent::WeaponFired(pc.player_name.clone(), go.path.clone(), target.clone(), 0.0));
+                }
+            }
+        }
+    }
+}
+
+/// FLIES MISSILES AT THEIR TARGETS, they explode on arrival or fizzle out when their fuel runs out
+/// Stage: ACTION
+pub fn sys_tick_missiles(mut missiles: Query<(Entity, &mut Missile, &mut Transform)>, positions: Query<&Transform, Without<Missile>>, mut targets: Query<&mut Health>, ptm: Res<PathToEntityMap>, dt: Res<DeltaTime>, mut commands: Commands, mut eev: EventWriter<EEvent>) {
+    for (ent, mut missile, mut t) in missiles.iter_mut() {
+        let target_ent = match ptm.get(&missile.target) {
+            Some(e) if missile.flight_left_s > 0.0 => e,
+            _ => { commands.entity(ent).despawn(); continue; }
+        };
+
+        let target_pos = match positions.get(target_ent) {
+            Ok(tt) => tt.pos,
+            Err(_) => { commands.entity(ent).despawn(); continue; }
+        };
+
+        let to_target = target_pos - t.pos;
+        let step = missile.speed_ms * dt.dt.min(missile.flight_left_s);
+        if to_target.norm() <= step {
+            if let Ok(mut h) = targets.get_mut(target_ent) {
+                h.apply_damage(missile.damage);
+                eev.send(EEvent::WeaponFired(missile.owner.clone(), missile.shooter.clone(), missile.target.clone(), missile.damage));
+            }
+            commands.entity(ent).despawn();
+            continue;
+        }
+
+        missile.flight_left_s -= dt.dt;
+        let dir = to_target.normalize();
+        t.pos += dir * step;
+        t.vel = dir * missile.speed_ms;
+    }
+}
+
+/// RECHARGES SHIELDS
+/// Stage: ACTION
+pub fn sys_regen_shields(mut ships: Query<(&Ship, &mut Health)>, dt: Res<DeltaTime>) {
+    for (ship, mut health) in ships.iter_mut() {
+        if health.shield >= ship.stats.shield_hp {
+            continue;
+        }
+        // clients only see whole hit points, flagging every fractional step would send health updates every tick
+        let before = health.shield.floor();
+        let h = health.bypass_change_detection();
+        h.regen(&ship.stats, dt.dt);
+        if h.shield.floor() != before {
+            health.set_changed();
+        }
+    }
+}
+
+/// everyone who can currently see the object, plus its pilot
+fn observers<'a>(sensors: &'a Query<(&PlayerController, &Sensor)>, path: &ObjPath, owner: Option<&'a PlayerController>) -> HashSet<&'a String> {
+    let mut observers: HashSet<&String> = sensors.iter()
+        .filter(|(_, s)| s.visible_objs.contains(path) || s.lockable_objs.contains(path))
+        .map(|(pc, _)| &pc.player_name)
+        .collect();
+    if let Some(pc) = owner {
+        observers.insert(&pc.player_name);
+    }
+    observers
+}
+
+/// TELLS EVERYONE WHO CAN SEE A DAMAGED OR RECHARGING OBJECT ITS NEW HEALTH
+/// Stage: DEATH
+pub fn sys_dispatch_health(changed: Query<(&Health, &GameObject, Option<&PlayerController>), Changed<Health>>, sensors: Query<(&PlayerController, &Sensor)>, mut est: EventWriter<EState>) {
+    for (health, go, owner) in changed.iter().filter(|(h, _, _)| !h.is_destroyed()) {
+        for player in observers(&sensors, &go.path, owner) {
+            est.send(EState::Health(player.clone(), go.path.clone(), health.clone()));
+        }
+    }
+}
+
+type DamagedObjects<'w, 's> = Query<'w, 's, (Entity, &'static Health, &'static GameObject, &'static Transform, Option<&'static PlayerController>, Option<&'static Ship>), Changed<Health>>;
+
+/// REMOVES DESTROYED SHIPS, LEAVING A WRECK BEHIND, AND TELLS EVERYONE WHO COULD SEE THEM
+/// the pilot wakes up in their home station with a new rookie ship
+/// Stage: DEATH
+pub fn sys_process_deaths(
+    damaged: DamagedObjects,
+    sensors: Query<(&PlayerController, &Sensor)>,
+    respawn: RespawnParams,
+    mut commands: Commands,
+    mut eev: EventWriter<EEvent>,
+    mut ein: EventWriter<EInfo>,
+){
+    let mut rng = rand::thread_rng();
+    for (e, _, go, t, owner, ship) in damaged.iter().filter(|(_, h, _, _, _, _)| h.is_destroyed()) {
+        commands.entity(e).despawn();
+        if let (Some(pc), Some(s)) = (owner, ship) {
+            commands.spawn(BWreck::new(&pc.player_name, s, &go.path, t, &respawn.gameplay.cfg, &respawn.db.db, &mut rng));
+            pay_insurance(&respawn.db, &pc.player_name, s, &mut ein);
+            respawn_at_home(&respawn, &pc.player_name, &mut eev, &mut ein);
+        }
+        for player in observers(&sensors, &go.path, owner) {
+            eev.send(EEvent::Destroyed(player.clone(), go.path.clone()));
+        }
+    }
+}
+
+
+fn pay_insurance(db: &DatabaseResource, player: &String, ship: &Ship, ein: &mut EventWriter<EInfo>) {
+    let contract = match db.db.insurance_claim(player, ship.ship_id, Utc::now().timestamp()) {
+        Some(c) => c,
+        None => return
+    };
+
+    match db.db.bank_apply_transaction(player, contract.payout, format!("Insurance payout for {}", contract.ship_name)) {
+        Some(_) => {
+            ein.send(EInfo::UpdateBankAccount(player.clone()));
+            ein.send(EInfo::Insurance(player.clone()));
+        },
+        None => { eprintln!("Could not pay {} insurance for {}: {:?}", player, contract.ship_name, contract); }
+    }
+}
+
+/// Where a pilot who lost their ship wakes up again
+#[derive(SystemParam)]
+pub struct RespawnParams<'w, 's> {
+    hangers: Query<'w, 's, &'static Hanger>,
+    ptm: Res<'w, PathToEntityMap>,
+    db: Res<'w, DatabaseResource>,
+    gameplay: Res<'w, GameplayConfig>,
+}
+
+fn respawn_at_home(respawn: &RespawnParams, player: &String, eev: &mut EventWriter<EEvent>, ein: &mut EventWriter<EInfo>) {
+    let RespawnParams { hangers, ptm, db, gameplay } = respawn;
+    let home = match db.db.account_get_home_station(player) {
+        Some(h) => h,
+        None => { eprintln!("{} has no home station to respawn in", player); return; }
+    };
+
+    // a home station that no longer exists sends the player back to the starting station
+    let (home, hanger) = match ptm.get(&home).and_then(|e| hangers.get(e).ok()) {
+        Some(h) => (home, h),
+        None => {
+            let start = ObjPath::new(&gameplay.cfg.starting_system, ObjectType::Station, &gameplay.cfg.starting_station);
+            match ptm.get(&start).and_then(|e| hangers.get(e).ok()) {
+                Some(h) => { db.db.account_set_home_station(player, start.clone()); (start, h) },
+                None => { eprintln!("Neither {}'s home station nor the starting station exist, can not respawn", player); return; }
+            }
+        }
+    };
+
+    match db.db.ship_class_table.get(&gameplay.cfg.starting_ship_class) {
+        Some(class) => db.db.hanger_dock(player, hanger.hanger_uid.clone(), Ship::new(db.db.ship_new_id(), String::from("Rookie ship"), class)),
+        None => { eprintln!("Starting ship class {} not found, {} respawns without a ship", gameplay.cfg.starting_ship_class, player); }
+    };
+    db.db.inventory_ensure(player, hanger.hanger_uid.clone());
+    db.db.account_change_location(player, home.clone());
+
+    eev.send(EEvent::Death(player.clone(), home));
+    ein.send(EInfo::UpdateInventoryId(player.clone(), hanger.hanger_uid.clone()));
+    ein.send(EInfo::UpdateInventoryHanger(player.clone(), hanger.hanger_uid.clone()));
+}
+
+/// CLEARS OUT OLD WRECKS, anything left inside is lost
+/// Stage: DEATH
+pub fn sys_despawn_wrecks(mut wrecks: Query<(Entity, &mut Wreck)>, dt: Res<DeltaTime>, mut commands: Commands) {
+    for (e, mut wreck) in wrecks.iter_mut() {
+        wreck.despawn_in_s -= dt.dt;
+        if wreck.despawn_in_s <= 0.0 {
+            commands.entity(e).despawn();
+        }
+    }
+}
@@ -1,14 +1,28 @@
 use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+
+use crate::{galaxy::{components::*, resources::{network_handler::NetworkHandler, path_to_entity::PathToEntityMap, database_resource::DatabaseResource, spatial_index::SpatialIndex}, bundles::ships::BPlayerShip, events::{EEvent, EInfo}}, network::messages::incoming::NetIncomingMessage, shared::ObjPath};
+
+/// world access shared by docking and undocking
+#[derive(SystemParam)]
+pub struct DockParams<'w, 's> {
+    players: Query<'w, 's, (&'static PlayerController, &'static Ship, &'static Transform)>,
+    hangers: Query<'w, 's, (&'static Hanger, &'static Transform)>,
+    ptm: Res<'w, PathToEntityMap>,
+    index: Res<'w, SpatialIndex>,
+    db: Res<'w, DatabaseResource>,
+    commands: Commands<'w, 's>,
+    eev: EventWriter<'w, 's, EEvent>,
+    ein: EventWriter<'w, 's, EInfo>,
+}
 
-use crate::{galaxy::{components::*, resources::{network_handler::NetworkHandler, path_to_entity::PathToEntityMap, database_resource::DatabaseResource}, bundles::ships::BPlayerShip, events::{EEvent, EInfo, EState}}, network::messages::incoming::NetIncomingMessage, shared::ObjPath};
-
-pub fn sys_process_dock(players: Query<(&PlayerController, &Ship, &Transform)>, hangers: Query<(&Hanger, &Transform)>, mut commands: Commands, n: Res<NetworkHandler>, ptm: Res<PathToEntityMap>, db: Res<DatabaseResource>, mut eev: EventWriter<EEvent>, mut ein: EventWriter<EInfo>) {
+pub fn sys_process_dock(mut p: DockParams, n: Res<NetworkHandler>) {
     for player in n.view_incoming() {
         let name = player.key();
         for msg in player.value() {
             match msg {
-                NetIncomingMessage::Dock(ship, station) => handle_dock(&players, &hangers, &mut commands, &ptm, ship, station, &db, name, &mut eev, &mut ein),
-                NetIncomingMessage::Undock(hanger_path) => handle_undock(&hangers, &ptm, hanger_path, &db, name, &mut commands, &mut eev, &mut ein),
+                NetIncomingMessage::Dock(ship, station) => handle_dock(&mut p, ship, station, name),
+                NetIncomingMessage::Undock(hanger_path) => handle_undock(&mut p, hanger_path, name),
                 _ => ()
             }
         }
@@ -16,28 +30,28 @@ pub fn sys_process_dock(players: Query<(&PlayerController, &Ship, &Transform)>,
     }
 }
 
-fn handle_dock(players: &Query<(&PlayerController, &Ship, &Transform)>, hangers: &Query<(&Hanger, &Transform)>, commands: &mut Commands, ptm: &Res<PathToEntityMap>, ship: &ObjPath, station: &ObjPath, db: &Res<DatabaseResource>, player_name: &String, eev: &mut EventWriter<EEvent>, ein: &mut EventWriter<EInfo>) {
+fn handle_dock(p: &mut DockParams, ship: &ObjPath, station: &ObjPath, player_name: &String) {
     if ship.sys != station.sys {
         eprintln!("Can't dock to station in other system");
         return;
     }
     
-    let docking_ent = match ptm.get(ship) {
+    let docking_ent = match p.ptm.get(ship) {
         Some(s) => s,
         None => { eprintln!("Ship entity not found for docking"); return; }
     };
 
-    let station_ent = match ptm.get(station) {
+    let station_ent = match p.ptm.get(station) {
         Some(s) => s,
         None => {
-            ein.send(EInfo::Error(player_name.clone(), "Hanger not found on object".to_string())); 
+            p.ein.send(EInfo::Error(player_name.clone(), "Hanger not found on object".to_string())); 
             eprintln!("Hanger entity not found for docking"); 
             return; 
         }
     };
     
-    let (pc, p_ship, p_transform) = match players.get(docking_ent) {
-        Ok(p) => p,
+    let (pc, p_ship, _p_transform) = match p.players.get(docking_ent) {
+        Ok(pl) => pl,
         Err(_) => {
             eprintln!("Docking nonexistent ship");
             return;
@@ -49,10 +63,10 @@ fn handle_dock(players: &Query<(&PlayerController, &Ship, &Transform)>, hangers:
         return;
     }
 
-    let (hanger, h_transform) = match hangers.get(station_ent) {
+    let (hanger, _h_transform) = match p.hangers.get(station_ent) {
         Ok(h) => h,
         Err(_) => {
-            ein.send(EInfo::Error(player_name.clone(), "Hanger not found on object".to_string())); 
+            p.ein.send(EInfo::Error(player_name.clone(), "Hanger not found on object".to_string())); 
             eprintln!("Docking to nonexistent hanger");
             return;
         }
@@ -61,32 +75,32 @@ fn handle_dock(players: &Query<(&PlayerController, &Ship, &Transform)>, hangers:
     // CHECK IF CAN DOCK
 
     // CHECK IF IN RANGE
-    if !(h_transform.pos.metric_distance(&p_transform.pos) < hanger.docking_range_m) {
-        ein.send(EInfo::Error(player_name.clone(), "You are not in range to dock".to_string())); 
+    if !p.index.in_range(docking_ent, station_ent, hanger.docking_range_m) {
+        p.ein.send(EInfo::Error(player_name.clone(), "You are not in range to dock".to_string())); 
         eprintln!("Player out of range to dock");
         return;
     }    
 
-    db.db.hanger_dock(player_name, hanger.hanger_uid.clone(), p_ship.clone());
-    db.db.account_change_location(player_name, station.clone());
-    eev.send(EEvent::Dock(player_name.clone(), station.clone()));
-    ein.send(EInfo::UpdateInventoryId(player_name.clone(), hanger.hanger_uid.clone()));
-    ein.send(EInfo::UpdateInventoryHanger(player_name.clone(), hanger.hanger_uid.clone()));
-    commands.entity(docking_ent).despawn();
+    p.db.db.hanger_dock(player_name, hanger.hanger_uid.clone(), p_ship.clone());
+    p.db.db.account_change_location(player_name, station.clone());
+    p.eev.send(EEvent::Dock(player_name.clone(), station.clone()));
+    p.ein.send(EInfo::UpdateInventoryId(player_name.clone(), hanger.hanger_uid.clone()));
+    p.ein.send(EInfo::UpdateInventoryHanger(player_name.clone(), hanger.hanger_uid.clone()));
+    p.commands.entity(docking_ent).despawn();
 }
 
-fn handle_undock(hangers: &Query<(&Hanger, &Transform)>, ptm: &Res<PathToEntityMap>, hanger_path: &ObjPath, db: &Res<DatabaseResource>, player_name: &String, commands: &mut Commands, eev: &mut EventWriter<EEvent>, ein: &mut EventWriter<EInfo>) {
-    let loc = match db.db.account_get_location(player_name) {
+fn handle_undock(p: &mut DockParams, hanger_path: &ObjPath, player_name: &String) {
+    let loc = match p.db.db.account_get_location(player_name) {
         Some(loc) => loc,
         None => { eprintln!("Player has no location when trying undock"); return; }
     };
 
     if loc != *hanger_path {
         eprintln!("Player trying to undock from hanger they are not in");
-        ein.send(EInfo::Error(player_name.clone(), String::from("You cannot undock from a station you are not in")));
+        p.ein.send(EInfo::Error(player_name.clone(), String::from("You cannot undock from a station you are not in")));
     }
 
-    let hanger_ent = match ptm.get(hanger_path) {
+    let hanger_ent = match p.ptm.get(hanger_path) {
         Some(h) => h,
         None => {
             eprintln!("Player undocking from nonexistent station");
@@ -94,7 +108,7 @@ fn handle_undock(hangers: &Query<(&Hanger, &Transform)>, ptm: &Res<PathToEntityM
         }
     };
 
-    let (hanger, h_transform) = match hangers.get(hanger_ent) {
+    let (hanger, h_transform) = match p.hangers.get(hanger_ent) {
         Ok(h) => h,
         Err(_) => {
             eprintln!("HANGER NO LONGER EXISTS");
@@ -103,11 +117,11 @@ fn handle_undock(hangers: &Query<(&Hanger, &Transform)>, ptm: &Res<PathToEntityM
         }
     };
 
-    let ship = db.db.hanger_undock(player_name, hanger.hanger_uid.clone());
+    let ship = p.db.db.hanger_undock(player_name, hanger.hanger_uid.clone());
 
     match ship {
         None => {
-            ein.send(EInfo::Error(player_name.clone(), "You do not have an active ship".to_string())); 
+            p.ein.send(EInfo::Error(player_name.clone(), "You do not have an active ship".to_string())); 
             eprintln!("Player has no active ship");
             return;
         },
@@ -115,12 +129,12 @@ fn handle_undock(hangers: &Query<(&Hanger, &Transform)>, ptm: &Res<PathToEntityM
             let mut t = h_transform.clone();
             t.pos += hanger.undock_offset;
             let ship_name = format!("{}:{}", player_name, s.ship_name);
-            let new_ship = BPlayerShip::new(player_name, t, s, &hanger_path.sys, &ship_name);
-            db.db.account_change_location(player_name, new_ship.game_obj.path.clone());
-            eev.send(EEvent::Undock(player_name.clone(), new_ship.game_obj.path.clone()));
+            let new_ship = BPlayerShip::new(player_name, t, s, &hanger_path.sys, &ship_name, &p.db.db);
+            p.db.db.account_change_location(player_name, new_ship.game_obj.path.clone());
+            p.eev.send(EEvent::Undock(player_name.clone(), new_ship.game_obj.path.clone()));
             /* THIS WILL NOT WORK SINCE THE SHIP WILL SPAWN AFTER THE REQUEST FOR ITS INVENTORY IS PROCESSED */
             // ein.send(EInfo::UpdateInventoryShip(player_name.clone(), new_ship.game_obj.path.clone()));
-            commands.spawn(new_ship);
+            p.commands.spawn(new_ship);
         }
     }
 
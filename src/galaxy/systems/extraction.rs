@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+
+use crate::galaxy::bundles::structures::BExtractor;
+use crate::galaxy::components::*;
+use crate::galaxy::events::EInfo;
+use crate::galaxy::resources::{database_resource::DatabaseResource, delta_time::DeltaTime, network_handler::NetworkHandler, path_to_entity::PathToEntityMap, spatial_index::SpatialIndex};
+use crate::inventory::{Mapping, Stack};
+use crate::network::messages::incoming::NetIncomingMessage;
+use crate::shared::{ObjPath, ObjectType};
+
+const EXTRACTOR_CYCLE_S: f64 = 600.0;
+
+// how far from the surface of a body a ship can be and still anchor a structure on it
+const DEPLOY_RANGE_M: f64 = 150_000.0;
+
+/// everything deploying and collecting needs, grouped so the helpers do not take a dozen arguments
+#[derive(SystemParam)]
+pub struct StructureParams<'w, 's> {
+    ships: Query<'w, 's, (&'static PlayerController, &'static mut Ship, &'static Transform)>,
+    extractors: Query<'w, 's, (&'static Extractor, &'static mut Container, &'static GameObject, &'static Transform)>,
+    bodies: Query<'w, 's, (&'static Harvestable, &'static Celestial)>,
+    ptm: Res<'w, PathToEntityMap>,
+    index: Res<'w, SpatialIndex>,
+    db: Res<'w, DatabaseResource>,
+    commands: Commands<'w, 's>,
+    ein: EventWriter<'w, 's, EInfo>,
+}
+
+/// PROCESS DEPLOYING, EMPTYING, RECOVERING, AND VIEWING EXTRACTORS AND PLANET OFFICES
+/// Stage: COMMAND
+pub fn sys_process_extractor_inputs(mut p: StructureParams, net: Res<NetworkHandler>) {
+    // ptm is not updated until bookkeeping, so remember what went down or was packed up this tick
+    let mut deployed = HashSet::new();
+    let mut recovered = HashSet::new();
+    for slot in net.view_incoming() {
+        let player = slot.key();
+        for msg in slot.value().iter() {
+            let res = match msg {
+                NetIncomingMessage::DeployStructure(ship, inv_slot, body) => deploy(&mut p, &mut deployed, player, ship, *inv_slot, body),
+                NetIncomingMessage::CollectStructure(_, structure) | NetIncomingMessage::RecoverStructure(_, structure) if recovered.contains(structure) => Err(String::from("Structure not found")),
+                NetIncomingMessage::CollectStructure(ship, structure) => collect(&mut p, player, ship, structure),
+                NetIncomingMessage::RecoverStructure(ship, structure) => recover(&mut p, player, ship, structure).map(|_| { recovered.insert(structure.clone()); }),
+                NetIncomingMessage::InvRequestGameObject(structure) => {
+                    match p.ptm.get(structure).and_then(|e| p.extractors.get(e).ok()) {
+                        Some((ext, _, _, _)) if ext.owner == *player => { p.ein.send(EInfo::UpdateInventoryGameObject(player.clone(), structure.clone())); Ok(()) },
+                        Some(_) => Err(String::from("You do not own this structure")),
+                        None => Ok(()) // wrecks and jettisoned containers are handled by sys_process_loot_requests
+                    }
+                },
+                _ => Ok(())
+            };
+
+            if let Err(e) = res {
+                p.ein.send(EInfo::Error(player.clone(), e));
+            }
+        }
+    }
+}
+
+fn deploy(p: &mut StructureParams, deployed: &mut HashSet<ObjPath>, player: &String, ship: &ObjPath, inv_slot: u32, body: &ObjPath) -> Result<(), String> {
+    let ship_ent = p.ptm.get(ship).ok_or(String::from("Ship not found"))?;
+    let (pc, mut s, t) = p.ships.get_mut(ship_ent).map_err(|_| String::from("Ship not found"))?;
+    if pc.player_name != *player {
+        eprintln!("{} trying to deploy from other player's ship", player);
+        return Err(String::from("Not your ship"));
+    }
+
+    let structure_type = match body.t {
+        ObjectType::Moon => ObjectType::MoonExtractor,
+        ObjectType::Planet => ObjectType::PlanetOffice,
+        _ => { return Err(String::from("Structures can only be deployed on moons and planets")); }
+    };
+
+    let item_id = s.inventory.get_stack(inv_slot).map(|st| st.id.clone()).ok_or(String::from("Nothing in that cargo slot"))?;
+    match p.db.db.item_table.get(&item_id).map(|i| &i.mapping) {
+        Some(Mapping::Structure(st)) if *st == structure_type => (),
+        _ => { return Err(format!("That item can not be deployed on a {:?}", body.t)); }
+    }
+
+    let body_ent = p.ptm.get(body).ok_or(String::from("Body not found"))?;
+    let (harvestable, celestial) = p.bodies.get(body_ent).map_err(|_| String::from("Nothing can be harvested here"))?;
+    if !p.index.in_range(ship_ent, body_ent, celestial.radius_m + DEPLOY_RANGE_M) {
+        return Err(String::from("Too far away to deploy"));
+    }
+
+    let path = ObjPath::new(&body.sys, structure_type, &body.name);
+    if deployed.contains(&path) || p.ptm.get(&path).is_some() || p.db.db.resources_extractor_exists(&path) {
+        return Err(format!("There is already a {:?} here", structure_type));
+    }
+
+    if harvestable.yields.is_empty() {
+        return Err(String::from("Nothing can be harvested here"));
+    }
+
+    s.inventory.remove_n_from_stack(inv_slot, 1).ok_or(String::from("Nothing in that cargo slot"))?;
+    let bundle = BExtractor::new(player, body, structure_type, t.pos, harvestable.yields.clone(), EXTRACTOR_CYCLE_S);
+    p.db.db.resources_save_extractor(&bundle.game_object, &bundle.extractor, &bundle.container, &bundle.transform);
+    p.commands.spawn(bundle);
+    deployed.insert(path);
+    p.ein.send(EInfo::UpdateInventoryShip(player.clone(), ship.clone()));
+    Ok(())
+}
+
+fn collect(p: &mut StructureParams, player: &String, ship: &ObjPath, structure: &ObjPath) -> Result<(), String> {
+    let ship_ent = p.ptm.get(ship).ok_or(String::from("Ship not found"))?;
+    let (pc, mut s, _) = p.ships.get_mut(ship_ent).map_err(|_| String::from("Ship not found"))?;
+    if pc.player_name != *player {
+        eprintln!("{} trying to collect with other player's ship", player);
+        return Err(String::from("Not your ship"));
+    }
+
+    let ext_ent = p.ptm.get(structure).ok_or(String::from("Structure not found"))?;
+    let (ext, mut container, go, t) = p.extractors.get_mut(ext_ent).map_err(|_| String::from("Structure not found"))?;
+    if ext.owner != *player {
+        return Err(String::from("You do not own this structure"));
+    }
+
+    if !p.index.in_range(ship_ent, ext_ent, container.access_dist) {
+        return Err(String::from("Too far away to collect"));
+    }
+
+    let mut full = false;
+    for stack in container.inv.take_all() {
+        if let Some(leftover) = s.inventory.add_stack(&p.db.db.item_table, stack, None) {
+            full = true;
+            if let Some(dead) = container.inv.add_stack(&p.db.db.item_table, leftover, None) {
+                eprintln!("WARNING: Could not return items to extractor, they have been annihilated: {:?}", dead);
+            }
+        }
+    }
+
+    p.db.db.resources_save_extractor(go, ext, &container, t);
+    p.ein.send(EInfo::UpdateInventoryShip(player.clone(), ship.clone()));
+    p.ein.send(EInfo::UpdateInventoryGameObject(player.clone(), structure.clone()));
+    if full {
+        p.ein.send(EInfo::Error(player.clone(), String::from("Cargo hold is full")));
+    }
+    Ok(())
+}
+
+/// packs a structure back up in to the ship's cargo, only if it and everything in it fits
+fn recover(p: &mut StructureParams, player: &String, ship: &ObjPath, structure: &ObjPath) -> Result<(), String> {
+    let ship_ent = p.ptm.get(ship).ok_or(String::from("Ship not found"))?;
+    let (pc, mut s, _) = p.ships.get_mut(ship_ent).map_err(|_| String::from("Ship not found"))?;
+    if pc.player_name != *player {
+        eprintln!("{} trying to recover with other player's ship", player);
+        return Err(String::from("Not your ship"));
+    }
+
+    let ext_ent = p.ptm.get(structure).ok_or(String::from("Structure not found"))?;
+    let (ext, container, _, _) = p.extractors.get(ext_ent).map_err(|_| String::from("Structure not found"))?;
+    if ext.owner != *player {
+        return Err(String::from("You do not own this structure"));
+    }
+
+    if !p.index.in_range(ship_ent, ext_ent, container.access_dist) {
+        return Err(String::from("Too far away to recover"));
+    }
+
+    let item_id = p.db.db.item_table.values()
+        .filter(|i| matches!(&i.mapping, Mapping::Structure(st) if *st == structure.t))
+        .map(|i| i.id.clone())
+        .min()
+        .ok_or(format!("No item packs up a {:?}", structure.t))?;
+
+    // whole units held back because the container was full come out too, partial units are lost
+    let mut cargo = s.inventory.clone();
+    let stacks = container.inv.clone().take_all().into_iter()
+        .chain(ext.clone().produce(0.0))
+        .chain(std::iter::once(Stack::new(item_id, 1)));
+    for stack in stacks {
+        if cargo.add_stack(&p.db.db.item_table, stack, None).is_some() {
+            return Err(String::from("Not enough room in the cargo hold to recover the structure"));
+        }
+    }
+
+    s.inventory = cargo;
+    p.commands.entity(ext_ent).despawn();
+    p.db.db.resources_remove_extractor(structure);
+    p.ein.send(EInfo::UpdateInventoryShip(player.clone(), ship.clone()));
+    Ok(())
+}
+
+/// RUNS EXTRACTION CYCLES, up to one cycle of output that does not fit is kept back until there is room
+/// Stage: ACTION
+pub fn sys_tick_extractors(mut extractors: Query<(&mut Extractor, &mut Container, &GameObject, &Transform)>, db: Res<DatabaseResource>, dt: Res<DeltaTime>) {
+    for (mut ext, mut container, go, t) in extractors.iter_mut() {
+        ext.cycle_left_s -= dt.dt;
+        if ext.cycle_left_s > 0.0 {
+            continue;
+        }
+
+        ext.cycle_left_s += EXTRACTOR_CYCLE_S;
+        for stack in ext.produce(EXTRACTOR_CYCLE_S) {
+            if let Some(leftover) = container.inv.add_stack(&db.db.item_table, stack, None) {
+                ext.bank(leftover, EXTRACTOR_CYCLE_S);
+            }
+        }
+
+        db.db.resources_save_extractor(go, &ext, &container, t);
+    }
+}
@@ -1,11 +1,15 @@
 use bevy_ecs::prelude::*;
+use chrono::Utc;
 use crate::galaxy::components::*;
 use crate::galaxy::events::EInfo;
 use crate::galaxy::resources::network_handler::NetworkHandler;
-use crate::galaxy::resources::{database_resource::DatabaseResource, path_to_entity::PathToEntityMap};
+use crate::galaxy::resources::{database_resource::DatabaseResource, gameplay_config::GameplayConfig, path_to_entity::PathToEntityMap};
 use crate::network::messages::incoming::NetIncomingMessage;
+use crate::db::{HangerSlot, InsuranceContract};
+use crate::inventory::{InvId, InvSlot, SlotType, Stack};
+use crate::shared::ObjPath;
 
-pub fn hanger_mgmt(hangers: Query<&Hanger>, ptm: Res<PathToEntityMap>, net: Res<NetworkHandler>, db: Res<DatabaseResource>, mut ein: EventWriter<EInfo>) {
+pub fn hanger_mgmt(hangers: Query<&Hanger>, ptm: Res<PathToEntityMap>, net: Res<NetworkHandler>, db: Res<DatabaseResource>, gameplay: Res<GameplayConfig>, mut ein: EventWriter<EInfo>) {
     for slot in net.view_incoming() {
         let player = slot.key();
         let msgs = slot.value();
@@ -29,9 +33,113 @@ pub fn hanger_mgmt(hangers: Query<&Hanger>, ptm: Res<PathToEntityMap>, net: Res<
                 },
                 NetIncomingMessage::HangerRequestShips(hanger_id) => {
                     ein.send(EInfo::UpdateInventoryHanger(player.clone(), hanger_id.clone()));
-                }
+                },
+                NetIncomingMessage::InsureShip(hanger_slot) => {
+                    match insure_ship(&hangers, &ptm, &db, &gameplay, player, *hanger_slot) {
+                        Ok(()) => {
+                            ein.send(EInfo::Insurance(player.clone()));
+                            ein.send(EInfo::UpdateBankAccount(player.clone()));
+                        },
+                        Err(e) => { ein.send(EInfo::Error(player.clone(), e)); }
+                    }
+                },
+                NetIncomingMessage::RequestInsurance => {
+                    ein.send(EInfo::Insurance(player.clone()));
+                },
+                NetIncomingMessage::SetHomeStation(station) => {
+                    match set_home_station(&hangers, &ptm, &db, player, station) {
+                        Ok(()) => { ein.send(EInfo::HomeStation(player.clone())); },
+                        Err(e) => { ein.send(EInfo::Error(player.clone(), e)); }
+                    }
+                },
+                NetIncomingMessage::FitModule(hanger_slot, inv_id, inv_slot, index) => {
+                    match docked_hanger(&hangers, &ptm, &db, player, inv_id).and_then(|id| fit_module(&db, player, id, *hanger_slot, inv_id, *inv_slot, *index as usize)) {
+                        Ok(id) => {
+                            ein.send(EInfo::UpdateInventoryHanger(player.clone(), id));
+                            ein.send(EInfo::UpdateInventoryId(player.clone(), inv_id.clone()));
+                        },
+                        Err(e) => { ein.send(EInfo::Error(player.clone(), e)); }
+                    }
+                },
+                NetIncomingMessage::UnfitModule(hanger_slot, slot_type, index, inv_id) => {
+                    match docked_hanger(&hangers, &ptm, &db, player, inv_id).and_then(|id| unfit_module(&db, player, id, *hanger_slot, *slot_type, *index as usize, inv_id)) {
+                        Ok(id) => {
+                            ein.send(EInfo::UpdateInventoryHanger(player.clone(), id));
+                            ein.send(EInfo::UpdateInventoryId(player.clone(), inv_id.clone()));
+                        },
+                        Err(e) => { ein.send(EInfo::Error(player.clone(), e)); }
+                    }
+                },
                 _ => ()
             }
         }
     }
-}
\ No newline at end of file
+}
+
+fn insure_ship(hangers: &Query<&Hanger>, ptm: &PathToEntityMap, db: &DatabaseResource, gameplay: &GameplayConfig, player: &String, hanger_slot: HangerSlot) -> Result<(), String> {
+    let loc = db.db.account_get_location(player).ok_or(String::from("Location not found"))?;
+    let hanger = ptm.get(&loc).and_then(|e| hangers.get(e).ok()).ok_or(String::from("You must be docked to insure a ship"))?;
+    let ships = db.db.hanger_get_ships(player, hanger.hanger_uid.clone()).ok_or(String::from("No ships in this hanger"))?;
+    let ship = ships.inventory.get(&hanger_slot).ok_or(String::from("No ship in that hanger slot"))?;
+
+    let now = Utc::now().timestamp();
+    if let Some(c) = db.db.insurance_get(player, ship.ship_id, now) {
+        return Err(format!("{} is already insured until {}", ship.ship_name, c.expires_at));
+    }
+
+    let payout = db.db.ship_class_table.get(&ship.ship_class).map(|c| c.insured_value).unwrap_or(0);
+    if payout <= 0 {
+        return Err(String::from("This ship can not be insured"));
+    }
+
+    let premium = gameplay.cfg.insurance_premium_for(payout);
+    db.db.bank_apply_transaction(player, -premium, format!("Insurance premium for {}", ship.ship_name)).ok_or(String::from("Not enough money to pay the premium"))?;
+    db.db.insurance_add(&InsuranceContract::new(player, ship, premium, payout, gameplay.cfg.insurance_duration_days));
+    Ok(())
+}
+
+fn set_home_station(hangers: &Query<&Hanger>, ptm: &PathToEntityMap, db: &DatabaseResource, player: &String, station: &ObjPath) -> Result<(), String> {
+    if db.db.account_get_location(player).as_ref() != Some(station) {
+        return Err(String::from("You must be docked in a station to make it your home"));
+    }
+
+    let hanger = ptm.get(station).and_then(|e| hangers.get(e).ok()).ok_or(String::from("Station not found"))?;
+    db.db.inventory_ensure(player, hanger.hanger_uid.clone());
+    db.db.account_set_home_station(player, station.clone());
+    Ok(())
+}
+
+/// the hanger the player is docked in, fitting only happens in the station the ship is in
+fn docked_hanger(hangers: &Query<&Hanger>, ptm: &PathToEntityMap, db: &DatabaseResource, player: &String, inv_id: &InvId) -> Result<HngId, String> {
+    let loc = db.db.account_get_location(player).ok_or(String::from("Location not found"))?;
+    let hanger = ptm.get(&loc).and_then(|e| hangers.get(e).ok()).ok_or(String::from("You must be docked to change fitting"))?;
+    if hanger.hanger_uid != *inv_id {
+        return Err(String::from("Modules must come from and go to the station you are docked in"));
+    }
+    Ok(hanger.hanger_uid.clone())
+}
+
+fn fit_module(db: &DatabaseResource, player: &String, hanger_id: HngId, hanger_slot: HangerSlot, inv_id: &InvId, inv_slot: InvSlot, index: usize) -> Result<HngId, String> {
+    let module = db.db.inventory_remove_stack(player, inv_id.clone(), inv_slot, Some(1)).ok_or(String::from("No module in that slot"))?;
+    match db.db.hanger_fit_module(player, hanger_id.clone(), hanger_slot, module.id.clone(), index) {
+        Ok(replaced) => {
+            if let Some(r) = replaced {
+                db.db.inventory_insert_stack_free_slot_ignore_capacity(player, inv_id.clone(), Stack::new(r, 1));
+            }
+            Ok(hanger_id)
+        },
+        Err(e) => {
+            // put the module back where it came from
+            if let Some(s) = db.db.inventory_insert_stack(player, inv_id.clone(), module, Some(inv_slot)) {
+                db.db.inventory_insert_stack_free_slot_ignore_capacity(player, inv_id.clone(), s);
+            }
+            Err(e)
+        }
+    }
+}
+
+fn unfit_module(db: &DatabaseResource, player: &String, hanger_id: HngId, hanger_slot: HangerSlot, slot_type: SlotType, index: usize, inv_id: &InvId) -> Result<HngId, String> {
+    let module = db.db.hanger_unfit_module(player, hanger_id.clone(), hanger_slot, slot_type, index)?;
+    db.db.inventory_insert_stack_free_slot_ignore_capacity(player, inv_id.clone(), Stack::new(module, 1));
+    Ok(hanger_id)
+}
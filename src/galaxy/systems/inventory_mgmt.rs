@@ -1,21 +1,34 @@
 use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+use crate::galaxy::bundles::structures::BJetCan;
 use crate::galaxy::components::*;
 use crate::galaxy::events::EInfo;
 use crate::galaxy::resources::network_handler::NetworkHandler;
-use crate::galaxy::resources::{database_resource::DatabaseResource, path_to_entity::PathToEntityMap};
+use crate::galaxy::resources::{database_resource::DatabaseResource, delta_time::DeltaTime, gameplay_config::GameplayConfig, path_to_entity::PathToEntityMap, spatial_index::SpatialIndex};
+use crate::inventory::InvSlot;
 use crate::network::messages::incoming::NetIncomingMessage;
+use crate::shared::ObjPath;
 
-pub fn sys_manage_inventory_transfers(mut ships: Query<(&mut Ship, &PlayerController, &Transform)>, mut containers: Query<(&mut Container, &Transform)>, hangers: Query<&Hanger>, net: Res<NetworkHandler>, ptm: Res<PathToEntityMap>, db: Res<DatabaseResource>, mut ein: EventWriter<EInfo>) {
+/// Inventories that can be reached in space, and what is needed to check they are close enough to each other
+#[derive(SystemParam)]
+pub struct SpaceInventories<'w, 's> {
+    ships: Query<'w, 's, (&'static mut Ship, &'static PlayerController, &'static Transform)>,
+    containers: Query<'w, 's, (&'static mut Container, &'static Transform)>,
+    ptm: Res<'w, PathToEntityMap>,
+    index: Res<'w, SpatialIndex>,
+}
+
+pub fn sys_manage_inventory_transfers(mut space: SpaceInventories, hangers: Query<&Hanger>, net: Res<NetworkHandler>, db: Res<DatabaseResource>, mut ein: EventWriter<EInfo>) {
     for slot in net.view_incoming() {
         let player = slot.key();
         let msgs = slot.value();
         for msg in msgs.iter() {
             match msg {
-                NetIncomingMessage::InvSpaceToSpace(_, _, _, _, _) => space_to_space(&mut ships, &mut containers, &ptm, &db, &mut ein, msg, player),
-                NetIncomingMessage::InvHangerShipToHangerShip(_, _, _, _, _) => inv_ship_to_ship(&hangers,&ptm, &db, &mut ein, msg, player),
-                NetIncomingMessage::InvHangerShipToStation(_, _, _, _, _) => inv_ship_to_inv(&hangers, &ptm, &db, &mut ein, msg, player),
-                NetIncomingMessage::InvStationToShip(_, _, _, _, _) => inv_inv_to_ship(&hangers, &ptm, &db, &mut ein, msg, player),
-                NetIncomingMessage::InvStationToStation(_, _, _, _, _) => inv_to_inv(&hangers, &ptm, &db, &mut ein, msg, player),
+                NetIncomingMessage::InvSpaceToSpace(_, _, _, _, _) => space_to_space(&mut space, &db, &mut ein, msg, player),
+                NetIncomingMessage::InvHangerShipToHangerShip(_, _, _, _, _) => inv_ship_to_ship(&hangers, &space.ptm, &db, &mut ein, msg, player),
+                NetIncomingMessage::InvHangerShipToStation(_, _, _, _, _) => inv_ship_to_inv(&hangers, &space.ptm, &db, &mut ein, msg, player),
+                NetIncomingMessage::InvStationToShip(_, _, _, _, _) => inv_inv_to_ship(&hangers, &space.ptm, &db, &mut ein, msg, player),
+                NetIncomingMessage::InvStationToStation(_, _, _, _, _) => inv_to_inv(&hangers, &space.ptm, &db, &mut ein, msg, player),
                 _ => ()
             }
         }
@@ -23,7 +36,8 @@ pub fn sys_manage_inventory_transfers(mut ships: Query<(&mut Ship, &PlayerContro
 }
 
 // Super overly verbose and full of checks here because we want to catch duplication and annihilation bugs really badly
-fn space_to_space(ships: &mut Query<(&mut Ship, &PlayerController, &Transform)>, containers: &mut Query<(&mut Container, &Transform)>, ptm: &Res<PathToEntityMap>, db: &Res<DatabaseResource>, ein: &mut EventWriter<EInfo>, msg: &NetIncomingMessage, player: &String) {
+fn space_to_space(space: &mut SpaceInventories, db: &Res<DatabaseResource>, ein: &mut EventWriter<EInfo>, msg: &NetIncomingMessage, player: &String) {
+    let SpaceInventories { ships, containers, ptm, index } = space;
     if let NetIncomingMessage::InvSpaceToSpace(src_path, src_slot, count, dst_path, dst_slot) = msg {
         let src_ent = match ptm.get(src_path) {
             None => { eprintln!("Source inventory does not exist"); return; },
@@ -42,19 +56,19 @@ fn space_to_space(ships: &mut Query<(&mut Ship, &PlayerController, &Transform)>,
         
         // get the position and stack from the source
         let res_stack = match src_path.t {
-            crate::shared::ObjectType::Container | crate::shared::ObjectType::Wreck => containers.get_mut(src_ent).and_then(|(mut i, t)| Ok((t.pos, i.inv.remove_n_from_stack(*src_slot, *count), i.access_dist))),
-            crate::shared::ObjectType::PlayerShip => ships.get_mut(src_ent).and_then(|(mut s, pc, t)| {
+            crate::shared::ObjectType::Container | crate::shared::ObjectType::Wreck => containers.get_mut(src_ent).and_then(|(mut i, _t)| Ok((i.inv.remove_n_from_stack(*src_slot, *count), i.access_dist))),
+            crate::shared::ObjectType::PlayerShip => ships.get_mut(src_ent).and_then(|(mut s, pc, _t)| {
                 if pc.player_name != *player { eprintln!("{} trying to control other player's inventory", player); return Err(bevy_ecs::query::QueryEntityError::NoSuchEntity(src_ent)); }
-                Ok((t.pos, s.inventory.remove_n_from_stack(*src_slot, *count), 0.0))
+                Ok((s.inventory.remove_n_from_stack(*src_slot, *count), 0.0))
             }),
             _ => { eprintln!("Object does not have an inventory"); return; }
         };
 
-        //unwrap the position and stack
-        let (src_pos, stack, src_access_dist) = match res_stack {
+        //unwrap the stack
+        let (stack, src_access_dist) = match res_stack {
             Err(_) => { eprintln!("Unable to get source component for s2s transfer"); return; },
-            Ok((_, None, _)) => { eprintln!("Source stack could not be accessed"); return; },
-            Ok((t, Some(s), dist)) => (t, s, dist)
+            Ok((None, _)) => { eprintln!("Source stack could not be accessed"); return; },
+            Ok((Some(s), dist)) => (s, dist)
         };
 
         //save this for if we need to return the items from the stack back to the original place
@@ -62,20 +76,18 @@ fn space_to_space(ships: &mut Query<(&mut Ship, &PlayerController, &Transform)>,
 
         // try placing the stack in the dest inventory, anything that wasn't able to be placed will be returned in an Ok(Some(stack)), Ok(None) means everything was placed, Err(_) means that the entity couldn't be found
         let result = match dst_path.t {
-            crate::shared::ObjectType::Container | crate::shared::ObjectType::Wreck => containers.get_mut(dst_ent).and_then(|(mut i, t)|{
+            crate::shared::ObjectType::Container | crate::shared::ObjectType::Wreck => containers.get_mut(dst_ent).and_then(|(mut i, _t)|{
                 // check the distance
-                let dist = t.pos.metric_distance(&src_pos);
-                if dist > i.access_dist { return Ok(Some(stack)); } //this will prompt the system to try and put back the stack it took
+                if !index.in_range(src_ent, dst_ent, i.access_dist) { return Ok(Some(stack)); } //this will prompt the system to try and put back the stack it took
                 ein.send(EInfo::UpdateInventoryShip(player.clone(), src_path.clone()));
                 match i.inv.add_stack(&db.db.item_table, stack, Some(*dst_slot)) {
                     None => Ok(None),
                     Some(s) => Ok(Some(s))
                 }
             }),
-            crate::shared::ObjectType::PlayerShip => ships.get_mut(dst_ent).and_then(|(mut i, pc, t)|{
+            crate::shared::ObjectType::PlayerShip => ships.get_mut(dst_ent).and_then(|(mut i, pc, _t)|{
                 if pc.player_name != *player { eprintln!("{} trying to control other player's inventory", player); return Ok(Some(stack)); }
-                let dist = t.pos.metric_distance(&src_pos);
-                if dist > src_access_dist { return Ok(Some(stack)); } //this will prompt the system to try and put back the stack it took
+                if !index.in_range(src_ent, dst_ent, src_access_dist) { return Ok(Some(stack)); } //this will prompt the system to try and put back the stack it took
                 ein.send(EInfo::UpdateInventoryShip(player.clone(), dst_path.clone()));
                 match i.inventory.add_stack(&db.db.item_table, stack, Some(*dst_slot)) {
                     None => Ok(None),
@@ -261,4 +273,95 @@ pub fn sys_inventory_service_inventory_requests(hangers: Query<(&Hanger, &GameOb
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[derive(SystemParam)]
+pub struct JettisonParams<'w, 's> {
+    ships: Query<'w, 's, (&'static PlayerController, &'static mut Ship, &'static Transform)>,
+    ptm: Res<'w, PathToEntityMap>,
+    db: Res<'w, DatabaseResource>,
+    gameplay: Res<'w, GameplayConfig>,
+    commands: Commands<'w, 's>,
+}
+
+/// PROCESS JETTISONING CARGO IN TO SPACE
+/// Stage: COMMAND
+pub fn sys_process_jettison(mut p: JettisonParams, net: Res<NetworkHandler>, mut ein: EventWriter<EInfo>) {
+    for slot in net.view_incoming() {
+        let player = slot.key();
+        for msg in slot.value().iter() {
+            if let NetIncomingMessage::Jettison(ship, inv_slot, count) = msg {
+                match jettison(&mut p, player, ship, *inv_slot, *count) {
+                    Ok(()) => ein.send(EInfo::UpdateInventoryShip(player.clone(), ship.clone())),
+                    Err(e) => ein.send(EInfo::Error(player.clone(), e))
+                }
+            }
+        }
+    }
+}
+
+fn jettison(p: &mut JettisonParams, player: &String, ship: &ObjPath, inv_slot: InvSlot, count: u32) -> Result<(), String> {
+    let JettisonParams { ships, ptm, db, gameplay, commands } = p;
+    let (pc, mut s, t) = ptm.get(ship).and_then(|e| ships.get_mut(e).ok()).ok_or(String::from("Ship not found"))?;
+    if pc.player_name != *player {
+        eprintln!("{} trying to jettison from other player's ship", player);
+        return Err(String::from("Not your ship"));
+    }
+
+    let stack = s.inventory.remove_n_from_stack(inv_slot, count).ok_or(String::from("Not enough items in that cargo slot"))?;
+    let mut can = BJetCan::new(db.db.object_new_id(), player, ship, t, &gameplay.cfg);
+    if let Some(extra) = can.container.inv.add_stack(&db.db.item_table, stack, None) {
+        if let Some(dead) = s.inventory.add_stack(&db.db.item_table, extra, Some(inv_slot)) {
+            eprintln!("WARNING: Could not return jettison extras to {}'s ship, they have been annihilated: {:?}", player, dead);
+        }
+    }
+
+    if !can.container.inv.is_empty() {
+        commands.spawn(can);
+    }
+    Ok(())
+}
+
+type LootContainers<'w, 's> = Query<'w, 's, &'static Container, Or<(With<Wreck>, With<JetCan>)>>;
+
+/// LETS PLAYERS LOOK IN TO WRECKS AND JETTISONED CONTAINERS, anyone can but their ship has to be in the same system and close by
+/// Stage: COMMAND
+pub fn sys_process_loot_requests(ships: Query<(Entity, &PlayerController), With<Ship>>, loot: LootContainers, ptm: Res<PathToEntityMap>, index: Res<SpatialIndex>, net: Res<NetworkHandler>, mut ein: EventWriter<EInfo>) {
+    for slot in net.view_incoming() {
+        let player = slot.key();
+        for msg in slot.value().iter() {
+            if let NetIncomingMessage::InvRequestGameObject(path) = msg {
+                // extractors and other structures are looked after by their own systems
+                let (ent, container) = match ptm.get(path).and_then(|e| loot.get(e).ok().map(|c| (e, c))) {
+                    Some(l) => l,
+                    None => continue
+                };
+                if let Err(e) = view_loot(&ships, &index, player, ent, container) {
+                    ein.send(EInfo::Error(player.clone(), e));
+                    continue;
+                }
+                ein.send(EInfo::UpdateInventoryGameObject(player.clone(), path.clone()));
+            }
+        }
+    }
+}
+
+fn view_loot(ships: &Query<(Entity, &PlayerController), With<Ship>>, index: &SpatialIndex, player: &String, loot: Entity, container: &Container) -> Result<(), String> {
+    let (ship_ent, _) = ships.iter().find(|(_, pc)| pc.player_name == *player).ok_or(String::from("You must be in space to look in to that"))?;
+    // in_range is false for anything in another system
+    if !index.in_range(ship_ent, loot, container.access_dist) {
+        return Err(String::from("Too far away to look in to that"));
+    }
+    Ok(())
+}
+
+/// REMOVES JETTISONED CONTAINERS THAT HAVE BEEN EMPTIED OR TIMED OUT
+/// Stage: DEATH
+pub fn sys_despawn_jet_cans(mut cans: Query<(Entity, &mut JetCan, &Container)>, dt: Res<DeltaTime>, mut commands: Commands) {
+    for (e, mut can, container) in cans.iter_mut() {
+        can.despawn_in_s -= dt.dt;
+        if can.despawn_in_s <= 0.0 || container.inv.is_empty() {
+            commands.entity(e).despawn();
+        }
+    }
+}
@@ -1,10 +1,20 @@
 use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
 use nalgebra::{Vector3};
 use rand::Rng;
-use crate::{galaxy::{components::*, resources::{network_handler::NetworkHandler, path_to_entity::PathToEntityMap, database_resource::DatabaseResource}, events::{EInfo, EEvent}}, network::messages::incoming::NetIncomingMessage, shared::ObjPath};
+use crate::{galaxy::{components::*, resources::{network_handler::NetworkHandler, path_to_entity::PathToEntityMap, database_resource::DatabaseResource, spatial_index::SpatialIndex}, events::{EInfo, EEvent}}, network::messages::incoming::NetIncomingMessage, shared::ObjPath};
 
-pub fn sys_process_jump_inputs(mut players: Query<(&PlayerController, &mut Transform, &mut GameObject, &mut Navigation)>, gates: Query<(&Gate, &Transform, &GameObject), Without<PlayerController>>, ptm: Res<PathToEntityMap>, n: Res<NetworkHandler>, db: Res<DatabaseResource>, mut eev: EventWriter<EEvent>, mut ein: EventWriter<EInfo>) {
-    let mut rng = rand::thread_rng();
+/// Everything needed to move a ship through a gate
+#[derive(SystemParam)]
+pub struct GateParams<'w, 's> {
+    pub gates: Query<'w, 's, (&'static Gate, &'static Transform, &'static GameObject), Without<PlayerController>>,
+    pub ptm: Res<'w, PathToEntityMap>,
+    pub index: Res<'w, SpatialIndex>,
+    pub db: Res<'w, DatabaseResource>,
+    pub eev: EventWriter<'w, 's, EEvent>,
+}
+
+pub fn sys_process_jump_inputs(mut players: Query<(&PlayerController, &mut Transform, &mut GameObject, &mut Navigation)>, mut gp: GateParams, n: Res<NetworkHandler>, mut ein: EventWriter<EInfo>) {
     for player in n.view_incoming() {
         let player_name = player.key();
         for msg in player.value() {
@@ -15,7 +25,7 @@ pub fn sys_process_jump_inputs(mut players: Query<(&PlayerController, &mut Trans
                         continue;
                     }
 
-                    let ship_ent = match ptm.get(ship_path) {
+                    let ship_ent = match gp.ptm.get(ship_path) {
                         None => {
                             eprintln!("Trying to jump with ship not in table");
                             continue;
@@ -29,7 +39,7 @@ pub fn sys_process_jump_inputs(mut players: Query<(&PlayerController, &mut Trans
                         continue;
                     }
 
-                    let gate_ent = match ptm.get(gate_path) {
+                    let gate_ent = match gp.ptm.get(gate_path) {
                         None => {
                             ein.send(EInfo::Error(player_name.clone(), String::from("Gate not found")));
                             eprintln!("Jumping on nonexistent gate");
@@ -38,37 +48,9 @@ pub fn sys_process_jump_inputs(mut players: Query<(&PlayerController, &mut Trans
                         Some(g) => g
                     };
 
-                    let (gate, g_transform, g_go) = match gates.get(gate_ent) {
-                        Ok(g) => g,
-                        Err(e) => {
-                            eprintln!("Unable to process jump (ship = {:?}, gate = {:?}): {:?}", ship_path, gate_path, e);
-                            continue;
-                        }
-                    };
-
-                    let dist = pc_transform.pos.metric_distance(&g_transform.pos);
-                    if dist >= gate.jump_range {
-                        ein.send(EInfo::Error(player_name.clone(), format!("Too far away to jump, must be within {} meters", gate.jump_range)));
-                        eprintln!("Too far away to jump");
-                        continue;
+                    if let Err(e) = jump_through_gate(player_name, ship_ent, &mut pc_transform, &mut go, &mut nav, gate_ent, &mut gp) {
+                        ein.send(EInfo::Error(player_name.clone(), e));
                     }
-
-                    let dst_gate_ent = match ptm.get(&gate.dst_gate) {
-                        Some(dst) => dst,
-                        None => {
-                            ein.send(EInfo::Error(player_name.clone(), format!("Gate destination ({}) not found", gate.dst_gate.name)));
-                            eprintln!("Gate is connected to nonexistent dst: {:?} -> {:?}", g_go.path, gate.dst_gate);
-                            continue;
-                        }
-                    };
-
-                    let (_dst_gate, dst_gate_transform, dst_go) = gates.get(dst_gate_ent).expect("Could not get dst gate");
-                    nav.reset();
-                    pc_transform.vel = Vector3::zeros();
-                    pc_transform.pos = dst_gate_transform.pos + (Vector3::<f64>::new(rng.gen::<f64>() - 0.5, rng.gen::<f64>() - 0.5, rng.gen::<f64>() - 0.5).normalize() * 1000.0);
-                    go.path = ObjPath::new(&dst_go.path.sys, go.path.t.clone(), &go.path.name);
-                    eev.send(EEvent::Jump(player_name.clone(), go.path.clone()));
-                    db.db.account_change_location(player_name, go.path.clone());
                 },
                 _ => ()
             }
@@ -76,3 +58,37 @@ pub fn sys_process_jump_inputs(mut players: Query<(&PlayerController, &mut Trans
     }
 }
 
+/// Moves a ship through a gate (if it is in range), shared between manual jumps and the autopilot
+pub fn jump_through_gate(player_name: &String, ship_ent: Entity, pc_transform: &mut Transform, go: &mut GameObject, nav: &mut Navigation, gate_ent: Entity, gp: &mut GateParams) -> Result<(), String> {
+    let GateParams { gates, ptm, index, db, eev } = gp;
+    let (gate, _g_transform, g_go) = match gates.get(gate_ent) {
+        Ok(g) => g,
+        Err(e) => {
+            eprintln!("Unable to process jump (ship = {:?}, gate = {:?}): {:?}", go.path, ptm.get_path_from_entity(gate_ent), e);
+            return Err(String::from("Gate not found"));
+        }
+    };
+
+    if !index.in_range(ship_ent, gate_ent, gate.jump_range) {
+        eprintln!("Too far away to jump");
+        return Err(format!("Too far away to jump, must be within {} meters", gate.jump_range));
+    }
+
+    let dst_gate_ent = match ptm.get(&gate.dst_gate) {
+        Some(dst) => dst,
+        None => {
+            eprintln!("Gate is connected to nonexistent dst: {:?} -> {:?}", g_go.path, gate.dst_gate);
+            return Err(format!("Gate destination ({}) not found", gate.dst_gate.name));
+        }
+    };
+
+    let (_dst_gate, dst_gate_transform, dst_go) = gates.get(dst_gate_ent).expect("Could not get dst gate");
+    let mut rng = rand::thread_rng();
+    nav.reset();
+    pc_transform.vel = Vector3::zeros();
+    pc_transform.pos = dst_gate_transform.pos + (Vector3::<f64>::new(rng.gen::<f64>() - 0.5, rng.gen::<f64>() - 0.5, rng.gen::<f64>() - 0.5).normalize() * 1000.0);
+    go.path = ObjPath::new(&dst_go.path.sys, go.path.t, &go.path.name);
+    eev.send(EEvent::Jump(player_name.clone(), go.path.clone()));
+    db.db.account_change_location(player_name, go.path.clone());
+    Ok(())
+}
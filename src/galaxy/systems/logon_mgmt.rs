@@ -11,7 +11,7 @@ use crate::network::serialization_structs::info::NetOutInfo;
 use crate::shared::ObjectType;
 
 pub fn sys_dispatch_login_info(
-    mut ships: Query<(&mut PlayerController, &Ship, &mut Transform, &mut Navigation, &GameObject, Entity)>,
+    mut ships: Query<(&mut PlayerController, &Ship, &mut Transform, &mut Navigation, &GameObject, &Health, Entity)>,
     hangers: Query<&Hanger>,
     ptm: Res<PathToEntityMap>,
     net: Res<NetworkHandler>,
@@ -27,7 +27,7 @@ pub fn sys_dispatch_login_info(
         for msg in msgs {
             match msg {
                 NetIncomingMessage::Login(player, _) => {
-                    let loc = match db.db.account_get_location(player) {
+                    let mut loc = match db.db.account_get_location(player) {
                         None => {
                             eprintln!("Account has no location set");
                             continue;
@@ -52,16 +52,29 @@ pub fn sys_dispatch_login_info(
                     if loc.t == ObjectType::PlayerShip {
                         match db.db.sis_load_ship(player) {
                             Some(s) => {
-                                command.spawn(BPlayerShip::load_from_db(s.ship, player, s.nav, s.transform, s.game_obj));
+                                command.spawn(BPlayerShip::load_from_db(s.ship, player, s.nav, s.transform, s.game_obj, Some(s.health), &db.db));
                                 /* TODO: the player is not in the PTEM by the time the inventory request executes*/
                                 /* MOVE THIS TO THE BOOK KEEPING SECTION SO IT GETS HANDLED NEXT FRAME */
                                 //ein.send(EInfo::UpdateInventoryShip(player.clone(), loc.clone())); 
                             },
                             None => {
-                                eprintln!("Ship not found in db, TODO: reset player to home");
-                                continue;
+                                // the ship was destroyed while the player was away
+                                match db.db.account_get_home_station(player) {
+                                    Some(home) => {
+                                        eprintln!("Ship not found in db, resetting {} to their home station", player);
+                                        db.db.account_change_location(player, home.clone());
+                                        loc = home;
+                                    },
+                                    None => {
+                                        eprintln!("Ship not found in db and {} has no home station", player);
+                                        continue;
+                                    }
+                                }
                             }
                         };
+                    }
+
+                    if loc.t == ObjectType::PlayerShip {
                         eev.send(EEvent::Undock(player.clone(), loc.clone()));
                     }
                     else {
@@ -88,7 +101,10 @@ pub fn sys_dispatch_login_info(
                     else {
                         eprintln!("ERROR: NO BANK ACCOUNT FOR {}", player);
                     }
-                    /* TODO: HANDLE SKILLS AND BANK ACCOUNT */
+                    ein.send(EInfo::UpdateBookmarks(player.clone()));
+                    db.db.market_refresh_max_orders(player);
+                    ein.send(EInfo::Skills(player.clone()));
+                    ein.send(EInfo::HomeStation(player.clone()));
                 },
                 NetIncomingMessage::Disconnect => {
                     let path = match db.db.account_get_location(player) {
@@ -118,7 +134,7 @@ pub fn sys_dispatch_login_info(
         }
     }
 
-    ships.for_each_mut(|(pc, ship, mut transform, mut nav, go, ent)| {
+    ships.for_each_mut(|(pc, ship, mut transform, mut nav, go, health, ent)| {
         let safe_log_time = match pc.login_state {
             LoginState::LoggedIn => { return; },
             LoginState::LoggedOut(time) => time,
@@ -138,7 +154,7 @@ pub fn sys_dispatch_login_info(
         if elapsed > 10 {
             transform.vel = Vector3::zeros();
             eprintln!("TODO: add safe logout duration as setting");
-            db.db.sis_save_ship(&pc.player_name, ship, &nav, &transform, go);
+            db.db.sis_save_ship(&pc.player_name, ship, &nav, &transform, go, health);
             command.entity(ent).despawn();
         }
     });
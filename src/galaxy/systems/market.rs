@@ -1,14 +1,30 @@
 use std::collections::HashMap;
 
 use bevy_ecs::prelude::*;
+use chrono::Utc;
 
-use crate::db::{ItemStore};
+use crate::db::{ItemStore, MarketFill, check_new_order, check_price, order_value};
+use crate::galaxy::components::{Hanger, GameObject};
 use crate::galaxy::events::EInfo;
-use crate::{galaxy::resources::{database_resource::DatabaseResource, network_handler::NetworkHandler}, inventory::ItemId};
+use crate::{galaxy::resources::{database_resource::DatabaseResource, network_handler::NetworkHandler, gameplay_config::GameplayConfig, galaxy_map::GalaxyMapRes, delta_time::DeltaTime}, inventory::{ItemId, InvId}};
 use crate::network::messages::incoming::NetIncomingMessage;
 
-pub fn sys_process_market(db: Res<DatabaseResource>, net: Res<NetworkHandler>, mut ein: EventWriter<EInfo>) {
+// expired orders are handed back at most this long after they expire
+const ORDER_SWEEP_INTERVAL_S: f64 = 60.0;
+
+/// map from station inventories to the system the station is in
+pub fn station_systems(stations: &Query<(&Hanger, &GameObject)>) -> HashMap<InvId, String> {
+    stations.iter().map(|(h, go)| (h.hanger_uid.clone(), go.path.sys.clone())).collect()
+}
+
+/// the region a player is currently in, docked or in space
+pub fn player_region(db: &DatabaseResource, gmap: &GalaxyMapRes, player: &String) -> Option<String> {
+    db.db.account_get_location(player).and_then(|loc| gmap.graph.get_region(&loc.sys).cloned())
+}
+
+pub fn sys_process_market(db: Res<DatabaseResource>, net: Res<NetworkHandler>, gameplay: Res<GameplayConfig>, gmap: Res<GalaxyMapRes>, stations: Query<(&Hanger, &GameObject)>, mut ein: EventWriter<EInfo>) {
     let mut local_cache = HashMap::new();
+    let station_sys = station_systems(&stations);
     for set in net.view_incoming() {
         let player = set.key();
         let msgs = set.value();
@@ -17,6 +33,9 @@ pub fn sys_process_market(db: Res<DatabaseResource>, net: Res<NetworkHandler>, m
                 NetIncomingMessage::GetStore(item_id) => {
                     ein.send(EInfo::ItemStore(player.clone(), item_id.clone()));
                 },
+                NetIncomingMessage::GetMarketHistory(item_id) => {
+                    ein.send(EInfo::MarketHistory(player.clone(), item_id.clone()));
+                },
                 NetIncomingMessage::CancelBuyOrder(item_id, order_id) => {
                     ensure_item_store_in_cache(&db, &mut local_cache, item_id);
                     let store = local_cache.get_mut(item_id).unwrap(); //guarenteed by above
@@ -39,15 +58,91 @@ pub fn sys_process_market(db: Res<DatabaseResource>, net: Res<NetworkHandler>, m
                         None => { ein.send(EInfo::Error(player.clone(), String::from("Requested sell order no longer exists"))); continue; }
                     };
                     let inv_id = order.location.clone();
-                    ein.send(EInfo::UpdateInventoryId(player.clone(), order.location.clone()));
-                    let stack = store.cancel_sell_order(player, *order_id).expect("Could not cancel sell order that was just validated");
-                    db.db.inventory_insert_stack_free_slot_ignore_capacity(player, inv_id, stack);
+                    let stack = match store.cancel_sell_order(player, *order_id) {
+                        Ok(s) => s,
+                        Err(e) => { ein.send(EInfo::Error(player.clone(), e)); continue; }
+                    };
+                    db.db.inventory_insert_stack_free_slot_ignore_capacity(player, inv_id.clone(), stack);
                     db.db.market_remove_sell_order_from_player(player, *order_id);
+                    ein.send(EInfo::UpdateInventoryId(player.clone(), inv_id));
+                },
+                NetIncomingMessage::ModifySellOrder(item_id, order_id, price_per_item) => {
+                    ensure_item_store_in_cache(&db, &mut local_cache, item_id);
+                    let store = local_cache.get_mut(item_id).unwrap();
+                    let order = match store.get_sell_order(*order_id) {
+                        Some(o) => o,
+                        None => { ein.send(EInfo::Error(player.clone(), String::from("Requested sell order no longer exists"))); continue; }
+                    };
+
+                    // raising the price is charged like placing the difference
+                    let raise = check_price(*price_per_item)
+                        .and_then(|_| order_value(order.stack.count, *price_per_item))
+                        .and_then(|v| order_value(order.stack.count, order.cost_per_item).and_then(|old| v.checked_sub(old).ok_or(String::from("Price too high"))));
+                    let fee = match raise {
+                        Ok(r) => gameplay.cfg.broker_fee_for(r),
+                        Err(e) => { ein.send(EInfo::Error(player.clone(), e)); continue; }
+                    };
+                    if db.db.bank_get_value(player).expect("Could not get player bank value") < fee {
+                        ein.send(EInfo::Error(player.clone(), format!("Insufficent funds to pay the {} broker fee", fee))); continue;
+                    }
+
+                    match store.modify_sell_order(player, *order_id, *price_per_item, Utc::now().timestamp(), gameplay.cfg.order_modify_cooldown_s) {
+                        Ok(()) => {
+                            pay_fee(&db, &gameplay, player, fee, format!("Broker fee for modifying sell order of {}", item_id));
+                            ein.send(EInfo::UpdateBankAccount(player.clone()));
+                            ein.send(EInfo::ItemStore(player.clone(), item_id.clone()));
+                        },
+                        Err(e) => { ein.send(EInfo::Error(player.clone(), e)); }
+                    }
+                },
+                NetIncomingMessage::ModifyBuyOrder(item_id, order_id, price_per_item) => {
+                    ensure_item_store_in_cache(&db, &mut local_cache, item_id);
+                    let store = local_cache.get_mut(item_id).unwrap();
+                    let order = match store.get_buy_order(*order_id) {
+                        Some(o) => o,
+                        None => { ein.send(EInfo::Error(player.clone(), String::from("Requested buy order no longer exists"))); continue; }
+                    };
+
+                    let delta = match check_price(*price_per_item).and_then(|_| order_value(order.count, *price_per_item)) {
+                        Ok(escrow) => match escrow.checked_sub(order.escrow) {
+                            Some(d) => d,
+                            None => { ein.send(EInfo::Error(player.clone(), String::from("Price too high"))); continue; }
+                        },
+                        Err(e) => { ein.send(EInfo::Error(player.clone(), e)); continue; }
+                    };
+                    let fee = gameplay.cfg.broker_fee_for(delta);
+                    if db.db.bank_get_value(player).expect("Could not get player bank value") < delta.saturating_add(fee) {
+                        ein.send(EInfo::Error(player.clone(), String::from("Insufficent funds"))); continue;
+                    }
+
+                    match store.modify_buy_order(player, *order_id, *price_per_item, Utc::now().timestamp(), gameplay.cfg.order_modify_cooldown_s) {
+                        Ok(delta) => {
+                            if delta != 0 {
+                                db.db.bank_apply_transaction(player, -delta, format!("Modified buy order for {}", item_id));
+                            }
+                            pay_fee(&db, &gameplay, player, fee, format!("Broker fee for modifying buy order of {}", item_id));
+                            ein.send(EInfo::UpdateBankAccount(player.clone()));
+                            ein.send(EInfo::ItemStore(player.clone(), item_id.clone()));
+                        },
+                        Err(e) => { ein.send(EInfo::Error(player.clone(), e)); }
+                    }
                 },
                 NetIncomingMessage::FulfillBuyOrder(item_id, order_id, inv_id, inv_slot, count) => {
                     // give money to selling player, take item from selling player, give item to buying player, remove money from buying escrow
                     ensure_item_store_in_cache(&db, &mut local_cache, item_id);
                     let store = local_cache.get_mut(item_id).unwrap();
+                    let order = match store.get_buy_order(*order_id) {
+                        Some(o) => o,
+                        None => { ein.send(EInfo::Error(player.clone(), String::from("Requested buy order no longer exists"))); continue; }
+                    };
+                    let in_range = match (station_sys.get(&order.location), station_sys.get(inv_id)) {
+                        (Some(order_sys), Some(inv_sys)) => order.range.reaches(&gmap.graph, (&order.location, order_sys), (inv_id, inv_sys)),
+                        _ => false
+                    };
+                    if !in_range {
+                        ein.send(EInfo::Error(player.clone(), String::from("Your items are not within range of that buy order"))); continue;
+                    }
+
                     let stack = match db.db.inventory_remove_stack(player, inv_id.clone(), *inv_slot, Some(*count)){
                         Some(s) => s,
                         None => { ein.send(EInfo::Error(player.clone(), String::from("Source inventory slot does not have items"))); continue; }
@@ -55,7 +150,11 @@ pub fn sys_process_market(db: Res<DatabaseResource>, net: Res<NetworkHandler>, m
                     
                     match store.fulfill_buy_order(*order_id, stack.clone(), inv_id.clone(), player.clone()){
                         Ok(t) => {
-                            db.db.bank_apply_transaction(player, t.cost, format!("Sold {}x{} to {}", t.purchased_stack.id, *count, t.purchasing_player)).expect("Could not apply bank transaction");
+                            db.db.statistics_record_fill(&MarketFill::new(*order_id, &t));
+                            if db.db.bank_apply_transaction(player, t.cost, format!("Sold {}x{} to {}", t.purchased_stack.id, *count, t.purchasing_player)).is_none() {
+                                eprintln!("{} could not be paid {} for filling buy order {}", player, t.cost, order_id);
+                            }
+                            pay_fee(&db, &gameplay, player, gameplay.cfg.sales_tax_for(t.cost), format!("Sales tax on {}x{}", t.purchased_stack.id, *count));
                             db.db.inventory_insert_stack_free_slot_ignore_capacity(&t.purchasing_player, t.location.clone(), stack);
                             if t.order_complete {
                                 if let Some(_res) = store.clear_buy_order(*order_id){
@@ -82,16 +181,30 @@ pub fn sys_process_market(db: Res<DatabaseResource>, net: Res<NetworkHandler>, m
                         Some(o) => o
                     };
 
-                    let money = db.db.bank_get_value(player).expect("Player has no bank account");
-                    if money < order.cost_per_item * (*count as i64) {
+                    // anything for sale in the player's region can be bought, it is delivered at the seller's station
+                    let order_region = station_sys.get(&order.location).and_then(|s| gmap.graph.get_region(s));
+                    if order_region.is_none() || order_region.cloned() != player_region(&db, &gmap, player) {
+                        ein.send(EInfo::Error(player.clone(), String::from("That sell order is not in your region"))); continue;
+                    }
+
+                    // the buyer pays up front, if that fails nothing has changed hands yet
+                    let cost = match order_value(*count, order.cost_per_item) {
+                        Ok(c) => c,
+                        Err(e) => { ein.send(EInfo::Error(player.clone(), e)); continue; }
+                    };
+                    let location = order.location.clone();
+                    if db.db.bank_apply_transaction(player, -cost, format!("Purchased {}x{} from {}", item_id, *count, order.player)).is_none() {
                         ein.send(EInfo::Error(player.clone(), String::from("Insufficent funds"))); continue;
                     }
 
-                    match store.fulfill_sell_order(*order_id, *count, order.location.clone(), player.clone()) {
+                    match store.fulfill_sell_order(*order_id, *count, location, player.clone()) {
                         Ok(t) => {
+                            db.db.statistics_record_fill(&MarketFill::new(*order_id, &t));
                             db.db.inventory_insert_stack_free_slot_ignore_capacity(&t.purchasing_player, t.location.clone(), t.purchased_stack);
-                            db.db.bank_apply_transaction(&t.purchasing_player, -t.cost, format!("Purchased {}x{} from {}", item_id, *count, t.selling_player));
-                            db.db.bank_apply_transaction(&t.selling_player, t.cost, format!("Sold {}x{} to {}", item_id, *count, t.purchasing_player));
+                            if db.db.bank_apply_transaction(&t.selling_player, t.cost, format!("Sold {}x{} to {}", item_id, *count, t.purchasing_player)).is_none() {
+                                eprintln!("{} could not be paid {} for filling sell order {}", t.selling_player, t.cost, order_id);
+                            }
+                            pay_fee(&db, &gameplay, &t.selling_player, gameplay.cfg.sales_tax_for(t.cost), format!("Sales tax on {}x{}", item_id, *count));
                             if t.order_complete {
                                 if let Some(_res) = store.clear_sell_order(*order_id){
                                     db.db.market_remove_sell_order_from_player(&t.selling_player, *order_id);
@@ -102,14 +215,19 @@ pub fn sys_process_market(db: Res<DatabaseResource>, net: Res<NetworkHandler>, m
                             ein.send(EInfo::UpdateInventoryId(t.purchasing_player.clone(), t.location.clone()));
                         },
                         Err(e) => {
+                            db.db.bank_apply_transaction(player, cost, format!("Refund for failed purchase of {}x{}", item_id, *count));
                             ein.send(EInfo::Error(player.clone(), e));
                             continue;
                         }
                     };
                 },
-                NetIncomingMessage::PlaceBuyOrder(item_id, location, count, price_per_item) => {
-                    if !db.db.market_can_place_new_order(player) {
-                        ein.send(EInfo::Error(player.clone(), String::from("No remaining order slots")));
+                NetIncomingMessage::PlaceBuyOrder(item_id, location, count, price_per_item, range, duration_days) => {
+                    if !station_sys.contains_key(location) {
+                        ein.send(EInfo::Error(player.clone(), String::from("Buy orders must be placed at a station"))); continue;
+                    }
+
+                    if let Err(e) = check_new_order(*count, *price_per_item).and_then(|_| db.db.market_can_place_new_order(player)) {
+                        ein.send(EInfo::Error(player.clone(), e));
                         continue;
                     }
 
@@ -117,14 +235,20 @@ pub fn sys_process_market(db: Res<DatabaseResource>, net: Res<NetworkHandler>, m
                     let store = local_cache.get_mut(item_id).unwrap();
 
                     let money = db.db.bank_get_value(player).expect("Could not get player bank value");
-                    if money < price_per_item * (*count as i64) {
+                    let escrow = match order_value(*count, *price_per_item) {
+                        Ok(e) => e,
+                        Err(e) => { ein.send(EInfo::Error(player.clone(), e)); continue; }
+                    };
+                    let fee = gameplay.cfg.broker_fee_for(escrow);
+                    if money < escrow.saturating_add(fee) {
                         ein.send(EInfo::Error(player.clone(), String::from("Insufficent funds"))); continue;
                     }
 
-                    match store.add_buy_order(player, item_id.clone(), *count, *price_per_item, location.clone()) {
+                    match store.add_buy_order(player, *count, *price_per_item, location.clone(), *range, *duration_days) {
                         Ok(order_id) => {
                             db.db.market_add_buy_order_to_player(player, item_id, order_id);
-                            db.db.bank_apply_transaction(player, -money, format!("Placed buy order for {}x{}", item_id, *count));
+                            db.db.bank_apply_transaction(player, -escrow, format!("Placed buy order for {}x{}", item_id, *count));
+                            pay_fee(&db, &gameplay, player, fee, format!("Broker fee for buy order of {}x{}", item_id, *count));
                             ein.send(EInfo::UpdateBankAccount(player.clone()));
                         },
                         Err(e) => {
@@ -133,12 +257,20 @@ pub fn sys_process_market(db: Res<DatabaseResource>, net: Res<NetworkHandler>, m
                         }
                     };
                 },
-                NetIncomingMessage::PlaceSellOrder(inventory_id, item_slot, count, price_per_item) => {
-                    if !db.db.market_can_place_new_order(player) {
-                        ein.send(EInfo::Error(player.clone(), String::from("No remaining order slots")));
+                NetIncomingMessage::PlaceSellOrder(inventory_id, item_slot, count, price_per_item, duration_days) => {
+                    if let Err(e) = check_new_order(*count, *price_per_item).and_then(|_| db.db.market_can_place_new_order(player)) {
+                        ein.send(EInfo::Error(player.clone(), e));
                         continue;
                     }
 
+                    let fee = match order_value(*count, *price_per_item) {
+                        Ok(v) => gameplay.cfg.broker_fee_for(v),
+                        Err(e) => { ein.send(EInfo::Error(player.clone(), e)); continue; }
+                    };
+                    if db.db.bank_get_value(player).expect("Could not get player bank value") < fee {
+                        ein.send(EInfo::Error(player.clone(), format!("Insufficent funds to pay the {} broker fee", fee))); continue;
+                    }
+
                     let item_stack = match db.db.inventory_remove_stack(player, inventory_id.clone(), *item_slot, Some(*count)) {
                         None => {
                             ein.send(EInfo::Error(player.clone(), String::from("Invalid item stack attempting to be sold (not enough items in stack?)")));
@@ -149,10 +281,12 @@ pub fn sys_process_market(db: Res<DatabaseResource>, net: Res<NetworkHandler>, m
                     ensure_item_store_in_cache(&db, &mut local_cache, &item_stack.id);
                     let id = item_stack.id.clone();
                     let store = local_cache.get_mut(&item_stack.id).unwrap();
-                    match store.add_sell_order(player, item_stack, *price_per_item, inventory_id.clone()) {
+                    match store.add_sell_order(player, item_stack, *price_per_item, inventory_id.clone(), *duration_days) {
                         Ok(order_id) => {
                             db.db.market_add_sell_order_to_player(player, &id, order_id);
+                            pay_fee(&db, &gameplay, player, fee, format!("Broker fee for sell order of {}x{}", id, *count));
                             ein.send(EInfo::UpdateInventoryId(player.clone(), inventory_id.clone()));
+                            ein.send(EInfo::UpdateBankAccount(player.clone()));
                         },
                         Err(e) => {
                             ein.send(EInfo::Error(player.clone(), e));
@@ -167,6 +301,43 @@ pub fn sys_process_market(db: Res<DatabaseResource>, net: Res<NetworkHandler>, m
     flush_cache(&db, &local_cache);
 }
 
+/// HANDS BACK THE ITEMS AND ESCROW OF EXPIRED ORDERS
+/// Stage: ACTION
+pub fn sys_expire_market_orders(db: Res<DatabaseResource>, dt: Res<DeltaTime>, mut timer: Local<f64>, mut ein: EventWriter<EInfo>) {
+    *timer -= dt.dt;
+    if *timer > 0.0 {
+        return;
+    }
+    *timer = ORDER_SWEEP_INTERVAL_S;
+
+    let (sells, buys) = db.db.market_take_expired_orders(Utc::now().timestamp());
+    for o in sells {
+        let item = o.stack.id.clone();
+        db.db.inventory_insert_stack_free_slot_ignore_capacity(&o.player, o.location.clone(), o.stack);
+        ein.send(EInfo::UpdateInventoryId(o.player.clone(), o.location.clone()));
+        ein.send(EInfo::OrderExpired(o.player, item, o.order_id));
+    }
+    for o in buys {
+        if o.escrow > 0 {
+            db.db.bank_apply_transaction(&o.player, o.escrow, format!("Buy order for {}x{} expired", o.item_id, o.count));
+        }
+        ein.send(EInfo::UpdateBankAccount(o.player.clone()));
+        ein.send(EInfo::OrderExpired(o.player, o.item_id, o.order_id));
+    }
+}
+
+/// moves a fee from the player to the market sink account, both sides show up in the bank history
+fn pay_fee(db: &DatabaseResource, gameplay: &GameplayConfig, player: &String, fee: i64, reason: String) {
+    if fee <= 0 {
+        return;
+    }
+    if db.db.bank_apply_transaction(player, -fee, reason.clone()).is_none() {
+        eprintln!("{} could not pay market fee: {}", player, reason);
+        return;
+    }
+    db.db.bank_apply_transaction(&gameplay.cfg.market_sink_account, fee, format!("{} ({})", reason, player));
+}
+
 fn ensure_item_store_in_cache(db: &DatabaseResource, cache: &mut HashMap<ItemId, ItemStore>, item_id: &ItemId) {
     if !cache.contains_key(item_id) {
         let market = db.db.market_load_item_store(item_id.clone()).expect("Could not load item store in to cache");
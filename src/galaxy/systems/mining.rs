@@ -0,0 +1,180 @@
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+
+use crate::galaxy::bundles::celestials::BAsteroid;
+use crate::galaxy::components::*;
+use crate::galaxy::events::{EInfo, EState};
+use crate::galaxy::resources::{database_resource::DatabaseResource, delta_time::DeltaTime, network_handler::NetworkHandler, path_to_entity::PathToEntityMap, spatial_index::SpatialIndex};
+use crate::inventory::Stack;
+use crate::network::messages::incoming::NetIncomingMessage;
+use crate::shared::{ObjPath, ObjectType};
+
+// time between a belt losing an asteroid and a new one appearing
+const ASTEROID_RESPAWN_S: f64 = 600.0;
+
+/// PROCESS MINING COMMANDS
+/// Stage: COMMAND
+pub fn sys_process_mining_inputs(mut ships: Query<(&PlayerController, &Ship, &Targeting, &mut Miner)>, ptm: Res<PathToEntityMap>, net: Res<NetworkHandler>, mut est: EventWriter<EState>, mut ein: EventWriter<EInfo>) {
+    for slot in net.view_incoming() {
+        let player = slot.key();
+        for msg in slot.value().iter() {
+            let res = match msg {
+                NetIncomingMessage::StartMining(ship, asteroid) => start_mining(&mut ships, &ptm, player, ship, asteroid, &mut est),
+                NetIncomingMessage::StopMining(ship) => {
+                    match ptm.get(ship).and_then(|e| ships.get_mut(e).ok()) {
+                        Some((pc, _, _, mut miner)) if pc.player_name == *player => {
+                            miner.stop();
+                            est.send(EState::Mining(player.clone(), None));
+                            Ok(())
+                        },
+                        _ => Err(String::from("Ship not found"))
+                    }
+                },
+                _ => Ok(())
+            };
+
+            if let Err(e) = res {
+                ein.send(EInfo::Error(player.clone(), e));
+            }
+        }
+    }
+}
+
+fn start_mining(ships: &mut Query<(&PlayerController, &Ship, &Targeting, &mut Miner)>, ptm: &PathToEntityMap, player: &String, ship: &ObjPath, asteroid: &ObjPath, est: &mut EventWriter<EState>) -> Result<(), String> {
+    let (pc, s, targeting, mut miner) = ptm.get(ship).and_then(|e| ships.get_mut(e).ok()).ok_or(String::from("Ship not found"))?;
+    if pc.player_name != *player {
+        eprintln!("{} trying to mine with other player's ship", player);
+        return Err(String::from("Not your ship"));
+    }
+
+    if asteroid.t != ObjectType::Asteroid {
+        return Err(String::from("Only asteroids can be mined"));
+    }
+
+    if !targeting.locked.contains(asteroid) {
+        return Err(String::from("Asteroid must be locked before it can be mined"));
+    }
+
+    if s.stats.mining_yield <= 0.0 {
+        return Err(String::from("Ship has no mining equipment"));
+    }
+
+    miner.start(asteroid.clone(), s.stats.mining_cycle_s);
+    est.send(EState::Mining(player.clone(), Some(asteroid.clone())));
+    Ok(())
+}
+
+/// Asteroids and what is needed to tell if a miner can still reach them
+#[derive(SystemParam)]
+pub struct MiningTargets<'w, 's> {
+    asteroids: Query<'w, 's, &'static mut Asteroid>,
+    ptm: Res<'w, PathToEntityMap>,
+    index: Res<'w, SpatialIndex>,
+}
+
+/// RUNS MINING CYCLES, ore goes in to the ship's cargo at the end of each cycle
+/// Stage: ACTION
+pub fn sys_tick_mining(
+    mut ships: Query<(Entity, &PlayerController, &mut Ship, &Targeting, &mut Miner, &GameObject)>,
+    mut mt: MiningTargets,
+    db: Res<DatabaseResource>,
+    dt: Res<DeltaTime>,
+    mut est: EventWriter<EState>,
+    mut ein: EventWriter<EInfo>,
+){
+    let MiningTargets { asteroids, ptm, index } = &mut mt;
+    for (ent, pc, mut ship, targeting, mut miner, go) in ships.iter_mut() {
+        let target = match &miner.target {
+            Some(t) => t.clone(),
+            None => continue
+        };
+
+        // the lock broke or the asteroid is gone
+        let ast_ent = match ptm.get(&target) {
+            Some(e) if targeting.locked.contains(&target) => e,
+            _ => {
+                miner.stop();
+                est.send(EState::Mining(pc.player_name.clone(), None));
+                continue;
+            }
+        };
+
+        if !index.in_range(ent, ast_ent, ship.stats.mining_range_m) {
+            miner.stop();
+            est.send(EState::Mining(pc.player_name.clone(), None));
+            ein.send(EInfo::Error(pc.player_name.clone(), String::from("Asteroid is out of mining range")));
+            continue;
+        }
+
+        miner.cycle_left_s -= dt.dt;
+        if miner.cycle_left_s > 0.0 {
+            continue;
+        }
+
+        let mut asteroid = match asteroids.get_mut(ast_ent) {
+            Ok(a) => a,
+            Err(_) => { eprintln!("Mining target {:?} is not an asteroid", target); miner.stop(); continue; }
+        };
+
+        let count = (ship.stats.mining_yield as u32).min(asteroid.remaining);
+        let leftover = ship.inventory.add_stack(&db.db.item_table, Stack::new(asteroid.ore.clone(), count), None);
+        let mined = count - leftover.as_ref().map(|s| s.count).unwrap_or(0);
+        asteroid.remaining -= mined;
+
+        if mined > 0 {
+            ein.send(EInfo::UpdateInventoryShip(pc.player_name.clone(), go.path.clone()));
+            est.send(EState::AsteroidRemaining(pc.player_name.clone(), target.clone(), asteroid.remaining));
+        }
+
+        if leftover.is_some() {
+            miner.stop();
+            est.send(EState::Mining(pc.player_name.clone(), None));
+            ein.send(EInfo::Error(pc.player_name.clone(), String::from("Cargo hold is full")));
+        }
+        else if asteroid.remaining == 0 {
+            miner.stop();
+            est.send(EState::Mining(pc.player_name.clone(), None));
+        }
+        else {
+            miner.cycle_left_s += ship.stats.mining_cycle_s;
+        }
+    }
+}
+
+/// REMOVES ASTEROIDS THAT HAVE BEEN MINED OUT
+/// Stage: DEATH
+pub fn sys_despawn_depleted_asteroids(asteroids: Query<(Entity, &Asteroid), Changed<Asteroid>>, mut belts: Query<&mut AsteroidBelt>, ptm: Res<PathToEntityMap>, mut commands: Commands) {
+    for (e, a) in asteroids.iter() {
+        if a.remaining > 0 {
+            continue;
+        }
+
+        commands.entity(e).despawn();
+        match ptm.get(&a.belt).and_then(|b| belts.get_mut(b).ok()) {
+            Some(mut belt) => { belt.live = belt.live.saturating_sub(1); },
+            None => { eprintln!("Depleted asteroid belongs to a belt that does not exist: {:?}", a.belt); }
+        }
+    }
+}
+
+/// REFILLS BELTS ONE ASTEROID AT A TIME
+/// Stage: ACTION
+pub fn sys_respawn_asteroids(mut belts: Query<(&mut AsteroidBelt, &GameObject, &WarpTarget)>, dt: Res<DeltaTime>, mut commands: Commands) {
+    let mut rng = rand::thread_rng();
+    for (mut belt, go, wt) in belts.iter_mut() {
+        if belt.live >= belt.target_count {
+            belt.respawn_timer_s = ASTEROID_RESPAWN_S;
+            continue;
+        }
+
+        belt.respawn_timer_s -= dt.dt;
+        if belt.respawn_timer_s > 0.0 {
+            continue;
+        }
+
+        belt.respawn_timer_s = ASTEROID_RESPAWN_S;
+        if let Some(a) = BAsteroid::new(&mut belt, &go.path, wt.warp_point, &mut rng) {
+            commands.spawn(a);
+        }
+    }
+}
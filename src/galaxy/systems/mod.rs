@@ -1,12 +1,23 @@
 pub mod path_table_bookeeping;
 pub mod star_system_table_bookeeping;
+pub mod spatial_index_bookeeping;
 pub mod removal_hooks;
 pub mod navigation;
 pub mod docking_undocking;
 pub mod jump;
 pub mod sense;
+pub mod targeting;
+pub mod mining;
+pub mod combat;
+pub mod extraction;
+pub mod production;
+pub mod refining;
+pub mod skills;
 pub mod network_msg_generator;
 pub mod logon_mgmt;
 pub mod inventory_mgmt;
 pub mod market;
-pub mod hanger_mgmt;
\ No newline at end of file
+pub mod hanger_mgmt;
+pub mod bookmarks;
+pub mod autopilot;
+pub mod routing;
\ No newline at end of file
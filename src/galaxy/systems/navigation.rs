@@ -3,7 +3,7 @@ use std::{f64::consts::PI};
 use bevy_ecs::prelude::*;
 use nalgebra::{Vector3, UnitQuaternion};
 
-use crate::{galaxy::{components::*, resources::{network_handler::NetworkHandler, path_to_entity::PathToEntityMap, delta_time::DeltaTime}, events::{EInfo}}, network::messages::incoming::NetIncomingMessage, shared::{ObjPath, ObjectType}};
+use crate::{galaxy::{components::*, resources::{network_handler::NetworkHandler, path_to_entity::PathToEntityMap, delta_time::DeltaTime, database_resource::DatabaseResource}, events::{EInfo}}, network::messages::incoming::NetIncomingMessage, shared::{ObjPath, ObjectType}, db::Bookmark};
 
 /// PROCESS NON WARP NAVIGATION MESSAGES
 /// Stage: COMMAND
@@ -12,6 +12,9 @@ pub fn sys_process_navigation_inputs_local(mut players: Query<(&PlayerController
         for msg in entry.value().iter() {
             match msg {
                 NetIncomingMessage::Approach(ship, dst) => update_navigation_local(&mut players, &ptm, ship, dst, &entry.key(), Action::Approach, &mut ein),
+                NetIncomingMessage::Orbit(ship, dst, r) => update_navigation_local(&mut players, &ptm, ship, dst, &entry.key(), Action::Orbit(*r), &mut ein),
+                NetIncomingMessage::KeepAtRange(ship, dst, r) => update_navigation_local(&mut players, &ptm, ship, dst, &entry.key(), Action::KeepAtRange(*r), &mut ein),
+                NetIncomingMessage::AlignTo(ship, dst) => update_navigation_local(&mut players, &ptm, ship, dst, &entry.key(), Action::AlignTo, &mut ein),
                 NetIncomingMessage::MNav(ship, x, y, z, t) => update_manual_navigation(&mut players, &ptm, ship, &entry.key(), *x, *y, *z, *t),
                 _ => ()
                 /* DO NOT PROCESS WARPS HERE */
@@ -102,6 +105,14 @@ fn update_navigation_local(q: &mut Query<(&PlayerController, &mut Navigation, &S
         return;
     }
 
+    match op {
+        Action::Orbit(r) | Action::KeepAtRange(r) if !(r >= 0.0 && r.is_finite()) => {
+            ein.send(EInfo::Error(player.clone(), String::from("Invalid navigation range")));
+            return;
+        },
+        _ => ()
+    };
+
     //println!("{:?} ===> {:?}", ship_path, dst);
     nav.reset_banked();
     nav.cur_action = op;
@@ -112,13 +123,17 @@ fn update_navigation_local(q: &mut Query<(&PlayerController, &mut Navigation, &S
 /// PROCESS WARP NAVIGATION MESSAGES
 /// Stage: COMMAND
 // TODO: CHECK VISIBILITY OF TRANSFORM
-pub fn sys_process_navigation_inputs_warp(mut players: Query<(&PlayerController, &mut Navigation, &Ship)>, warp_targets: Query<&WarpTarget>, transforms: Query<&Transform>, n: ResMut<NetworkHandler>, ptm: Res<PathToEntityMap>, mut ein: EventWriter<EInfo>) {
+pub fn sys_process_navigation_inputs_warp(mut players: Query<(&PlayerController, &mut Navigation, &Ship)>, warp_targets: Query<&WarpTarget>, transforms: Query<&Transform>, n: ResMut<NetworkHandler>, ptm: Res<PathToEntityMap>, db: Res<DatabaseResource>, mut ein: EventWriter<EInfo>) {
     for entry in n.view_incoming().iter() {
         let msgs = entry.value();
         let player = entry.key();
         for msg in msgs.iter() {
             match msg {
                 NetIncomingMessage::WarpTo(ship_path, dst, dist) => update_navigation_warp(&mut players, &warp_targets, &transforms, &ptm, ship_path, &dst, player, *dist, &mut ein),
+                NetIncomingMessage::WarpToBookmark(ship_path, bookmark_id, dist) => match db.db.bookmark_get(player, *bookmark_id) {
+                    Some(bookmark) => update_navigation_warp_bookmark(&mut players, &ptm, ship_path, &bookmark, player, *dist, &mut ein),
+                    None => ein.send(EInfo::Error(player.clone(), String::from("Bookmark not found")))
+                },
                 _ => ()
             }
         }
@@ -189,6 +204,44 @@ fn update_navigation_warp(q: &mut Query<(&PlayerController, &mut Navigation, &Sh
     nav.target = wt;
 }
 
+fn update_navigation_warp_bookmark(q: &mut Query<(&PlayerController, &mut Navigation, &Ship)>, ptm: &Res<PathToEntityMap>, ship_path: &ObjPath, bookmark: &Bookmark, player: &String, dist: f64, ein: &mut EventWriter<EInfo>) {
+    // get ship entity
+    let ship_ent = match ptm.get(ship_path){
+        Some(s) => s,
+        None => { eprintln!("Player requesting action for nonexistent ship"); return; }
+    };
+
+    //get components
+    let (pc, mut nav, _ship) = match q.get_mut(ship_ent) {
+        Ok(x) => x,
+        Err(_) => {
+            eprintln!("Navigation ship not found in space: {}", player);
+            return;
+        }
+    };
+
+    // validate player
+    if pc.player_name != *player {
+        eprintln!("Player sending command for ship they don't own");
+        return;
+    }
+
+    if ship_path.sys != bookmark.system {
+        ein.send(EInfo::Error(player.clone(), String::from("Bookmark is not in this system")));
+        return;
+    }
+
+    if let WarpState::Warping(_) = nav.warp_state {
+        ein.send(EInfo::Error(player.clone(), String::from("You cannot issue navigation commands while warping")));
+        return;
+    }
+
+    nav.reset_banked();
+    nav.cur_action = Action::Warp(dist);
+    nav.warp_state = WarpState::Aligning;
+    nav.target = NavTarget::Point(bookmark.pos);
+}
+
 /// UDPATES THE POSITIONS OF ALL THE NAVIGATION TARGETS, MUST BE RUN BEFORE sys_tick_navigation
 pub fn sys_navigation_update_transform_positions(mut q: Query<(&mut Navigation, &Sensor)>, transforms: Query<&Transform>, ptm: Res<PathToEntityMap>) {
     q.par_for_each_mut(128, |(mut nav, sensor)| {
@@ -263,7 +316,7 @@ fn update_navigation(nav: &mut Navigation, ship: &Ship, transform: &mut Transfor
         Action::Approach => handle_approach(nav, ship, transform, target_pos, target_vel, dt),
         Action::KeepAtRange(r) => handle_keep_at_range(nav, ship, transform, target_pos, target_vel, dt, r),
         Action::None => (),
-        Action::Orbit(r) => handle_orbit(nav, ship, transform, target_pos, dt, r),
+        Action::Orbit(r) => handle_orbit(nav, ship, transform, target_pos, target_vel, dt, r),
     }
 }
 
@@ -446,15 +499,91 @@ fn kill_relative_velocity(nav: &mut Navigation, ship: &Ship, transform: &mut Tra
     }
 }
 
-fn handle_keep_at_range(nav: &mut Navigation, ship: &Ship, transform: &mut Transform, target_pos: Vector3<f64> ,target_vel: Option<Vector3<f64>>, dt: f64, r: f64) {
-    eprintln!("TODO: keep at range");
+fn handle_keep_at_range(_nav: &mut Navigation, ship: &Ship, transform: &mut Transform, target_pos: Vector3<f64> ,target_vel: Option<Vector3<f64>>, dt: f64, r: f64) {
+    let tvel = match target_vel { Some(v) => v, None => Vector3::zeros() };
+    let offset = transform.pos - target_pos;
+    let dist = offset.magnitude();
+    let radial = radial_direction(offset);
+    let err = dist - r;
+
+    if err.abs() < 10.0 && (tvel - transform.vel).magnitude() < 1.0 { //if we are at range and within 1 m/s, just call it close enough
+        transform.vel = tvel;
+        return;
+    }
+
+    // close (or open) the distance along the line to the target, and kill any sideways drift
+    let max_accel = ship.stats.thrust_n / ship.stats.mass_kg;
+    let radial_speed = closing_speed(err, max_accel);
+    let desired_vel = tvel - radial * radial_speed;
+    match_velocity(transform, ship, desired_vel, dt);
 }
 
-fn handle_orbit(nav: &mut Navigation, ship: &Ship, transform: &mut Transform, target_pos: Vector3<f64>, dt: f64, r: f64) {
-    eprintln!("TODO: orbit")
+fn handle_orbit(_nav: &mut Navigation, ship: &Ship, transform: &mut Transform, target_pos: Vector3<f64>, target_vel: Option<Vector3<f64>>, dt: f64, r: f64) {
+    let tvel = match target_vel { Some(v) => v, None => Vector3::zeros() };
+    let rel_vel = transform.vel - tvel;
+    let offset = transform.pos - target_pos;
+    let dist = offset.magnitude();
+    let radial = radial_direction(offset);
+    let err = dist - r;
+
+    // keep orbiting in whatever plane we are already moving in, if we aren't moving sideways pick one
+    let sideways = rel_vel - radial * rel_vel.dot(&radial);
+    let tangent = if sideways.magnitude() > 1.0 {
+        sideways.normalize()
+    }
+    else {
+        let up = if radial.angle(&Vector3::new(0.0, 1.0, 0.0)) < 0.01 {
+            Vector3::new(1.0, 0.0, 0.0)
+        }
+        else {
+            Vector3::new(0.0, 1.0, 0.0)
+        };
+        radial.cross(&up).normalize()
+    };
+
+    // we only use part of our thrust to hold the orbit (v^2 / r = a), the rest is left over for corrections
+    // this also keeps the turn rate of the orbit (v / r) low enough for the ship to follow it
+    let max_accel = ship.stats.thrust_n / ship.stats.mass_kg;
+    let radius = r.max(1.0);
+    let orbit_speed = (ORBIT_ACCEL_FRACTION * max_accel * radius).sqrt().min(ORBIT_ACCEL_FRACTION * ship.stats.ang_vel_rads * radius);
+    let radial_speed = closing_speed(err, max_accel);
+    let desired_vel = tvel + tangent * orbit_speed - radial * radial_speed;
+    match_velocity(transform, ship, desired_vel, dt);
 }
 
 /* UTILITY FUNCTIONS */
+const ORBIT_ACCEL_FRACTION: f64 = 0.5;
+
+/// speed we want to be closing a gap of err meters at, so that we can still stop in time using half our thrust
+fn closing_speed(err: f64, max_accel: f64) -> f64 {
+    err.signum() * (max_accel * err.abs()).sqrt()
+}
+
+/// direction pointing from the target to us, if we are sitting right on top of it just pick one
+fn radial_direction(offset: Vector3<f64>) -> Vector3<f64> {
+    if offset.magnitude() < 0.001 {
+        Vector3::new(0.0, 0.0, 1.0)
+    }
+    else {
+        offset.normalize()
+    }
+}
+
+/// turn towards the velocity change we need and burn once we are pointing the right way
+fn match_velocity(transform: &mut Transform, ship: &Ship, desired_vel: Vector3<f64>, dt: f64) {
+    let dv = desired_vel - transform.vel;
+    if dv.magnitude() < 0.01 {
+        return;
+    }
+
+    align_to_vector(transform, ship, dv, dt);
+    if is_aligned(transform, dv, 0.1) {
+        let max_accel = ship.stats.thrust_n / ship.stats.mass_kg;
+        let cur_accel = (max_accel * dt).min(dv.magnitude());
+        transform.vel += transform.rot.transform_vector(&Vector3::new(0.0, 0.0, cur_accel));
+    }
+}
+
 fn align_to_vector(transform: &mut Transform, ship: &Ship, v: Vector3<f64>, dt: f64) {
     let up = if v.angle(&Vector3::new(0.0, 1.0, 0.0)) < 0.01 {
         Vector3::new(1.0, 0.0, 0.0)
@@ -498,4 +627,4 @@ pub fn sys_tick_transforms(mut t: Query<&mut Transform>, dt: Res<DeltaTime>) {
         let vel = transform.vel;
         transform.pos += vel * dt.dt
     });
-}
\ No newline at end of file
+}
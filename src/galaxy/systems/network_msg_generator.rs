@@ -1,10 +1,15 @@
 use std::collections::HashMap;
 
 use bevy_ecs::prelude::*;
-use crate::{galaxy::{resources::{path_to_entity::PathToEntityMap, network_handler::NetworkHandler, star_system_table::SystemMapTable, database_resource::DatabaseResource}, events::{EEvent, EInfo, EState}}, network::{serialization_structs::{state::{SSystem, SPlayerShip_OTHER, NetOutState, SPlayerShip_OWN}, event::NetOutEvent, info::{NetOutInfo, hanger::SHanger}}, messages::{outgoing::NetOutgoingMessage}}, inventory::Inventory};
+use bevy_ecs::system::SystemParam;
+use chrono::Utc;
+use crate::{galaxy::{resources::{path_to_entity::PathToEntityMap, network_handler::NetworkHandler, star_system_table::SystemMapTable, database_resource::DatabaseResource, galaxy_map::GalaxyMapRes}, events::{EEvent, EInfo, EState}}, network::{serialization_structs::{state::{SSystem, SPlayerShip_OTHER, NetOutState, SPlayerShip_OWN, SAsteroid, SExtractor, SMissile, SWreck, SContainer}, event::NetOutEvent, info::{NetOutInfo, hanger::SHanger, route::SRoute, skills::SSkills}}, messages::{outgoing::NetOutgoingMessage}}, inventory::Inventory};
 
 use super::super::components::*;
 
+// how far back market history goes
+const MARKET_HISTORY_DAYS: i64 = 365;
+
 /* LOGIC IS NOT CHECKED IN HERE FOR THE MOST PART, INSTEAD IF WE GET AN EVENT, WE ASSUME IT IS LEGIT */
 
 pub fn sys_dispatch_static_data(
@@ -48,13 +53,25 @@ pub fn sys_dispatch_static_data(
 
 }
 
+/// Everything other than ships that can show up on a player's sensors
+#[derive(SystemParam)]
+pub struct SensedObjects<'w, 's> {
+    asteroids: Query<'w, 's, (&'static Asteroid, &'static GameObject, &'static Transform)>,
+    extractors: Query<'w, 's, (&'static Extractor, &'static GameObject, &'static Transform)>,
+    missiles: Query<'w, 's, (&'static Missile, &'static GameObject, &'static Transform)>,
+    wrecks: Query<'w, 's, (&'static Wreck, &'static GameObject, &'static Transform)>,
+    jet_cans: Query<'w, 's, (&'static JetCan, &'static GameObject, &'static Transform)>,
+}
+
 pub fn sys_dispatch_other_ships(
     sensor: Query<(&PlayerController, &Sensor)>,
     ships: Query<(&Ship, &PlayerController, &GameObject, &Transform)>,
+    objects: SensedObjects,
     net: Res<NetworkHandler>,
     ptm: Res<PathToEntityMap>,
     mut est: EventReader<EState>,
 ){
+    let SensedObjects { asteroids, extractors, missiles, wrecks, jet_cans } = &objects;
     // sensor.par_for_each(16, |(pc, s)|{
     //     match pc.login_state {
     //         LoginState::LoggedOut(_) => { /* println!("Ship for {} logged out", pc.player_name); */ return; },
@@ -117,6 +134,55 @@ pub fn sys_dispatch_other_ships(
                         };
                         update_map.entry(player).or_insert(vec![]).push(other_ship);
                     }
+                    else if let Ok((a, ago, at)) = asteroids.get(oship_ent) {
+                        let asteroid = SAsteroid {
+                            path: ago.path.clone(),
+                            ore: a.ore.clone(),
+                            remaining: a.remaining,
+                            transform: at.clone(),
+                            vis: vis.clone()
+                        };
+                        net.enqueue_outgoing(player, NetOutgoingMessage::State(NetOutState::Asteroid(asteroid)));
+                    }
+                    else if let Ok((ext, ego, et)) = extractors.get(oship_ent) {
+                        let extractor = SExtractor {
+                            path: ego.path.clone(),
+                            owner: ext.owner.clone(),
+                            body: ext.body.clone(),
+                            transform: et.clone(),
+                            vis: vis.clone()
+                        };
+                        net.enqueue_outgoing(player, NetOutgoingMessage::State(NetOutState::Extractor(extractor)));
+                    }
+                    else if let Ok((m, mgo, mt)) = missiles.get(oship_ent) {
+                        let missile = SMissile {
+                            path: mgo.path.clone(),
+                            owner: m.owner.clone(),
+                            target: m.target.clone(),
+                            transform: mt.clone(),
+                            vis: vis.clone()
+                        };
+                        net.enqueue_outgoing(player, NetOutgoingMessage::State(NetOutState::Missile(missile)));
+                    }
+                    else if let Ok((w, wgo, wt)) = wrecks.get(oship_ent) {
+                        let wreck = SWreck {
+                            path: wgo.path.clone(),
+                            owner: w.owner.clone(),
+                            ship_class: w.ship_class.clone(),
+                            transform: wt.clone(),
+                            vis: vis.clone()
+                        };
+                        net.enqueue_outgoing(player, NetOutgoingMessage::State(NetOutState::Wreck(wreck)));
+                    }
+                    else if let Ok((c, cgo, ct)) = jet_cans.get(oship_ent) {
+                        let container = SContainer {
+                            path: cgo.path.clone(),
+                            owner: c.owner.clone(),
+                            transform: ct.clone(),
+                            vis: vis.clone()
+                        };
+                        net.enqueue_outgoing(player, NetOutgoingMessage::State(NetOutState::Container(container)));
+                    }
                 }
                 else {
                     eprintln!("Sensed ship not found in ptm ({} seeing {:?})", player, ship_path);
@@ -124,7 +190,14 @@ pub fn sys_dispatch_other_ships(
             },
             EState::LostSight(player, ship_path) => {
                 net.enqueue_outgoing(player, NetOutgoingMessage::State(NetOutState::LostSight(ship_path.clone())));
-            }
+            },
+            EState::TargetLocking(player, target, time) => net.enqueue_outgoing(player, NetOutgoingMessage::State(NetOutState::TargetLocking(target.clone(), *time))),
+            EState::TargetLocked(player, target) => net.enqueue_outgoing(player, NetOutgoingMessage::State(NetOutState::TargetLocked(target.clone()))),
+            EState::TargetUnlocked(player, target) => net.enqueue_outgoing(player, NetOutgoingMessage::State(NetOutState::TargetUnlocked(target.clone()))),
+            EState::Mining(player, target) => net.enqueue_outgoing(player, NetOutgoingMessage::State(NetOutState::Mining(target.clone()))),
+            EState::AsteroidRemaining(player, target, left) => net.enqueue_outgoing(player, NetOutgoingMessage::State(NetOutState::AsteroidRemaining(target.clone(), *left))),
+            EState::Weapon(player, slot, target) => net.enqueue_outgoing(player, NetOutgoingMessage::State(NetOutState::Weapon(*slot, target.clone()))),
+            EState::Health(player, obj, health) => net.enqueue_outgoing(player, NetOutgoingMessage::State(NetOutState::Health(obj.clone(), health.clone()))),
         }
     }
 
@@ -206,6 +279,15 @@ pub fn sys_dispatch_ev_dock_undock_jump(
                 net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::Location(ship.clone())));
                 net.enqueue_outgoing(player, NetOutgoingMessage::Event(NetOutEvent::Jump(ship.clone())));   
             },
+            EEvent::AutopilotRoute(player, route) => net.enqueue_outgoing(player, NetOutgoingMessage::Event(NetOutEvent::AutopilotRoute(route.clone()))),
+            EEvent::AutopilotArrived(player, sys) => net.enqueue_outgoing(player, NetOutgoingMessage::Event(NetOutEvent::AutopilotArrived(sys.clone()))),
+            EEvent::AutopilotDisengaged(player) => net.enqueue_outgoing(player, NetOutgoingMessage::Event(NetOutEvent::AutopilotDisengaged)),
+            EEvent::WeaponFired(player, shooter, target, damage) => net.enqueue_outgoing(player, NetOutgoingMessage::Event(NetOutEvent::WeaponFired(shooter.clone(), target.clone(), *damage))),
+            EEvent::Destroyed(player, obj) => net.enqueue_outgoing(player, NetOutgoingMessage::Event(NetOutEvent::Destroyed(obj.clone()))),
+            EEvent::Death(player, station) => {
+                net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::Location(station.clone())));
+                net.enqueue_outgoing(player, NetOutgoingMessage::Event(NetOutEvent::Death(station.clone())));
+            },
             _ => ()
         }
     }
@@ -213,12 +295,19 @@ pub fn sys_dispatch_ev_dock_undock_jump(
 
 
 pub fn sys_dispatch_inv_bank_updates(
+    stations: Query<(&Hanger, &GameObject)>,
     mut inf: EventReader<EInfo>,
     net: Res<NetworkHandler>,
-    db: Res<DatabaseResource>
+    db: Res<DatabaseResource>,
+    gmap: Res<GalaxyMapRes>
 ){
+    // stations do not move between systems, so build this at most once per run and only if a store is sent
+    let mut station_sys = None;
     for e in inf.iter(){
         match e {
+            EInfo::Error(player, msg) => {
+                net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::Error(msg.clone())));
+            },
             EInfo::UpdateInventoryHanger(player, hanger_id) => {
                 let hanger = db.db.hanger_get_ships(player, hanger_id.clone());
                 if let Some(h) = hanger {
@@ -239,13 +328,47 @@ pub fn sys_dispatch_inv_bank_updates(
                 net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::Bank(val)));
             },
             EInfo::ItemStore(player, item_id) => {
-                if let Some(store) = db.db.market_load_item_store(item_id.clone()) {
+                if let Some(mut store) = db.db.market_load_item_store(item_id.clone()) {
+                    // players only see the market of the region they are in
+                    let region = super::market::player_region(&db, &gmap, player);
+                    let station_sys = station_sys.get_or_insert_with(|| super::market::station_systems(&stations));
+                    store.retain_orders(|loc| region.is_some() && station_sys.get(loc).and_then(|s| gmap.graph.get_region(s)).cloned() == region);
                     net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::Store(store)));
                 }
             },
+            EInfo::MarketHistory(player, item_id) => {
+                let since = Utc::now().timestamp() - MARKET_HISTORY_DAYS * 86_400;
+                net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::MarketHistory(item_id.clone(), db.db.statistics_market_history(item_id, since))));
+            },
             EInfo::UpdateInventoryList(player, inv_list) => {
                 net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::InvList(inv_list.clone())));
             },
+            EInfo::Route(player, start, end, route) => {
+                net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::Route(SRoute::new(start, end, route))));
+            },
+            EInfo::UpdateBookmarks(player) => {
+                let bookmarks = db.db.bookmark_get_all(player).map(|bms| bms.list()).unwrap_or(vec![]);
+                net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::Bookmarks(bookmarks)));
+            },
+            EInfo::ProductionJobs(player) => {
+                net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::ProductionJobs(db.db.production_player_jobs(player))));
+            },
+            EInfo::OrderExpired(player, item_id, order_id) => {
+                net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::OrderExpired(item_id.clone(), *order_id)));
+            },
+            EInfo::Skills(player) => {
+                let skills = db.db.skills_get(player, Utc::now().timestamp());
+                net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::Skills(SSkills::new(&skills, &db.db.skill_table))));
+            },
+            EInfo::Insurance(player) => {
+                net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::Insurance(db.db.insurance_player_contracts(player, Utc::now().timestamp()))));
+            },
+            EInfo::HomeStation(player) => {
+                match db.db.account_get_home_station(player) {
+                    Some(home) => net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::HomeStation(home))),
+                    None => { eprintln!("No home station for {}", player); }
+                }
+            },
             _ => ()
         }
     }
@@ -253,6 +376,7 @@ pub fn sys_dispatch_inv_bank_updates(
 
 pub fn sys_dispatch_ship_inventory_requests(
     ships: Query<&Ship>,
+    containers: Query<&Container>,
     mut inf: EventReader<EInfo>,
     net: Res<NetworkHandler>,
     ptm: Res<PathToEntityMap>,
@@ -272,9 +396,13 @@ pub fn sys_dispatch_ship_inventory_requests(
                     eprintln!("Player ship not found: {}-{:?}", player, ship_path);
                 }
             },
+            EInfo::UpdateInventoryGameObject(player, path) => {
+                match ptm.get(path).and_then(|e| containers.get(e).ok()) {
+                    Some(c) => net.enqueue_outgoing(player, NetOutgoingMessage::Info(NetOutInfo::InventoryGameObject(c.inv.clone(), path.clone()))),
+                    None => { eprintln!("Container not found: {}-{:?}", player, path); }
+                }
+            },
             _ => ()
         }
     }
 }
-
-/* TODO: Implement game object inventory requests */
\ No newline at end of file
@@ -0,0 +1,106 @@
+use bevy_ecs::prelude::*;
+use chrono::Utc;
+
+use crate::db::ProductionJob;
+use crate::galaxy::components::*;
+use crate::galaxy::events::EInfo;
+use crate::galaxy::resources::{database_resource::DatabaseResource, gameplay_config::GameplayConfig, network_handler::NetworkHandler, path_to_entity::PathToEntityMap, production_queue::ProductionQueue};
+use crate::inventory::{InvId, ItemId, Mapping};
+use crate::network::messages::incoming::NetIncomingMessage;
+use crate::skills::skill_production_time_mult;
+
+/// PROCESS REQUESTS TO START AND LIST PRODUCTION JOBS
+/// Stage: COMMAND
+pub fn sys_process_production_inputs(hangers: Query<&Hanger>, ptm: Res<PathToEntityMap>, net: Res<NetworkHandler>, db: Res<DatabaseResource>, gameplay: Res<GameplayConfig>, mut queue: ResMut<ProductionQueue>, mut ein: EventWriter<EInfo>) {
+    for slot in net.view_incoming() {
+        let player = slot.key();
+        for msg in slot.value().iter() {
+            let res = match msg {
+                NetIncomingMessage::StartProductionJob(inv_id, bp_item, runs) => {
+                    if *runs > gameplay.cfg.max_production_runs {
+                        Err(format!("A job can have at most {} runs", gameplay.cfg.max_production_runs))
+                    }
+                    else {
+                        docked_at(&hangers, &ptm, &db, player, inv_id)
+                            .and_then(|_| start_job(&db, &mut queue, player, inv_id, bp_item, *runs))
+                            .map(|_| {
+                                ein.send(EInfo::UpdateInventoryId(player.clone(), inv_id.clone()));
+                                ein.send(EInfo::ProductionJobs(player.clone()));
+                            })
+                    }
+                },
+                NetIncomingMessage::RequestProductionJobs => { ein.send(EInfo::ProductionJobs(player.clone())); Ok(()) },
+                _ => Ok(())
+            };
+
+            if let Err(e) = res {
+                ein.send(EInfo::Error(player.clone(), e));
+            }
+        }
+    }
+}
+
+/// jobs can only be started from the station the player is docked at
+fn docked_at(hangers: &Query<&Hanger>, ptm: &PathToEntityMap, db: &DatabaseResource, player: &String, inv_id: &InvId) -> Result<(), String> {
+    let player_loc = db.db.account_get_location(player).ok_or(String::from("Player location not found"))?;
+    let hanger = ptm.get(&player_loc).and_then(|e| hangers.get(e).ok()).ok_or(String::from("You must be docked to start a job"))?;
+    if hanger.hanger_uid != *inv_id {
+        return Err(String::from("Jobs can only use the inventory of the station you are docked at"));
+    }
+    Ok(())
+}
+
+fn start_job(db: &DatabaseResource, queue: &mut ProductionQueue, player: &String, inv_id: &InvId, bp_item: &ItemId, runs: u32) -> Result<(), String> {
+    if runs == 0 {
+        return Err(String::from("A job needs at least one run"));
+    }
+
+    let bp = match db.db.item_table.get(bp_item).map(|i| &i.mapping) {
+        Some(Mapping::Blueprint(b)) => db.db.blueprint_table.get(b).ok_or(String::from("Blueprint not found"))?,
+        _ => { return Err(String::from("That item is not a blueprint")); }
+    };
+
+    // the blueprint itself is not used up, but it has to be there
+    let inv = db.db.inventory_get_inv(player, inv_id.clone()).ok_or(String::from("You do not have that blueprint here"))?;
+    if inv.count_item(bp_item) == 0 {
+        return Err(String::from("You do not have that blueprint here"));
+    }
+
+    let inputs = bp.inputs_for(runs)?;
+    bp.outputs_for(runs)?; // checked now so the job can not fail to deliver later
+    db.db.inventory_consume_stacks(player, inv_id.clone(), &inputs)?;
+    let skills = db.db.skills_get(player, Utc::now().timestamp());
+    let time_mult = skill_production_time_mult(&db.db.skill_table, &skills.levels);
+    let job = ProductionJob::new(player, bp, runs, inv_id.clone(), time_mult);
+    db.db.production_add_job(&job);
+    queue.add(job);
+    Ok(())
+}
+
+/// DELIVERS THE OUTPUT OF FINISHED JOBS TO THE STATION THEY WERE STARTED AT
+/// a job that can not deliver gets its inputs back if they can still be worked out, either way it is finished with
+/// Stage: ACTION
+pub fn sys_tick_production(mut queue: ResMut<ProductionQueue>, db: Res<DatabaseResource>, mut ein: EventWriter<EInfo>) {
+    for job in queue.take_finished(Utc::now().timestamp()) {
+        let outputs = match db.db.blueprint_table.get(&job.blueprint) {
+            Some(bp) => bp.outputs_for(job.runs).or_else(|e| {
+                eprintln!("Finished job {} can not deliver its output, refunding its inputs: {}", job.job_id, e);
+                bp.inputs_for(job.runs)
+            }).unwrap_or_else(|e| {
+                eprintln!("Finished job {} can not refund its inputs either, they are lost: {}", job.job_id, e);
+                vec![]
+            }),
+            None => {
+                eprintln!("Finished job {} uses blueprint {} which no longer exists, its inputs are lost", job.job_id, job.blueprint);
+                vec![]
+            }
+        };
+        for stack in outputs {
+            db.db.inventory_insert_stack_free_slot_ignore_capacity(&job.player, job.location.clone(), stack);
+        }
+        db.db.production_remove_job(&job.player, job.job_id);
+
+        ein.send(EInfo::UpdateInventoryId(job.player.clone(), job.location.clone()));
+        ein.send(EInfo::ProductionJobs(job.player.clone()));
+    }
+}
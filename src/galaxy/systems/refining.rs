@@ -0,0 +1,58 @@
+use bevy_ecs::prelude::*;
+
+use crate::galaxy::components::*;
+use crate::galaxy::events::EInfo;
+use crate::galaxy::resources::{database_resource::DatabaseResource, network_handler::NetworkHandler, path_to_entity::PathToEntityMap};
+use crate::inventory::{InvId, InvSlot};
+use crate::network::messages::incoming::NetIncomingMessage;
+
+/// PROCESS ORE REFINING REQUESTS, everything happens in the station inventory of the station the player is docked at
+/// Stage: COMMAND
+pub fn sys_process_refining(stations: Query<(&Station, &Hanger)>, ptm: Res<PathToEntityMap>, net: Res<NetworkHandler>, db: Res<DatabaseResource>, mut ein: EventWriter<EInfo>) {
+    for slot in net.view_incoming() {
+        let player = slot.key();
+        for msg in slot.value().iter() {
+            if let NetIncomingMessage::RefineOre(inv_id, inv_slot, count) = msg {
+                match refine(&stations, &ptm, &db, player, inv_id, *inv_slot, *count) {
+                    Ok(()) => { ein.send(EInfo::UpdateInventoryId(player.clone(), inv_id.clone())); },
+                    Err(e) => { ein.send(EInfo::Error(player.clone(), e)); }
+                }
+            }
+        }
+    }
+}
+
+fn refine(stations: &Query<(&Station, &Hanger)>, ptm: &PathToEntityMap, db: &DatabaseResource, player: &String, inv_id: &InvId, inv_slot: InvSlot, count: u32) -> Result<(), String> {
+    let player_loc = db.db.account_get_location(player).ok_or(String::from("Player location not found"))?;
+    let (station, hanger) = ptm.get(&player_loc).and_then(|e| stations.get(e).ok()).ok_or(String::from("You must be docked to refine"))?;
+    if hanger.hanger_uid != *inv_id {
+        return Err(String::from("Can only refine ore in the station you are docked at"));
+    }
+
+    let inv = db.db.inventory_get_inv(player, inv_id.clone()).ok_or(String::from("Nothing to refine"))?;
+    let stack = inv.get_stack(inv_slot).ok_or(String::from("Nothing to refine"))?;
+    let refine = db.db.refine_table.get(&stack.id).ok_or(format!("{} can not be refined", stack.id))?;
+
+    let batches = count.min(stack.count) / refine.batch;
+    if batches == 0 {
+        return Err(format!("Need at least {} {} to refine", refine.batch, stack.id));
+    }
+
+    // work out what comes out before any ore is taken so a bad request costs nothing
+    let outputs = refine.outputs_for(batches, station.refining_tax)?;
+    let taken = db.db.inventory_remove_stack(player, inv_id.clone(), inv_slot, Some(batches * refine.batch)).ok_or(String::from("Nothing to refine"))?;
+    if taken.id != refine.ore {
+        // the slot changed under us, put it back
+        if let Some(s) = db.db.inventory_insert_stack(player, inv_id.clone(), taken, Some(inv_slot)) {
+            eprintln!("refine: Could not return items to station inventory, they have been annihilated: {:?}", s);
+        }
+        return Err(String::from("Nothing to refine"));
+    }
+
+    for out in outputs {
+        if let Some(s) = db.db.inventory_insert_stack(player, inv_id.clone(), out, None) {
+            eprintln!("refine: Could not deposit refined materials, they have been annihilated: {:?}", s);
+        }
+    }
+    Ok(())
+}
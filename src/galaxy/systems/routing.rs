@@ -0,0 +1,17 @@
+use bevy_ecs::prelude::*;
+
+use crate::galaxy::events::EInfo;
+use crate::galaxy::resources::{galaxy_map::GalaxyMapRes, network_handler::NetworkHandler};
+use crate::network::messages::incoming::NetIncomingMessage;
+
+pub fn sys_process_route_requests(gmap: Res<GalaxyMapRes>, net: Res<NetworkHandler>, mut ein: EventWriter<EInfo>) {
+    for slot in net.view_incoming() {
+        let player = slot.key();
+        for msg in slot.value().iter() {
+            if let NetIncomingMessage::RequestRoute(start, end, opts) = msg {
+                let route = gmap.graph.find_route(start, end, opts).map(|r| r.into_iter().collect());
+                ein.send(EInfo::Route(player.clone(), start.clone(), end.clone(), route));
+            }
+        }
+    }
+}
@@ -1,92 +1,174 @@
+use std::collections::HashSet;
 use std::sync::Mutex;
 
 use bevy_ecs::prelude::*;
-use crate::galaxy::{components::*, resources::star_system_table::SystemMapTable, events::{EState, EEvent}};
+use nalgebra::Vector3;
+use crate::galaxy::{components::*, resources::spatial_index::SpatialIndex, events::EState};
 
-/* TODO: rework to only operate over signatured objects, we don't need to find the visibility of static objects */
-pub fn sys_get_visible(mut sensors: Query<(Entity, &mut Sensor, &Ship, &PlayerController)>, signatures: Query<&Signature>, game_objects: Query<(&GameObject, &Transform)>, sys_map: Res<SystemMapTable>, mut est: EventWriter<EState>) {
+pub fn sys_get_visible(mut sensors: Query<(Entity, &mut Sensor, &Ship, &PlayerController)>, game_objects: Query<&GameObject>, index: Res<SpatialIndex>, est: EventWriter<EState>) {
     let est_mut = Mutex::new(est);
     sensors.par_for_each_mut(4, |(ent, mut sensor, ship, pc)| {
-        let sensor_system = match sys_map.get_system_of_entity(ent){
-            Some(ss) => ss,
+        let me = match index.get(ent) {
+            Some(me) => me,
             None => {
-                // This goes off the tick the sensor spawns as it hasn't been reflected in the sys_map table yet
-                //if sys_map.entity_table.is_empty() { return; } 
-                //eprintln!("NO system found for sensor (bookeeping is broken or sensor object wasn't cleaned up properly?)"); 
+                // This goes off the tick the sensor spawns as it hasn't been reflected in the spatial index yet
                 return; 
             }
         };
-        let objects_in_system = match sys_map.get_entities_in_system(sensor_system) {
-            Some(ois) => ois,
-            None => { eprintln!("System that sensor is in not found, something is really broken"); return; }
-        };
-
-        //sensor.lockable_objs.clear();
-        //sensor.visible_objs.clear();
 
-        let sensor_pos = match game_objects.get(ent) {
-            Ok((_, mt)) => mt,
-            Err(_) => {
-                eprintln!("Sensor could not find own game object in query");
-                return;
-            } 
-        };
+        // nothing in the system can be seen further out than the biggest signature around
+        let sweep_radius = detection_range(ship, index.max_sig_m());
 
-        for entity in objects_in_system {
-            if *entity == ent {
+        let mut lockable = HashSet::new();
+        let mut visible = HashSet::new();
+        index.for_each_in_radius(&me.sys, &me.pos, sweep_radius, |entity, target| {
+            if entity == ent {
                 // looking at ourselves
-                continue;
+                return;
             }
 
-            let (object_path, target_pos) = match game_objects.get(*entity) {
-                Ok((go, t)) => (go.path.clone(), t),
+            // if something does not have a signature, it is static and always known about
+            let sig_m = match target.sig_m {
+                Some(s) => s,
+                None => return
+            };
+
+            let object_path = match game_objects.get(entity) {
+                Ok(go) => go.path.clone(),
                 Err(_) => {
-                    eprintln!("Game object from sys_map not found in game object query");
+                    eprintln!("Game object from spatial index not found in game object query");
                     return;
                 }
             };
-        
-            // if something does not have a signature component, it is static
-            let vis_status = match signatures.get(*entity) {
-                Ok(sig) => vis_test(&sensor, &ship, sensor_pos, sig, target_pos),
-                Err(_) => ObjectVisibility::Static
-            };
 
-            //println!("Ship {} is sensing {:?} ({:?})", ship.ship_name, object_path, vis_status);
-
-            let newly_visible = match vis_status {
-                ObjectVisibility::Lockable => {
-                    let was_visible = sensor.visible_objs.remove(&object_path);
-                    sensor.lockable_objs.insert(object_path.clone()) && !was_visible //returns true if the item was previously invisible
-                },
-                ObjectVisibility::Visible => {
-                    let was_lockable = sensor.lockable_objs.remove(&object_path);
-                    sensor.visible_objs.insert(object_path.clone()) && !was_lockable //returns true if the item was previously invisible
-                },
-                ObjectVisibility::NotVisible => {
-                    if sensor.lockable_objs.remove(&object_path) || sensor.visible_objs.remove(&object_path) {
-                        //IF THIS IS TRUE, WE ACTUALLY LOST SIGHT OF THE OBJECT
-                        let mut ew = est_mut.lock().expect("Could not lock mutex");
-                        ew.send(EState::LostSight(pc.player_name.clone(), object_path.clone()));
-                    }
-                    false
-                }
-                ObjectVisibility::Static => false
-            };
+            match vis_test(ship, &me.pos, sig_m, &target.pos) {
+                ObjectVisibility::Lockable => { lockable.insert(object_path); },
+                ObjectVisibility::Visible => { visible.insert(object_path); },
+                _ => ()
+            }
+        });
 
-            if newly_visible {
-                let mut ew = est_mut.lock().expect("Could not lock mutex");
-                ew.send(EState::OtherShip(pc.player_name.clone(), object_path.clone(), vis_status));
+        //println!("Ship {} is sensing {:?} / {:?}", ship.ship_name, lockable, visible);
+
+        {
+            let mut ew = est_mut.lock().expect("Could not lock mutex");
+            // anything we could see last tick but not this one has been lost (includes things that left the system)
+            for lost in sensor.lockable_objs.iter().chain(sensor.visible_objs.iter()) {
+                if !lockable.contains(lost) && !visible.contains(lost) {
+                    ew.send(EState::LostSight(pc.player_name.clone(), lost.clone()));
+                }
+            }
+            for path in lockable.iter() {
+                if !sensor.lockable_objs.contains(path) && !sensor.visible_objs.contains(path) {
+                    ew.send(EState::OtherShip(pc.player_name.clone(), path.clone(), ObjectVisibility::Lockable));
+                }
+            }
+            for path in visible.iter() {
+                if !sensor.lockable_objs.contains(path) && !sensor.visible_objs.contains(path) {
+                    ew.send(EState::OtherShip(pc.player_name.clone(), path.clone(), ObjectVisibility::Visible));
+                }
             }
         }
-            
+
+        sensor.lockable_objs = lockable;
+        sensor.visible_objs = visible;
     });
 }
 
-fn vis_test(sensor: &Sensor, sensing_ship: &Ship, sensor_pos: &Transform, target: &Signature, target_pos: &Transform) -> ObjectVisibility {
-    if sensor_pos.pos.metric_distance(&target_pos.pos) > 100_000.0 {
+// signature size that is seen at exactly the sensor range of a ship
+const REFERENCE_SIG_M: f64 = 10.0;
+
+/// range at which a ship can see a signature, bigger signatures are seen further out
+fn detection_range(sensing_ship: &Ship, target_sig_m: f64) -> f64 {
+    sensing_ship.stats.sensor_range_m * (target_sig_m.max(0.0) / REFERENCE_SIG_M).sqrt()
+}
+
+fn vis_test(sensing_ship: &Ship, sensor_pos: &Vector3<f64>, target_sig_m: f64, target_pos: &Vector3<f64>) -> ObjectVisibility {
+    // no signature at all can not be picked up, even right on top of the sensor
+    if target_sig_m <= 0.0 {
         return ObjectVisibility::NotVisible;
     }
 
-    ObjectVisibility::Lockable
-}
\ No newline at end of file
+    let dist = sensor_pos.metric_distance(target_pos);
+    let detect_range = detection_range(sensing_ship, target_sig_m);
+
+    if dist > detect_range {
+        ObjectVisibility::NotVisible
+    } else if dist <= sensing_ship.stats.lock_range_m {
+        ObjectVisibility::Lockable
+    } else {
+        ObjectVisibility::Visible
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::Inventory;
+
+    fn test_ship(sensor_range_m: f64, lock_range_m: f64) -> Ship {
+        Ship {
+            ship_name: String::from("Sensor test"),
+            ship_class: String::from("Test Ship"),
+            stats: Stats { warp_speed_ms: 1.0, thrust_n: 1.0, ang_vel_rads: 1.0, mass_kg: 1.0, warp_spool_s: 1.0, sensor_range_m, lock_range_m, scan_resolution_mm: 300.0, max_locks: 3, mining_yield: 0.0, mining_cycle_s: 10.0, mining_range_m: 10_000.0, shield_hp: 300.0, armor_hp: 300.0, hull_hp: 300.0, shield_recharge_s: 120.0, damage_mult: 1.0 },
+            inventory: Inventory::new(None, None),
+            fitting: Fitting::default(),
+            ship_id: 1
+        }
+    }
+
+    fn test(ship: &Ship, sig_m: f64, dist: f64) -> ObjectVisibility {
+        vis_test(ship, &Vector3::zeros(), sig_m, &Vector3::new(dist, 0.0, 0.0))
+    }
+
+    #[test]
+    fn reference_signature_seen_at_sensor_range() {
+        let ship = test_ship(200_000.0, 100_000.0);
+        assert_eq!(test(&ship, REFERENCE_SIG_M, 199_999.0), ObjectVisibility::Visible);
+        assert_eq!(test(&ship, REFERENCE_SIG_M, 200_000.0), ObjectVisibility::Visible);
+        assert_eq!(test(&ship, REFERENCE_SIG_M, 200_001.0), ObjectVisibility::NotVisible);
+    }
+
+    #[test]
+    fn lockable_inside_lock_range() {
+        let ship = test_ship(200_000.0, 100_000.0);
+        assert_eq!(test(&ship, REFERENCE_SIG_M, 0.0), ObjectVisibility::Lockable);
+        assert_eq!(test(&ship, REFERENCE_SIG_M, 100_000.0), ObjectVisibility::Lockable);
+        assert_eq!(test(&ship, REFERENCE_SIG_M, 100_001.0), ObjectVisibility::Visible);
+    }
+
+    #[test]
+    fn signature_size_scales_detection_range() {
+        let ship = test_ship(200_000.0, 100_000.0);
+        // 4x the signature is seen at 2x the range
+        assert_eq!(test(&ship, 4.0 * REFERENCE_SIG_M, 399_000.0), ObjectVisibility::Visible);
+        assert_eq!(test(&ship, 4.0 * REFERENCE_SIG_M, 401_000.0), ObjectVisibility::NotVisible);
+        // 1/4 the signature is seen at half the range
+        assert_eq!(test(&ship, 0.25 * REFERENCE_SIG_M, 99_000.0), ObjectVisibility::Lockable);
+        assert_eq!(test(&ship, 0.25 * REFERENCE_SIG_M, 101_000.0), ObjectVisibility::NotVisible);
+    }
+
+    #[test]
+    fn small_signature_not_lockable_past_detection() {
+        // lock range is longer than the range this signature can be seen at, it must not be lockable while unseen
+        let ship = test_ship(100_000.0, 100_000.0);
+        assert_eq!(test(&ship, 0.01 * REFERENCE_SIG_M, 20_000.0), ObjectVisibility::NotVisible);
+        assert_eq!(test(&ship, 0.01 * REFERENCE_SIG_M, 9_000.0), ObjectVisibility::Lockable);
+    }
+
+    #[test]
+    fn zero_signature_never_visible() {
+        let ship = test_ship(200_000.0, 100_000.0);
+        assert_eq!(test(&ship, 0.0, 1.0), ObjectVisibility::NotVisible);
+        assert_eq!(test(&ship, 0.0, 0.0), ObjectVisibility::NotVisible);
+        assert_eq!(test(&ship, -5.0, 0.0), ObjectVisibility::NotVisible);
+    }
+
+    #[test]
+    fn better_sensors_see_further() {
+        let weak = test_ship(100_000.0, 50_000.0);
+        let strong = test_ship(300_000.0, 150_000.0);
+        assert_eq!(test(&weak, REFERENCE_SIG_M, 150_000.0), ObjectVisibility::NotVisible);
+        assert_eq!(test(&strong, REFERENCE_SIG_M, 150_000.0), ObjectVisibility::Lockable);
+    }
+}
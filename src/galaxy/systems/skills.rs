@@ -0,0 +1,61 @@
+use bevy_ecs::prelude::*;
+use chrono::Utc;
+
+use crate::galaxy::components::*;
+use crate::galaxy::events::EInfo;
+use crate::galaxy::resources::{database_resource::DatabaseResource, network_handler::NetworkHandler, skill_queue::SkillQueue};
+use crate::network::messages::incoming::NetIncomingMessage;
+
+/// PROCESS REQUESTS TO QUEUE, REMOVE AND LIST SKILLS
+/// Stage: COMMAND
+pub fn sys_process_skill_inputs(net: Res<NetworkHandler>, db: Res<DatabaseResource>, mut queue: ResMut<SkillQueue>, mut ein: EventWriter<EInfo>) {
+    let now = Utc::now().timestamp();
+    for slot in net.view_incoming() {
+        let player = slot.key();
+        for msg in slot.value().iter() {
+            let res = match msg {
+                NetIncomingMessage::QueueSkill(id) => db.db.skills_enqueue(player, id, now).map(|_| ()),
+                NetIncomingMessage::RemoveQueuedSkill(index) => db.db.skills_remove_queued(player, *index as usize, now),
+                NetIncomingMessage::RequestSkills => { ein.send(EInfo::Skills(player.clone())); continue; },
+                _ => { continue; }
+            };
+
+            match res {
+                Ok(()) => {
+                    let (_, next) = db.db.skills_advance(player, now);
+                    queue.set(player, next);
+                    ein.send(EInfo::Skills(player.clone()));
+                },
+                Err(e) => { ein.send(EInfo::Error(player.clone(), e)); }
+            }
+        }
+    }
+}
+
+/// COMPLETES TRAINED SKILL LEVELS AND APPLIES THEM TO SHIPS IN SPACE
+/// Stage: ACTION
+pub fn sys_tick_skills(mut ships: Query<(&PlayerController, &mut Ship)>, db: Res<DatabaseResource>, mut queue: ResMut<SkillQueue>, mut ein: EventWriter<EInfo>) {
+    let now = Utc::now().timestamp();
+    let due = queue.due(now);
+    if due.is_empty() {
+        return;
+    }
+
+    for player in due.iter() {
+        let (done, next) = db.db.skills_advance(player, now);
+        queue.set(player, next);
+        if !done.is_empty() {
+            ein.send(EInfo::Skills(player.clone()));
+        }
+    }
+
+    // docked ships pick up new levels when they undock
+    for (pc, mut ship) in ships.iter_mut() {
+        if !due.contains(&pc.player_name) {
+            continue;
+        }
+        if let Err(e) = ship.recompute_stats(&db.db.ship_class_table, &db.db.item_table, &db.db.module_table, &db.db.skills_stat_modifiers(&pc.player_name)) {
+            eprintln!("Could not apply skills to {}'s ship: {}", pc.player_name, e);
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use bevy_ecs::prelude::*;
+
+use crate::galaxy::{components::*, resources::spatial_index::SpatialIndex};
+
+type MovedObjects<'w, 's> = Query<'w, 's, (Entity, &'static GameObject, &'static Transform, Option<&'static Signature>), Or<(Changed<GameObject>, Changed<Transform>)>>;
+
+pub fn update_spatial_index(q: MovedObjects, mut index: ResMut<SpatialIndex>) {
+    for (e, go, t, sig) in q.iter() {
+        index.update_entity(&go.path.sys, e, t.pos, sig.map(|s| s.size_m));
+    }
+}
+
+pub fn spatial_index_removal_hook(e: Entity, index: &mut SpatialIndex) {
+    index.remove_entity(e);
+}
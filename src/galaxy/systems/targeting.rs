@@ -0,0 +1,155 @@
+use bevy_ecs::prelude::*;
+
+use crate::galaxy::components::*;
+use crate::galaxy::events::{EInfo, EState};
+use crate::galaxy::resources::{delta_time::DeltaTime, network_handler::NetworkHandler, path_to_entity::PathToEntityMap};
+use crate::network::messages::incoming::NetIncomingMessage;
+use crate::shared::ObjPath;
+
+// numerator of the lock time formula, a 300mm scan resolution locks a 10m signature in ~15 seconds
+const LOCK_TIME_FACTOR: f64 = 40_000.0;
+const MIN_LOCK_TIME_S: f64 = 1.0;
+
+/// seconds it takes a ship to lock a target, smaller signatures take longer
+pub fn lock_time_s(scan_resolution_mm: f64, target_sig_m: f64) -> f64 {
+    let sig_term = target_sig_m.max(0.0).asinh().powi(2);
+    if scan_resolution_mm <= 0.0 || sig_term <= 0.0 {
+        return f64::INFINITY;
+    }
+    (LOCK_TIME_FACTOR / (scan_resolution_mm * sig_term)).max(MIN_LOCK_TIME_S)
+}
+
+/// PROCESS LOCK AND UNLOCK REQUESTS
+/// Stage: COMMAND
+pub fn sys_process_lock_inputs(mut ships: Query<(&PlayerController, &Ship, &Sensor, &mut Targeting)>, signatures: Query<&Signature>, ptm: Res<PathToEntityMap>, net: Res<NetworkHandler>, mut est: EventWriter<EState>, mut ein: EventWriter<EInfo>) {
+    for slot in net.view_incoming() {
+        let player = slot.key();
+        for msg in slot.value().iter() {
+            let res = match msg {
+                NetIncomingMessage::LockTarget(ship, target) => lock_target(&mut ships, &signatures, &ptm, player, ship, target, &mut est),
+                NetIncomingMessage::UnlockTarget(ship, target) => unlock_target(&mut ships, &ptm, player, ship, target, &mut est),
+                _ => Ok(())
+            };
+
+            if let Err(e) = res {
+                ein.send(EInfo::Error(player.clone(), e));
+            }
+        }
+    }
+}
+
+fn lock_target(ships: &mut Query<(&PlayerController, &Ship, &Sensor, &mut Targeting)>, signatures: &Query<&Signature>, ptm: &PathToEntityMap, player: &String, ship: &ObjPath, target: &ObjPath, est: &mut EventWriter<EState>) -> Result<(), String> {
+    let ship_ent = ptm.get(ship).ok_or(String::from("Ship not found"))?;
+    let (pc, s, sensor, mut targeting) = ships.get_mut(ship_ent).map_err(|_| String::from("Ship not found"))?;
+    if pc.player_name != *player {
+        eprintln!("{} trying to lock with other player's ship", player);
+        return Err(String::from("Not your ship"));
+    }
+
+    if targeting.is_targeting(target) {
+        return Ok(()); // already on it
+    }
+
+    if !sensor.lockable_objs.contains(target) {
+        return Err(String::from("Target is not in lock range"));
+    }
+
+    if targeting.lock_count() >= s.stats.max_locks as usize {
+        return Err(format!("Can not lock more than {} targets", s.stats.max_locks));
+    }
+
+    let sig = ptm.get(target).and_then(|e| signatures.get(e).ok()).ok_or(String::from("Target can not be locked"))?;
+    let time = lock_time_s(s.stats.scan_resolution_mm, sig.size_m);
+    if !time.is_finite() {
+        return Err(String::from("Target can not be locked"));
+    }
+
+    targeting.pending.insert(target.clone(), time);
+    est.send(EState::TargetLocking(player.clone(), target.clone(), time));
+    Ok(())
+}
+
+fn unlock_target(ships: &mut Query<(&PlayerController, &Ship, &Sensor, &mut Targeting)>, ptm: &PathToEntityMap, player: &String, ship: &ObjPath, target: &ObjPath, est: &mut EventWriter<EState>) -> Result<(), String> {
+    let ship_ent = ptm.get(ship).ok_or(String::from("Ship not found"))?;
+    let (pc, _s, _sensor, mut targeting) = ships.get_mut(ship_ent).map_err(|_| String::from("Ship not found"))?;
+    if pc.player_name != *player {
+        eprintln!("{} trying to unlock with other player's ship", player);
+        return Err(String::from("Not your ship"));
+    }
+
+    if targeting.unlock(target) {
+        est.send(EState::TargetUnlocked(player.clone(), target.clone()));
+    }
+    Ok(())
+}
+
+/// COUNTS DOWN PENDING LOCKS AND BREAKS LOCKS ON ANYTHING THAT IS NO LONGER LOCKABLE
+/// Stage: ACTION
+pub fn sys_tick_targeting(mut ships: Query<(&PlayerController, &Sensor, &mut Targeting)>, dt: Res<DeltaTime>, mut est: EventWriter<EState>) {
+    for (pc, sensor, mut targeting) in ships.iter_mut() {
+        if targeting.lock_count() == 0 {
+            continue;
+        }
+
+        // the target left lock range, jumped, docked, or we did
+        let broken: Vec<ObjPath> = targeting.locked.iter().chain(targeting.pending.keys()).filter(|t| !sensor.lockable_objs.contains(*t)).cloned().collect();
+        for t in broken {
+            targeting.unlock(&t);
+            est.send(EState::TargetUnlocked(pc.player_name.clone(), t));
+        }
+
+        let mut done = vec![];
+        for (t, left) in targeting.pending.iter_mut() {
+            *left -= dt.dt;
+            if *left <= 0.0 {
+                done.push(t.clone());
+            }
+        }
+
+        for t in done {
+            targeting.pending.remove(&t);
+            targeting.locked.insert(t.clone());
+            est.send(EState::TargetLocked(pc.player_name.clone(), t));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_value() {
+        // asinh(10)^2 is about 8.988
+        assert!((lock_time_s(300.0, 10.0) - 14.835).abs() < 0.01);
+    }
+
+    #[test]
+    fn scales_inversely_with_scan_resolution() {
+        let slow = lock_time_s(100.0, 10.0);
+        let fast = lock_time_s(200.0, 10.0);
+        assert!((slow / fast - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bigger_signatures_lock_faster() {
+        let times: Vec<f64> = [1.0, 10.0, 100.0, 1000.0].iter().map(|sig| lock_time_s(50.0, *sig)).collect();
+        assert!(times.windows(2).all(|w| w[0] > w[1]));
+        let expected = (1000.0_f64.asinh() / 10.0_f64.asinh()).powi(2);
+        assert!((lock_time_s(50.0, 10.0) / lock_time_s(50.0, 1000.0) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn never_faster_than_the_minimum() {
+        assert_eq!(lock_time_s(1_000_000.0, 10.0), MIN_LOCK_TIME_S);
+        assert_eq!(lock_time_s(300.0, 1e12), MIN_LOCK_TIME_S);
+    }
+
+    #[test]
+    fn no_scan_resolution_or_signature_never_locks() {
+        assert_eq!(lock_time_s(0.0, 10.0), f64::INFINITY);
+        assert_eq!(lock_time_s(-5.0, 10.0), f64::INFINITY);
+        assert_eq!(lock_time_s(300.0, 0.0), f64::INFINITY);
+        assert_eq!(lock_time_s(300.0, -1.0), f64::INFINITY);
+    }
+}
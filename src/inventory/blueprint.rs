@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use super::Stack;
+
+pub type BlueprintTable = HashMap<String, Blueprint>;
+pub type BlueprintId = String;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Blueprint {
+    pub id: BlueprintId,
+    pub inputs: Vec<Stack>, // consumed when the job starts
+    pub outputs: Vec<Stack>, // delivered when the job finishes
+    pub duration_s: f64, // per run
+}
+
+fn scale(stacks: &[Stack], runs: u32) -> Result<Vec<Stack>, String> {
+    stacks.iter()
+        .map(|s| s.count.checked_mul(runs).map(|n| Stack::new(s.id.clone(), n)).ok_or(String::from("Too many runs")))
+        .collect()
+}
+
+impl Blueprint {
+    pub fn inputs_for(&self, runs: u32) -> Result<Vec<Stack>, String> {
+        scale(&self.inputs, runs)
+    }
+
+    pub fn outputs_for(&self, runs: u32) -> Result<Vec<Stack>, String> {
+        scale(&self.outputs, runs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_blueprint() -> Blueprint {
+        Blueprint {
+            id: String::from("test"),
+            inputs: vec![Stack::new(String::from("ore"), 1000)],
+            outputs: vec![Stack::new(String::from("widget"), 2)],
+            duration_s: 60.0
+        }
+    }
+
+    #[test]
+    fn scales_by_runs() {
+        let bp = test_blueprint();
+        assert_eq!(bp.inputs_for(3).unwrap()[0].count, 3000);
+        assert_eq!(bp.outputs_for(3).unwrap()[0].count, 6);
+    }
+
+    #[test]
+    fn overflowing_runs_are_rejected() {
+        let bp = test_blueprint();
+        // 1000 * runs wraps a u32 long before 2 * runs does, neither side may go through
+        let runs = u32::MAX / 1000 + 1;
+        assert_eq!(bp.inputs_for(runs).unwrap_err(), "Too many runs");
+        assert!(bp.outputs_for(runs).is_ok());
+        assert_eq!(bp.outputs_for(u32::MAX).unwrap_err(), "Too many runs");
+    }
+}
@@ -19,6 +19,10 @@ impl Inventory {
         Inventory { id: id, inv: HashMap::new(), capacity_vunits: capacity }
     }
 
+    pub fn set_capacity(&mut self, capacity: Option<u32>) {
+        self.capacity_vunits = capacity;
+    }
+
     ///will attempt to insert the stack, if there is not enough space, this will insert as many items as possible and then return the remainder as leftovers.
     ///if the destination slot is occupied, this will bump the contents of the destination slot to somewhere else IN THE SAME INVENTORY.
     ///NOTE: THIS WILL ONLY RETURN ITEMS IF THERE WAS NOT ENOUGH SPACE, IT WILL NEVER RETURN A DIFFERENT TYPE OF ITEM THAN THE INPUT TYPE.
@@ -27,9 +31,10 @@ impl Inventory {
             Some(cap) => {
                 let vol_per_item = item_table.get(&stack.id).expect("GOT INVALID ITEM ID").size_vunits;
                 let used_vol = self.get_cap_used(item_table);
-                let free_vol = cap - used_vol;
-                let max_count = free_vol / vol_per_item;
+                let free_vol = cap.saturating_sub(used_vol);
+                let max_count = free_vol.checked_div(vol_per_item).unwrap_or(stack.count); // items that take up no space always fit
                 let count = max_count.min(stack.count);
+                if count == 0 { return Some(stack); } // no room at all
                 let insert_stack = stack.take_n(count);
                 match (insert_stack, slot) {
                     (None, _) => { return Some(stack); }, //weird stuff going on
@@ -63,6 +68,37 @@ impl Inventory {
     }
 
 
+    pub fn get_stack(&self, slot: InvSlot) -> Option<&Stack> {
+        self.inv.get(&slot)
+    }
+
+    pub fn count_item(&self, id: &ItemId) -> u32 {
+        self.inv.values().filter(|s| s.id == *id).map(|s| s.count).sum()
+    }
+
+    /// takes count items out of however many stacks they are spread over, returns false (and takes nothing) if there are not enough
+    pub fn remove_items(&mut self, id: &ItemId, count: u32) -> bool {
+        if self.count_item(id) < count {
+            return false;
+        }
+
+        let mut left = count;
+        let slots: Vec<InvSlot> = self.inv.iter().filter(|(_, s)| s.id == *id).map(|(k, _)| *k).collect();
+        for slot in slots {
+            if left == 0 { break; }
+            let have = self.inv.get(&slot).map(|s| s.count).unwrap_or(0);
+            let take = have.min(left);
+            self.remove_n_from_stack(slot, take);
+            left -= take;
+        }
+        true
+    }
+
+    /// empties the inventory, handing back every stack in it
+    pub fn take_all(&mut self) -> Vec<Stack> {
+        self.inv.drain().map(|(_, s)| s).collect()
+    }
+
     pub fn remove_stack(&mut self, slot: InvSlot) -> Option<Stack> {
         self.inv.remove(&slot)
     }
@@ -143,8 +179,10 @@ impl Inventory {
         return 0;
     }
 
+    /// every item in a stack takes up space. Inventories saved while only one item per stack was counted can come out over capacity,
+    /// nothing is lost, they just accept nothing more until enough is taken out
     pub fn get_cap_used(&self, item_table: &ItemTable) -> u32 {
-        self.inv.iter().map(|(k, v)| item_table.get(&v.id).and_then(|i| Some(i.size_vunits)).unwrap_or(0)).sum()
+        self.inv.values().map(|v| item_table.get(&v.id).map(|i| i.size_vunits.saturating_mul(v.count)).unwrap_or(0)).fold(0, u32::saturating_add)
     }
 
     pub fn is_empty(&self) -> bool {
@@ -192,4 +230,58 @@ impl Stack {
         }
         /* TODO: Decide if we want a stack size limit (no) */
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::inventory::{Item, Mapping};
+    use super::*;
+
+    fn items() -> ItemTable {
+        let item = |id: &str, size_vunits| (String::from(id), Item { id: String::from(id), tags: HashSet::new(), mapping: Mapping::None, size_vunits, tech_level: 1 });
+        ItemTable::from([item("ore", 10), item("blueprint_copy", 0)])
+    }
+
+    #[test]
+    fn every_item_in_a_stack_uses_space() {
+        let items = items();
+        let mut inv = Inventory::new(None, Some(1000));
+        assert!(inv.add_stack(&items, Stack::new(String::from("ore"), 30), None).is_none());
+        assert_eq!(inv.get_cap_used(&items), 300);
+    }
+
+    #[test]
+    fn zero_size_items_always_fit() {
+        let items = items();
+        let mut inv = Inventory::new(None, Some(100));
+        assert!(inv.add_stack(&items, Stack::new(String::from("ore"), 10), None).is_none());
+        assert!(inv.add_stack(&items, Stack::new(String::from("blueprint_copy"), 1_000_000), None).is_none());
+        assert_eq!(inv.count_item(&String::from("blueprint_copy")), 1_000_000);
+        assert_eq!(inv.get_cap_used(&items), 100);
+    }
+
+    #[test]
+    fn adding_past_capacity_returns_the_rest() {
+        let items = items();
+        let mut inv = Inventory::new(None, Some(250));
+        let left = inv.add_stack(&items, Stack::new(String::from("ore"), 40), None).expect("Should not all fit");
+        assert_eq!(left.count, 15);
+        assert_eq!(inv.count_item(&String::from("ore")), 25);
+        let left = inv.add_stack(&items, Stack::new(String::from("ore"), 1), None).expect("Inventory is full");
+        assert_eq!(left.count, 1);
+    }
+
+    #[test]
+    fn over_capacity_inventory_keeps_its_items() {
+        let items = items();
+        let mut inv = Inventory::new(None, None);
+        inv.add_stack(&items, Stack::new(String::from("ore"), 50), None);
+        inv.set_capacity(Some(100));
+        assert_eq!(inv.get_cap_used(&items), 500);
+        assert_eq!(inv.add_stack(&items, Stack::new(String::from("ore"), 1), None).map(|s| s.count), Some(1));
+        assert_eq!(inv.count_item(&String::from("ore")), 50);
+        assert!(inv.remove_items(&String::from("ore"), 45));
+        assert!(inv.add_stack(&items, Stack::new(String::from("ore"), 5), None).is_none());
+    }
+}
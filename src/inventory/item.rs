@@ -2,6 +2,8 @@ use std::collections::{HashSet, HashMap};
 
 use serde::{Serialize, Deserialize};
 
+use crate::shared::ObjectType;
+
 pub type ItemTable = HashMap<String, Item>;
 pub type ItemId = String;
 
@@ -13,7 +15,9 @@ pub enum ItemTag {
     Ore,
     Component,
     Ship,
-    Structure
+    Structure,
+    Blueprint,
+    Material
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -21,7 +25,7 @@ pub enum Mapping {
     Module(String), //module id
     Ship(String), //ship class
     Ammo(String), //ammo class
-    Structure(String), //structure class
+    Structure(ObjectType), //structure deployed from this item
     Blueprint(String), //blueprint id
     None,
 }
@@ -2,4 +2,16 @@ mod item;
 pub use item::*;
 
 mod inventory;
-pub use inventory::*;
\ No newline at end of file
+pub use inventory::*;
+
+mod module;
+pub use module::*;
+
+mod ship_class;
+pub use ship_class::*;
+
+mod blueprint;
+pub use blueprint::*;
+
+mod refining;
+pub use refining::*;
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+pub type ModuleTable = HashMap<String, Module>;
+pub type ModuleId = String;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum SlotType {
+    High,
+    Mid,
+    Low
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ShipStat {
+    WarpSpeed,
+    Thrust,
+    AngularVelocity,
+    Mass,
+    WarpSpool,
+    SensorRange,
+    LockRange,
+    ScanResolution,
+    MaxLocks,
+    MiningYield,
+    MiningCycle,
+    MiningRange,
+    ShieldHp,
+    ArmorHp,
+    HullHp,
+    ShieldRecharge,
+    Damage,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum Modifier {
+    Add(f64),
+    Mul(f64),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatModifier {
+    pub stat: ShipStat,
+    pub modifier: Modifier
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub enum WeaponKind {
+    Turret, // hits instantly if the target is in range
+    Missile, // launches a missile that flies to the target
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WeaponStats {
+    pub kind: WeaponKind,
+    pub damage: f64, // per shot, before the ship's damage multiplier
+    pub cycle_s: f64,
+    pub range_m: f64, // turrets: max range, missiles: how far they fly before burning out
+    #[serde(default)]
+    pub missile_speed_ms: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Module {
+    pub id: ModuleId,
+    pub slot: SlotType,
+    pub modifiers: Vec<StatModifier>,
+    pub weapon: Option<WeaponStats>, // high slot modules that can be activated on a locked target
+}
@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use super::{ItemId, Stack};
+
+pub type RefineTable = HashMap<ItemId, Refine>;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Refine {
+    pub ore: ItemId,
+    pub batch: u32, // ore is refined in batches of this many units, the rest is left alone
+    pub outputs: Vec<Stack>, // materials per batch, before tax
+}
+
+impl Refine {
+    /// materials out of the given number of batches after the station takes its cut
+    pub fn outputs_for(&self, batches: u32, tax: f64) -> Result<Vec<Stack>, String> {
+        let keep = (1.0 - tax).clamp(0.0, 1.0);
+        let mut out = vec![];
+        for s in self.outputs.iter() {
+            let count = s.count.checked_mul(batches).ok_or(String::from("Too much ore to refine at once"))?;
+            let count = (count as f64 * keep).floor() as u32;
+            if count > 0 {
+                out.push(Stack::new(s.id.clone(), count));
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_refine() -> Refine {
+        Refine {
+            ore: String::from("ore"),
+            batch: 100,
+            outputs: vec![Stack::new(String::from("metal"), 300), Stack::new(String::from("dust"), 1)]
+        }
+    }
+
+    #[test]
+    fn tax_comes_off_each_output() {
+        let out = test_refine().outputs_for(2, 0.1).unwrap();
+        assert_eq!(out[0].count, 540);
+        // 1.8 dust rounds down
+        assert_eq!(out[1].count, 1);
+        assert!(test_refine().outputs_for(1, 0.5).unwrap().iter().all(|s| s.id != "dust"));
+    }
+
+    #[test]
+    fn overflowing_batches_are_rejected() {
+        // 2e9 units in batches of 100 is 2e7 batches, 300 per batch does not fit in a u32
+        let batches = 2_000_000_000 / test_refine().batch;
+        assert_eq!(test_refine().outputs_for(batches, 0.0).unwrap_err(), "Too much ore to refine at once");
+    }
+}
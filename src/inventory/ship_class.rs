@@ -0,0 +1,21 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::galaxy::components::Stats;
+
+pub type ShipClassTable = HashMap<String, ShipClass>;
+pub type ShipClassId = String;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShipClass {
+    pub id: ShipClassId,
+    pub name: String,
+    pub stats: Stats, // hull stats, before any modules
+    pub cargo_vunits: u32,
+    pub signature_m: f64,
+    pub high_slots: usize,
+    pub mid_slots: usize,
+    pub low_slots: usize,
+    pub insured_value: i64, // insurance payout, 0 if the hull can not be insured
+}
@@ -1,6 +1,6 @@
 use std::{thread::{JoinHandle, self}, time::{Duration, Instant}};
 
-use crate::{db::injector::{inject_statics, load_items}, inventory::ItemTable};
+use crate::{db::injector::{inject_statics, load_items, load_modules, load_ship_classes, load_blueprints, load_refining, load_skills}, inventory::{ItemTable, ModuleTable, ShipClassTable, BlueprintTable, RefineTable}, skills::SkillTable};
 
 mod config;
 mod db;
@@ -8,6 +8,7 @@ mod galaxy;
 mod inventory;
 mod network;
 mod shared;
+mod skills;
 mod special;
 
 fn spawn_sleepy_thread(time_ms: u32) -> JoinHandle<()> {
@@ -18,10 +19,17 @@ fn main() {
     println!("Hello, world!");
     let config = config::load_config("./assets/config.json".to_string());
     let items: ItemTable = load_items(config.assets_path.clone());
-    let world = inject_statics(config.assets_path.clone());
-    let db = db::database::DB::load(&config.db_path, 1024 * 1024 * 1024, items.clone());
+    let modules: ModuleTable = load_modules(config.assets_path.clone(), &items);
+    let ship_classes: ShipClassTable = load_ship_classes(config.assets_path.clone(), &items);
+    let blueprints: BlueprintTable = load_blueprints(config.assets_path.clone(), &items);
+    let refines: RefineTable = load_refining(config.assets_path.clone(), &items);
+    let skills: SkillTable = load_skills(config.assets_path.clone());
+    let world = inject_statics(config.assets_path.clone(), &items);
+    let tables = db::database::AssetTables { items: items.clone(), modules, ship_classes, blueprints, refines, skills };
+    let db = db::database::DB::load(&config.db_path, 1024 * 1024 * 1024, tables);
+    db.ships_migrate_legacy_classes(&config.gameplay_config.legacy_ship_classes);
     let server = network::server::start_network(format!("{}:{}", config.network.websocket_ip, config.network.websocket_port));
-    let mut gal = galaxy::Galaxy::new(world, db, items);
+    let mut gal = galaxy::Galaxy::new(world, db, items, config.gameplay_config.clone());
 
     let mut last_cycle_time: f32 = 0.1;
 
@@ -1,5 +1,5 @@
 use serde::{Serialize, Deserialize};
-use crate::{shared::ObjPath, inventory::{InvSlot, InvId, ItemId}, db::HangerSlot, galaxy::components::HngId};
+use crate::{shared::ObjPath, inventory::{InvSlot, InvId, ItemId, SlotType}, db::{HangerSlot, BookmarkId, OrderRange}, galaxy::{components::HngId, pathfinding::RouteOptions}, skills::SkillId};
 
 // player will be known due to map location
 
@@ -12,19 +12,60 @@ pub enum NetIncomingMessage {
     /* Motion */
     WarpTo(ObjPath, ObjPath, f64), //ship, dst, dist
     Approach(ObjPath, ObjPath), //ship, dst
+    Orbit(ObjPath, ObjPath, f64), //ship, dst, radius
+    KeepAtRange(ObjPath, ObjPath, f64), //ship, dst, range
+    AlignTo(ObjPath, ObjPath), //ship, dst
+    WarpToBookmark(ObjPath, BookmarkId, f64), //ship, bookmark, dist
     MNav(ObjPath, f64, f64, f64, f64), //net rotation and thrust time x,y,z,t (integrate [-1, 1] (or [0,1] for thrust) axis input over the reporting period)
     
+    /* Bookmarks */
+    CreateBookmark(ObjPath, String, String), //ship, label, folder (bookmarks the ship's current position)
+    RenameBookmark(BookmarkId, String), //bookmark, new label
+    MoveBookmark(BookmarkId, String), //bookmark, new folder
+    DeleteBookmark(BookmarkId), //bookmark
+    ShareBookmark(BookmarkId, String), //bookmark, player to share with
+    RequestBookmarks,
+
+    /* Targeting */
+    LockTarget(ObjPath, ObjPath), //ship, target
+    UnlockTarget(ObjPath, ObjPath), //ship, target
+
+    /* Mining */
+    StartMining(ObjPath, ObjPath), //ship, asteroid (must be locked)
+    StopMining(ObjPath), //ship
+
+    /* Combat */
+    ActivateWeapon(ObjPath, u32, ObjPath), //ship, high slot index, target (must be locked)
+    DeactivateWeapon(ObjPath, u32), //ship, high slot index
+
+    /* Extraction */
+    DeployStructure(ObjPath, InvSlot, ObjPath), //ship, cargo slot holding the structure, moon or planet to deploy on
+    CollectStructure(ObjPath, ObjPath), //ship, extractor or planet office to empty in to the ship's cargo
+    RecoverStructure(ObjPath, ObjPath), //ship, extractor or planet office to pack up in to the ship's cargo along with everything in it
+
     /* Docking */
     Undock(ObjPath), //station path
     Dock(ObjPath, ObjPath), //ship, station path
     
     /* Jumping */
     Jump(ObjPath, ObjPath), //ship, gate
+    SetAutopilot(ObjPath, String, RouteOptions), //ship, destination system, route options
+    CancelAutopilot(ObjPath), //ship
+
+    /* Routes */
+    RequestRoute(String, String, RouteOptions), //start system, end system, route options
 
     /* Hanger */
     SetActiveShip(HangerSlot), // hanger slot
     /* TODO: request list of all hangers */
     HangerRequestShips(HngId), // hanger id
+    FitModule(HangerSlot, InvId, InvSlot, u32), //ship, station inventory, slot holding the module, fitting slot index (of the module's slot type)
+    UnfitModule(HangerSlot, SlotType, u32, InvId), //ship, slot type, fitting slot index, station inventory to put the module in
+    SetHomeStation(ObjPath), //station (must be docked in it), where the player wakes up after losing their ship
+
+    /* Insurance */
+    InsureShip(HangerSlot), //ship in the hanger the player is docked in
+    RequestInsurance,
 
     /* Inventory */
     InvSpaceToSpace(ObjPath, InvSlot, u32, ObjPath, InvSlot), //source object, source slot, source count, dst_container, dst_slot
@@ -35,14 +76,30 @@ pub enum NetIncomingMessage {
     InvRequestInventoryList, //requesting list of all paths/ids with inventories
     InvRequestInventory(InvId), //requesting dump of specific station inventory
     InvRequestShip(ObjPath), // requesting ship onboard inventory
-    InvRequestGameObject(ObjPath), // TODO: figure out rules for this
+    InvRequestGameObject(ObjPath), // requesting dump of a container in space (structures the player owns, wrecks and jettisoned containers)
+    Jettison(ObjPath, InvSlot, u32), //ship, cargo slot, count (dropped in to a new container behind the ship)
+
+    /* Refining */
+    RefineOre(InvId, InvSlot, u32), //station inventory, slot holding the ore, count
+
+    /* Production */
+    StartProductionJob(InvId, ItemId, u32), //station inventory, blueprint item, runs
+    RequestProductionJobs,
+
+    /* Skills */
+    QueueSkill(SkillId), //trains the next level of the skill after everything already queued
+    RemoveQueuedSkill(u32), //queue position, later levels of the skill and anything depending on it are removed too
+    RequestSkills,
 
     /* Market */
-    PlaceBuyOrder(ItemId, InvId, u32, i64), //item, location, count, price PER ITEM
+    PlaceBuyOrder(ItemId, InvId, u32, i64, OrderRange, u32), //item, location, count, price PER ITEM, range items are accepted from, duration in days
     FulfillBuyOrder(ItemId, u64, InvId, InvSlot, u32), //item, order id, inventory id, inventory slot, count
     CancelBuyOrder(ItemId, u64), //item, order id
-    PlaceSellOrder(InvId, InvSlot, u32, i64), //inventory, slot, count, cost per
+    PlaceSellOrder(InvId, InvSlot, u32, i64, u32), //inventory, slot, count, cost per, duration in days
     FulfillSellOrder(ItemId, u64, u32), //item, order id, count
     CancelSellOrder(ItemId, u64), //item, order id
+    ModifyBuyOrder(ItemId, u64, i64), //item, order id, new price PER ITEM
+    ModifySellOrder(ItemId, u64, i64), //item, order id, new cost per
     GetStore(ItemId), // item id
+    GetMarketHistory(ItemId), // item id
 }
\ No newline at end of file
@@ -8,4 +8,10 @@ pub enum NetOutEvent {
     Dock(ObjPath), //station
     Undock(ObjPath), //ship
     Jump(ObjPath), //new ship path
+    AutopilotRoute(Vec<String>), //systems left to jump through
+    AutopilotArrived(String), //destination system
+    AutopilotDisengaged,
+    WeaponFired(ObjPath, ObjPath, f64), //shooter, target, damage (0 for a missile launch)
+    Destroyed(ObjPath), //destroyed object
+    Death(ObjPath), //home station the player woke up in
 }
\ No newline at end of file
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 use crate::db::PlayerHanger;
 use crate::galaxy::components::HngId;
-use crate::{galaxy::components::{Stats, Ship}, inventory::Inventory};
+use crate::{galaxy::components::{Stats, Ship, Fitting}, inventory::Inventory};
 
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -24,11 +24,12 @@ pub struct SShip {
     pub class: String,
     pub name: String,
     pub stats: Stats,
+    pub fitting: Fitting,
     pub inv: Inventory
 }
 
 impl SShip {
     pub fn from_ship(s: &Ship) -> Self {
-        SShip { class: s.ship_class.clone(), name: s.ship_name.clone(), stats: s.stats.clone(), inv: s.inventory.clone() }
+        SShip { class: s.ship_class.clone(), name: s.ship_name.clone(), stats: s.stats.clone(), fitting: s.fitting.clone(), inv: s.inventory.clone() }
     }
 }
\ No newline at end of file
@@ -1,19 +1,30 @@
 use serde::{Serialize, Deserialize};
 
-use crate::{shared::ObjPath, inventory::{Inventory, InvId}, db::ItemStore, galaxy::galaxy_map::GalaxyMap};
+use crate::{shared::ObjPath, inventory::{Inventory, InvId, ItemId}, db::{ItemStore, Bookmark, ProductionJob, DailyStats, InsuranceContract}, galaxy::galaxy_map::GalaxyMap};
 
-use self::hanger::SHanger;
+use self::{hanger::SHanger, route::SRoute, skills::SSkills};
 
 pub mod hanger;
+pub mod route;
+pub mod skills;
 
 #[derive(Serialize, Deserialize)]
 pub enum NetOutInfo {
     Location(ObjPath),
+    HomeStation(ObjPath),
     Hanger(SHanger),
     Inventory(Inventory, InvId), //inv, inv_id
     Bank(i64), //value
     Store(ItemStore), //store
+    MarketHistory(ItemId, Vec<DailyStats>), //item, one entry per day with trades, oldest first
     GalaxyMap(GalaxyMap),
     InvList(Vec<(ObjPath, InvId)>), // station paths, inv ids
     InventoryGameObject(Inventory, ObjPath), //inv, path
+    Bookmarks(Vec<Bookmark>),
+    Route(SRoute),
+    ProductionJobs(Vec<ProductionJob>),
+    Insurance(Vec<InsuranceContract>),
+    Skills(SSkills),
+    Error(String), //message
+    OrderExpired(ItemId, u64), //item, order id
 }
\ No newline at end of file
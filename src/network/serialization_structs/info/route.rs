@@ -0,0 +1,15 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SRoute {
+    pub start: String,
+    pub end: String,
+    pub systems: Option<Vec<String>>, // systems to jump through, not including the start (None if unreachable)
+    pub jumps: Option<u32>
+}
+
+impl SRoute {
+    pub fn new(start: &str, end: &str, systems: &Option<Vec<String>>) -> Self {
+        SRoute { start: start.to_string(), end: end.to_string(), systems: systems.clone(), jumps: systems.as_ref().map(|s| s.len() as u32) }
+    }
+}
@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{db::PlayerSkills, skills::{SkillId, SkillTable}};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SQueuedSkill {
+    pub skill: SkillId,
+    pub level: u8,
+    pub finishes_at: i64, // unix timestamp
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SSkills {
+    pub levels: HashMap<SkillId, u8>,
+    pub queue: Vec<SQueuedSkill>,
+}
+
+impl SSkills {
+    pub fn new(skills: &PlayerSkills, table: &SkillTable) -> Self {
+        let queue = skills.queue.iter().zip(skills.queue_times(table))
+            .map(|((skill, level), finishes_at)| SQueuedSkill { skill: skill.clone(), level: *level, finishes_at })
+            .collect();
+        SSkills { levels: skills.levels.clone(), queue }
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Serialize, Deserialize};
 
-use crate::{shared::ObjPath, galaxy::components::Transform};
+use crate::{shared::ObjPath, galaxy::components::{Transform, ObjectVisibility}, inventory::ItemId};
 
 
 #[derive(Serialize, Deserialize)]
@@ -34,4 +34,12 @@ pub struct SMoon {
 pub struct SAsteroidBelt {
     pub path: ObjPath,
     pub transform: Transform
-}
\ No newline at end of file
+}
+#[derive(Serialize, Deserialize)]
+pub struct SAsteroid {
+    pub path: ObjPath,
+    pub ore: ItemId,
+    pub remaining: u32,
+    pub transform: Transform,
+    pub vis: ObjectVisibility
+}
@@ -16,6 +16,18 @@ pub enum NetOutState {
     OtherShip(SPlayerShip_OTHER),
     OwnShip(SPlayerShip_OWN),
     LostSight(ObjPath),
+    TargetLocking(ObjPath, f64), //target, seconds until locked
+    TargetLocked(ObjPath),
+    TargetUnlocked(ObjPath),
+    Asteroid(SAsteroid),
+    Extractor(SExtractor), //moon extractors and planet offices
+    Mining(Option<ObjPath>), //asteroid being mined
+    AsteroidRemaining(ObjPath, u32), //asteroid, ore left
+    Missile(SMissile),
+    Wreck(SWreck),
+    Container(SContainer), //jettisoned containers
+    Weapon(u32, Option<ObjPath>), //high slot index, target (None when the weapon stops)
+    Health(ObjPath, Health), //object, current shield/armor/hull
 }
 
 
@@ -99,7 +111,8 @@ impl SSystem {
             if let Ok((s, go, t)) = station_query.get(*e) {
                 let ser_station = SStation {
                     path: go.path.clone(),
-                    transform: t.clone()
+                    transform: t.clone(),
+                    refining_tax: s.refining_tax
                 };
 
                 self.station.push(ser_station);
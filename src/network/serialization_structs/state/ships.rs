@@ -22,4 +22,22 @@ pub struct SPlayerShip_OWN {
     pub nav_action: Action,
     pub nav_target: NavTarget,
     pub warp_state: WarpState
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SMissile {
+    pub path: ObjPath,
+    pub owner: String,
+    pub target: ObjPath,
+    pub transform: Transform,
+    pub vis: ObjectVisibility
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SWreck {
+    pub path: ObjPath,
+    pub owner: String,
+    pub ship_class: String,
+    pub transform: Transform,
+    pub vis: ObjectVisibility
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 use serde::{Serialize, Deserialize};
 
-use crate::{shared::ObjPath, galaxy::components::Transform};
+use crate::{shared::ObjPath, galaxy::components::{Transform, ObjectVisibility}};
 
 #[derive(Serialize, Deserialize)]
 pub struct SGate {
@@ -12,5 +12,22 @@ pub struct SGate {
 #[derive(Serialize, Deserialize)]
 pub struct SStation {
     pub path: ObjPath,
-    pub transform: Transform
+    pub transform: Transform,
+    pub refining_tax: f64
+}
+#[derive(Serialize, Deserialize)]
+pub struct SExtractor {
+    pub path: ObjPath,
+    pub owner: String,
+    pub body: ObjPath,
+    pub transform: Transform,
+    pub vis: ObjectVisibility
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SContainer {
+    pub path: ObjPath,
+    pub owner: String,
+    pub transform: Transform,
+    pub vis: ObjectVisibility
 }
\ No newline at end of file
@@ -0,0 +1,2 @@
+mod skill;
+pub use skill::*;
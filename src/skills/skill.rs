@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::inventory::{StatModifier, Modifier};
+
+pub type SkillTable = HashMap<String, Skill>;
+pub type SkillId = String;
+
+pub const MAX_SKILL_LEVEL: u8 = 5;
+
+// a rank 1 skill takes this long to train to level 1, every level after takes 3x longer than the one before
+const BASE_TRAIN_TIME_S: i64 = 600;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum SkillEffect {
+    Stat(StatModifier), // applied once per level, to every ship the player flies
+    MaxOrders(u32), // extra market orders per level
+    ProductionTime(f64), // fraction shaved off production job durations per level
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Skill {
+    pub id: SkillId,
+    pub name: String,
+    pub rank: u32, // training time multiplier
+    pub prerequisites: Vec<(SkillId, u8)>,
+    pub effects: Vec<SkillEffect>,
+}
+
+impl Skill {
+    /// seconds it takes to go from level - 1 to level
+    pub fn train_time_s(&self, level: u8) -> i64 {
+        BASE_TRAIN_TIME_S * self.rank as i64 * 3i64.pow(level.max(1) as u32 - 1)
+    }
+}
+
+/// modifiers on ship stats from every trained skill, per level modifiers stack (Add scales linearly, Mul compounds)
+pub fn skill_stat_modifiers(table: &SkillTable, levels: &HashMap<SkillId, u8>) -> Vec<StatModifier> {
+    let mut mods = vec![];
+    for (id, level) in levels.iter() {
+        let skill = match table.get(id) { Some(s) => s, None => continue };
+        for e in skill.effects.iter() {
+            if let SkillEffect::Stat(m) = e {
+                let modifier = match m.modifier {
+                    Modifier::Add(v) => Modifier::Add(v * *level as f64),
+                    Modifier::Mul(v) => Modifier::Mul(v.powi(*level as i32)),
+                };
+                mods.push(StatModifier { stat: m.stat, modifier });
+            }
+        }
+    }
+    mods
+}
+
+pub fn skill_max_orders_bonus(table: &SkillTable, levels: &HashMap<SkillId, u8>) -> u32 {
+    levels.iter()
+        .filter_map(|(id, level)| table.get(id).map(|s| (s, *level as u32)))
+        .flat_map(|(s, level)| s.effects.iter().map(move |e| match e { SkillEffect::MaxOrders(n) => n * level, _ => 0 }))
+        .sum()
+}
+
+/// multiplier on production job durations, never goes below 10% of the base time
+pub fn skill_production_time_mult(table: &SkillTable, levels: &HashMap<SkillId, u8>) -> f64 {
+    let reduction: f64 = levels.iter()
+        .filter_map(|(id, level)| table.get(id).map(|s| (s, *level as f64)))
+        .flat_map(|(s, level)| s.effects.iter().map(move |e| match e { SkillEffect::ProductionTime(f) => f * level, _ => 0.0 }))
+        .sum();
+    (1.0 - reduction).max(0.1)
+}
@@ -1,4 +1,4 @@
-use crate::{galaxy::{Galaxy, resources::{database_resource::DatabaseResource, path_to_entity::PathToEntityMap}, components::{Ship, Stats, Hanger, Station}}, network::{server::ServerHandle, self}, shared::{ObjPath, self}, config::Config, db, inventory::{Inventory, Stack}};
+use crate::{galaxy::{Galaxy, resources::{database_resource::DatabaseResource, path_to_entity::PathToEntityMap}, components::{Ship, Hanger}}, network::{server::ServerHandle, self}, shared::{ObjPath, self}, config::Config, db, inventory::Stack};
 
 /// returns if login was successful
 pub fn handle_new_player(gal: &Galaxy, name: &String, token: &String, server: &ServerHandle, config: &Config) -> bool{
@@ -6,6 +6,11 @@ pub fn handle_new_player(gal: &Galaxy, name: &String, token: &String, server: &S
     match db.account_try_login(&name, &token) {
         db::database::LoginStatus::Good => { server.send_message_to_player(name.clone(), crate::network::messages::outgoing::NetOutgoingMessage::LoginOk); true },
         db::database::LoginStatus::BadPass => { server.send_message_to_player(name.clone(), network::messages::outgoing::NetOutgoingMessage::LoginBad); false },
+        db::database::LoginStatus::NoAccount if *name == config.gameplay_config.market_sink_account => {
+            // the sink only has a bank account, creating a player over it would wipe the collected fees
+            server.send_message_to_player(name.clone(), network::messages::outgoing::NetOutgoingMessage::LoginBad);
+            false
+        },
         db::database::LoginStatus::NoAccount => {
             let starter_station_path = ObjPath::new(&config.gameplay_config.starting_system, shared::ObjectType::Station, &config.gameplay_config.starting_station);
             let starter_station = gal.world.get_resource::<PathToEntityMap>().expect("Could not get path to entity map for new player").get(&starter_station_path).expect("Starter station not found in world");
@@ -15,22 +20,12 @@ pub fn handle_new_player(gal: &Galaxy, name: &String, token: &String, server: &S
             db.bank_apply_transaction(&name, config.gameplay_config.starting_money, String::from("Starting money"));
             db.market_add_player_index(&name);
             db.inventory_ensure(&name, sh.hanger_uid.clone());
-            let mut ship_inv = Inventory::new(None, Some(10000));
-            ship_inv.insert_stack(Stack::new("haxonite".to_string(), 200));
-            ship_inv.insert_stack(Stack::new("hapkeite".to_string(), 200));
-            ship_inv.insert_stack(Stack::new("wolframite".to_string(), 200));
-            db.hanger_add_ship(&name, sh.hanger_uid.clone(), Ship { 
-                ship_name: String::from("New ship"),
-                ship_class: String::from("Test Ship"),
-                stats: Stats {
-                    warp_speed_ms: 1.496e11,
-                    thrust_n: 100.0,
-                    ang_vel_rads: 1.0,
-                    mass_kg: 10.0,
-                    warp_spool_s: 5.0
-                },
-                inventory: ship_inv
-            });
+            let class = db.ship_class_table.get(&config.gameplay_config.starting_ship_class).expect("Starting ship class not found");
+            let mut ship = Ship::new(db.ship_new_id(), String::from("New ship"), class);
+            ship.inventory.insert_stack(Stack::new("haxonite".to_string(), 200));
+            ship.inventory.insert_stack(Stack::new("hapkeite".to_string(), 200));
+            ship.inventory.insert_stack(Stack::new("wolframite".to_string(), 200));
+            db.hanger_add_ship(name, sh.hanger_uid.clone(), ship);
 
             let cur_hanger = db.hanger_get_ships(&name, sh.hanger_uid.clone()).expect("Could not get ships from new player hanger");
             let slot = cur_hanger.inventory.keys().last().expect("Could not get last key in hanger");